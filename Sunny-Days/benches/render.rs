@@ -0,0 +1,71 @@
+//! How long a frame takes to draw as the level fills up with people.
+//!
+//!     cargo bench --bench render
+//!
+//! Lookups by position go through each level's occupancy grid, so the time per frame (and per
+//! sweep of `npc_at` over the whole map) should stay flat however many villagers are added.
+
+use sunny_days::{render, Action, Npc, NpcId, Theme, Tile, World};
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use ratatui::{backend::TestBackend, Terminal};
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SEED: u64 = 42;
+const CROWDS: [usize; 5] = [0, 10, 50, 200, 500];
+const FRAMES: u32 = 300;
+const SWEEPS: u32 = 300;
+
+/// The seeded world past the intro, with `extra` villagers on free floor tiles of the first room.
+fn crowded_world(extra: usize) -> World {
+    let mut w = World::new(SEED, 80, 45);
+    w.apply_action(Action::Confirm);
+    w.apply_action(Action::Confirm);
+
+    let map = w.current_map();
+    let mut free: Vec<(i32, i32)> = (0..map.height as i32)
+        .flat_map(|y| (0..map.width as i32).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.get(x as usize, y as usize) == Tile::Floor)
+        .filter(|&(x, y)| (x, y) != w.player_pos() && w.npc_at(0, x, y).is_none())
+        .collect();
+    free.shuffle(&mut StdRng::seed_from_u64(SEED));
+    assert!(free.len() >= extra, "only {} free tiles for {} villagers", free.len(), extra);
+
+    for &(x, y) in &free[..extra] {
+        w.add_npc(Npc { id: NpcId::Random1, name: "Villager".to_string(), room: 0, x, y, symbol: '●' });
+    }
+    w
+}
+
+fn per(total: Duration, n: u32) -> f64 {
+    total.as_secs_f64() * 1e6 / n as f64
+}
+
+fn main() {
+    let theme = Theme::default();
+    println!("{:>8} {:>14} {:>16}", "npcs", "us/frame", "us/npc_at sweep");
+    for extra in CROWDS {
+        let w = crowded_world(extra);
+        let mut term = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        for _ in 0..FRAMES / 10 { term.draw(|f| render(f, &w, &theme)).unwrap(); }
+
+        let started = Instant::now();
+        for _ in 0..FRAMES { term.draw(|f| render(f, &w, &theme)).unwrap(); }
+        let frame = per(started.elapsed(), FRAMES);
+
+        let map = w.current_map();
+        let started = Instant::now();
+        for _ in 0..SWEEPS {
+            for y in 0..map.height as i32 {
+                for x in 0..map.width as i32 { black_box(w.npc_at(0, x, y)); }
+            }
+        }
+        let sweep = per(started.elapsed(), SWEEPS);
+
+        println!("{:>8} {:>14.1} {:>16.1}", w.npcs().len(), frame, sweep);
+    }
+}
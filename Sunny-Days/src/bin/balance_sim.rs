@@ -0,0 +1,159 @@
+//! Runs thousands of seeded battles for player builds against each enemy and prints how they go.
+//!
+//!     cargo run --release --bin balance_sim -- --build basic-shield --enemy mayor-sol
+
+use sunny_days::balance::{self, Build, Policy, Report, Sim};
+use sunny_days::{NpcId, World};
+
+const USAGE: &str = "\
+Usage: balance_sim [OPTIONS]
+
+Fights every build against every enemy and reports win rate, turns taken, HP left on a win,
+and how often running from the first turn gets you away alive.
+
+Options:
+  --battles <n>              Battles per build and enemy (default: 2000)
+  --seed <n>                 World seed; battle i rolls its dice from seed + i (default: 1)
+  --build <name>             Only this preset (repeatable): bare, basic-sword, basic-shield,
+                             shield-axe, endgame
+  --enemy <name>             Only this enemy (repeatable): shab, krad, mah, mayor-sol
+  --gear <item,item,..>      Add a custom build wearing these items
+  --consumables <item*n,..>  ...carrying these, e.g. \"Sunny Jerky*3,Fiery ale\"
+  --wins <n>                 ...with this many battles already won (+3 ATK/DEF/SPD each)
+  --policy <p>               ...using consumables: never, heal:<pct>, buff or buff+heal:<pct>
+  --list                     List the known items and exit
+  -h, --help                 Show this help";
+
+struct Options {
+    battles: u32,
+    seed: u64,
+    builds: Vec<Build>,
+    enemies: Vec<NpcId>,
+}
+
+fn parse(args: Vec<String>) -> Result<Options, String> {
+    let mut battles = 2000;
+    let mut seed = 1;
+    let mut presets = Vec::new();
+    let mut enemies = Vec::new();
+    let mut custom: Option<Build> = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--battles" => battles = value()?.parse().map_err(|_| "--battles needs a number".to_string())?,
+            "--seed" => seed = value()?.parse().map_err(|_| "--seed needs a number".to_string())?,
+            "--build" => {
+                let name = value()?;
+                let build = Build::presets().into_iter().find(|b| b.name == name).ok_or(format!("unknown build: {}", name))?;
+                presets.push(build);
+            }
+            "--enemy" => {
+                let name = value()?;
+                enemies.push(balance::named_enemy(&name).ok_or(format!("unknown enemy: {}", name))?);
+            }
+            "--gear" => {
+                let mut build = custom.take().unwrap_or_else(|| Build::new("custom"));
+                for name in value()?.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    build = build.with_gear(balance::named_gear(name).ok_or(format!("unknown item: {} (see --list)", name))?);
+                }
+                custom = Some(build);
+            }
+            "--consumables" => {
+                let mut build = custom.take().unwrap_or_else(|| Build::new("custom"));
+                for entry in value()?.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                    let (name, qty) = match entry.rsplit_once('*') {
+                        Some((name, qty)) => (name.trim(), qty.trim().parse().map_err(|_| format!("bad count in {}", entry))?),
+                        None => (entry, 1),
+                    };
+                    let item = balance::named_consumable(name).ok_or(format!("unknown consumable: {} (see --list)", name))?;
+                    build = build.with_consumables(item, qty);
+                }
+                custom = Some(build);
+            }
+            "--wins" => {
+                let wins = value()?.parse().map_err(|_| "--wins needs a number".to_string())?;
+                custom = Some(custom.take().unwrap_or_else(|| Build::new("custom")).with_wins(wins));
+            }
+            "--policy" => {
+                let text = value()?;
+                let policy = Policy::parse(&text).ok_or(format!("unknown policy: {}", text))?;
+                custom = Some(custom.take().unwrap_or_else(|| Build::new("custom")).with_policy(policy));
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    // A custom build runs on its own unless presets were asked for too.
+    let mut builds = presets;
+    match custom {
+        Some(build) => builds.push(build),
+        None if builds.is_empty() => builds = Build::presets(),
+        None => {}
+    }
+    if enemies.is_empty() { enemies = World::ENEMIES.to_vec(); }
+    Ok(Options { battles, seed, builds, enemies })
+}
+
+fn describe(build: &Build) -> String {
+    let p = build.player_at(0, 0);
+    let gear = if build.gear.is_empty() {
+        "no gear".to_string()
+    } else {
+        build.gear.iter().map(|e| e.name.as_str()).collect::<Vec<_>>().join(", ")
+    };
+    let pack = if build.consumables.is_empty() {
+        "no consumables".to_string()
+    } else {
+        build.consumables.iter().map(|(c, n)| format!("{} x{}", c.name, n)).collect::<Vec<_>>().join(", ")
+    };
+    format!(
+        "{}: HP {}  ATK {}  DEF {}  SPD {} | {} | {} | {}",
+        build.name, p.max_hp, p.attack(), p.defense(), p.speed(), gear, pack, build.policy.describe()
+    )
+}
+
+fn row(enemy: NpcId, r: &Report) -> String {
+    let name = World::enemy_stats(enemy).map_or("?", |s| s.0);
+    format!(
+        "  {:<10} {:>6.1} {:>6.1} {:>8.1} {:>11} {:>8.1}",
+        name,
+        100.0 * r.win_rate(),
+        r.avg_turns(),
+        r.avg_hp_left(),
+        r.stalemates,
+        100.0 * r.escape_rate()
+    )
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return;
+    }
+    if args.iter().any(|a| a == "--list") {
+        println!("Gear: {}", balance::known_gear().join(", "));
+        println!("Consumables: {}", balance::known_consumables().join(", "));
+        return;
+    }
+
+    let opts = match parse(args) {
+        Ok(o) => o,
+        Err(msg) => {
+            eprintln!("{msg}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    let sim = Sim::new(opts.seed);
+    for build in &opts.builds {
+        println!("{}", describe(build));
+        println!("  {:<10} {:>6} {:>6} {:>8} {:>11} {:>8}", "enemy", "win%", "turns", "HP left", "stalemates", "escape%");
+        for &enemy in &opts.enemies {
+            println!("{}", row(enemy, &sim.run(build, enemy, opts.battles, opts.seed)));
+        }
+        println!();
+    }
+}
@@ -0,0 +1,70 @@
+//! Lets the bot play a range of seeds and reports which ones it couldn't finish, and why.
+//!
+//!     cargo run --release --bin bot -- --seeds 200
+
+use sunny_days::bot::{self, Run};
+
+const USAGE: &str = "\
+Usage: bot [OPTIONS]
+
+Plays the story headlessly for each seed and reports whether it reaches the end.
+Exits with status 1 if any seed got stuck.
+
+Options:
+  --seeds <n>         How many seeds to play (default: 50)
+  --from <n>          First seed (default: 1)
+  --max-actions <n>   Give up on a seed after this many inputs (default: 50000)
+  -q, --quiet         Only print seeds that got stuck, and the summary
+  -h, --help          Show this help";
+
+fn describe(run: &Run) -> String {
+    let (room, x, y) = run.at;
+    match &run.stuck {
+        None => format!("seed {}: finished in {} actions", run.seed, run.actions),
+        Some(why) => format!("seed {}: STUCK after {} actions, {} (room {} at {}, {})", run.seed, run.actions, why, room + 1, x, y),
+    }
+}
+
+fn main() {
+    let mut seeds = 50u64;
+    let mut from = 1u64;
+    let mut max_actions = 50_000;
+    let mut quiet = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = |what: &str| -> u64 {
+            match args.next().and_then(|v| v.parse().ok()) {
+                Some(n) => n,
+                None => {
+                    eprintln!("{} needs a number\n\n{}", what, USAGE);
+                    std::process::exit(2);
+                }
+            }
+        };
+        match arg.as_str() {
+            "--seeds" => seeds = number("--seeds"),
+            "--from" => from = number("--from"),
+            "--max-actions" => max_actions = number("--max-actions") as u32,
+            "-q" | "--quiet" => quiet = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return;
+            }
+            _ => {
+                eprintln!("unknown argument: {}\n\n{}", arg, USAGE);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let (mut stuck, mut died) = (0, 0);
+    for seed in from..from + seeds {
+        let run = bot::play(seed, max_actions);
+        if !run.finished { stuck += 1; }
+        if run.died { died += 1; }
+        if !quiet || !run.finished { println!("{}", describe(&run)); }
+    }
+    println!("{}/{} seeds finished; {} lost a fight, {} got stuck.", seeds - stuck, seeds, died, stuck - died);
+    if stuck > 0 { std::process::exit(1); }
+}
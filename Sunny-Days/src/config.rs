@@ -0,0 +1,168 @@
+use crate::engine::settings::{Difficulty, Settings};
+use crate::tui::theme::{Theme, ThemeName};
+
+use std::{fs, path::PathBuf};
+
+/// `--help` text for the `Sunny-Days` binary.
+pub const USAGE: &str = "\
+Usage: Sunny-Days [OPTIONS]
+
+Options:
+  --difficulty <story|normal|hard>  Battle timer preset (default: normal)
+  --battle-timer <secs>             Override the battle inactivity timer
+  --no-battle-timer                 Disable the battle inactivity timer
+  --theme <name>                    default, high-contrast, deuteranopia or monochrome
+  --ascii                           Draw with ASCII characters only
+  --carry-limit <weight>            Limit how much weight you can carry (0: slots only)
+  --config <file>                   Read settings from <file> (default: sunny-days.conf)
+  --seed <n>                        Start from a fixed world seed instead of a random one
+  --record <file>                   Save every input to <file> for replaying later
+  --replay <file>                   Play back a recording and check it ends the same way
+  --speed <x>                       Replay speed multiplier (default: 1, 0: as fast as possible)
+  --dev                             Developer mode: F1 shows hidden state, ` opens a console
+  -h, --help                        Show this help
+
+The config file uses the same names, one `key = value` per line:
+  difficulty = story
+  theme = monochrome
+  ascii = true";
+
+const DEFAULT_CONFIG_FILE: &str = "sunny-days.conf";
+
+/// Everything the frontend can be told at startup.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub(crate) settings: Settings,
+    pub(crate) theme: ThemeName,
+    pub(crate) ascii: bool,
+    pub(crate) seed: Option<u64>,
+    pub(crate) record: Option<PathBuf>,
+    pub(crate) replay: Option<PathBuf>,
+    pub(crate) replay_speed: f32,
+    /// State overlay and command console.
+    pub(crate) dev: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            theme: ThemeName::Default,
+            ascii: false,
+            seed: None,
+            record: None,
+            replay: None,
+            replay_speed: 1.0,
+            dev: false,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file (if any), then apply command-line arguments (without the program name) over it.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        // Split into options first, so a value that happens to read "--config" isn't taken for one.
+        let mut options = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(key) = arg.strip_prefix("--") else {
+                return Err(format!("unknown argument: {}\n\n{}", arg, USAGE));
+            };
+            let value = match key {
+                "no-battle-timer" => "0".to_string(),
+                "ascii" | "dev" => "true".to_string(),
+                _ => args.next().ok_or(format!("--{} needs a value", key))?,
+            };
+            let key = if key == "no-battle-timer" { "battle-timer" } else { key };
+            options.push((key.to_string(), value));
+        }
+
+        let mut config = match options.iter().rfind(|(key, _)| key == "config") {
+            Some((_, path)) => Self::from_file(&PathBuf::from(path), true)?,
+            None => Self::from_file(&PathBuf::from(DEFAULT_CONFIG_FILE), false)?,
+        };
+        for (key, value) in options.iter().filter(|(key, _)| key != "config") {
+            config.apply(key, value).map_err(|e| format!("{}\n\n{}", e, USAGE))?;
+        }
+
+        // Console commands bypass the inputs a recording is made of.
+        if config.dev && (config.record.is_some() || config.replay.is_some()) {
+            return Err("--dev can't be combined with --record or --replay".to_string());
+        }
+        Ok(config)
+    }
+
+    fn from_file(path: &PathBuf, required: bool) -> Result<Self, String> {
+        let mut config = Self::default();
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(_) if !required => return Ok(config),
+            Err(e) => return Err(format!("cannot read {}: {}", path.display(), e)),
+        };
+
+        for (n, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or("").trim();
+            if line.is_empty() { continue; }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(format!("{}:{}: expected `key = value`", path.display(), n + 1))?;
+            config
+                .apply(&key.trim().replace('_', "-"), value.trim())
+                .map_err(|e| format!("{}:{}: {}", path.display(), n + 1, e))?;
+        }
+        Ok(config)
+    }
+
+    fn apply(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "difficulty" => {
+                let d = Difficulty::from_name(value).ok_or(format!("unknown difficulty: {}", value))?;
+                // An explicit battle-timer value survives a later difficulty change.
+                let timer = self.settings.battle_timer_secs;
+                let customised = timer != Settings::for_difficulty(self.settings.difficulty).battle_timer_secs;
+                let carry_limit = self.settings.carry_limit;
+                self.settings = Settings::for_difficulty(d);
+                self.settings.carry_limit = carry_limit;
+                if customised { self.settings.battle_timer_secs = timer; }
+            }
+            "battle-timer" => {
+                let secs = value.parse::<u32>().map_err(|_| format!("invalid seconds: {}", value))?;
+                self.settings.battle_timer_secs = if secs == 0 { None } else { Some(secs) };
+            }
+            "carry-limit" => {
+                let weight = value.parse::<u32>().map_err(|_| format!("invalid weight: {}", value))?;
+                self.settings.carry_limit = if weight == 0 { None } else { Some(weight) };
+            }
+            "theme" => {
+                self.theme = ThemeName::from_name(value).ok_or(format!(
+                    "unknown theme: {} (expected one of: {})",
+                    value,
+                    ThemeName::ALL.map(|t| t.name()).join(", ")
+                ))?;
+            }
+            "ascii" => self.ascii = parse_bool(value)?,
+            "dev" => self.dev = parse_bool(value)?,
+            "seed" => self.seed = Some(value.parse().map_err(|_| format!("invalid seed: {}", value))?),
+            "record" => self.record = Some(PathBuf::from(value)),
+            "replay" => self.replay = Some(PathBuf::from(value)),
+            "speed" => {
+                let speed = value.parse::<f32>().ok().filter(|s| s.is_finite() && *s >= 0.0);
+                self.replay_speed = speed.ok_or(format!("invalid speed: {}", value))?;
+            }
+            _ => return Err(format!("unknown option: {}", key)),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn theme(&self) -> Theme {
+        Theme::new(self.theme, self.ascii)
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("invalid boolean: {}", value)),
+    }
+}
@@ -1,23 +1,52 @@
-#[derive(Debug, Clone, Copy)]
+/// One player input, already mapped from whatever key produced it.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
+    /// Step by (dx, dy).
     Move(i32, i32),
 
+    /// I opens and closes the inventory.
     ToggleInventory,
-    ToggleInvTab,   // NEW: T/t cycles inventory tab
+    /// T cycles the inventory tab.
+    ToggleInvTab,
 
+    /// Cursor up in the inventory or shop list.
     InventoryUp,
+    /// Cursor down in the inventory or shop list.
     InventoryDown,
-    UseConsumable, // also unequip when hovering sword/shield
+    /// Use the selected item; on gear, equip or unequip it.
+    UseConsumable,
+    /// E toggles the detail panel for the selected item.
+    InspectItem,
+    /// D drops it on the current tile.
+    DropItem,
+    /// O sorts by name, type or stat.
+    CycleSort,
+    /// F shows everything or one item type.
+    CycleFilter,
+    /// C combines the items marked on the Crafting tab.
+    Craft,
 
+    /// Q opens and closes the stats panel.
     ToggleStats,
+    /// M shows the whole level scaled down.
+    ToggleOverview,
 
+    /// Enter: next page, or on to the next screen.
     Confirm,
+    /// E talks, opens, uses or picks up whatever is next to you.
     Interact,
+    /// A letter answering the question on screen.
     Choice(char),
+    /// Esc/Q walks away from Tajer's counter.
+    LeaveShop,
 
-    // NEW: Battle Option (1=Fight, 2=Inv, 3=Run). bool = 10s penalty active
-    BattleOption(u8, bool), 
+    /// 1 fights, 2 opens the pack, 3 tries to run. Hesitation penalty is tracked by the engine.
+    BattleOption(u8),
+    /// P pauses/resumes the battle inactivity timer.
+    ToggleBattleTimer,
 
-    Quit, // Ctrl+C / Ctrl+Q
+    /// Ctrl+C / Ctrl+Q.
+    Quit,
+    /// A key that does nothing.
     None,
 }
\ No newline at end of file
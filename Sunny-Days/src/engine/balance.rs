@@ -0,0 +1,328 @@
+//! Batch battle simulation, for tuning enemy stats against player builds.
+//!
+//! Every simulated battle is a real one: a copy of a template world with the build put on the
+//! player, the combat dice reseeded, and `Action`s sent through `apply_action` until someone
+//! drops. A turn is taken to last `TURN_SECS` of play, so consumable buffs wear off as they
+//! would in the game.
+
+use crate::engine::action::Action;
+use crate::engine::clock::GameClock;
+use crate::engine::entity::{Consumable, Equipment, Player};
+use crate::engine::loot;
+use crate::engine::world::{NpcId, World};
+
+const TURN_SECS: u32 = 2;
+/// Fights still going after this many turns count as stalemates.
+const MAX_TURNS: u32 = 300;
+/// What each battle won adds to attack, defense and speed (see `World::handle_win`).
+const WIN_BONUS: i32 = 3;
+
+/// When to reach for consumables mid-fight. Each one costs a turn: the enemy swings meanwhile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Policy {
+    /// Eat the biggest heal on hand once HP drops below this percent of max.
+    pub heal_below: Option<i32>,
+    /// Take one of each attack or defense buff before the first swing.
+    pub buff_first: bool,
+}
+
+impl Policy {
+    /// `never`, `heal:<percent>`, `buff`, or both joined with `+` (`buff+heal:40`).
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut policy = Policy::default();
+        for part in text.split('+') {
+            match part.split_once(':') {
+                Some(("heal", pct)) => policy.heal_below = Some(pct.parse().ok().filter(|p| (1..=100).contains(p))?),
+                None if part == "buff" => policy.buff_first = true,
+                None if part == "never" => {}
+                _ => return None,
+            }
+        }
+        Some(policy)
+    }
+
+    /// A short label for reports, e.g. "buffs first, heal below 40%".
+    pub fn describe(&self) -> String {
+        match (self.buff_first, self.heal_below) {
+            (false, None) => "no consumables used".to_string(),
+            (true, None) => "buffs first".to_string(),
+            (false, Some(p)) => format!("heal below {}%", p),
+            (true, Some(p)) => format!("buffs first, heal below {}%", p),
+        }
+    }
+}
+
+/// A player to send into battle.
+#[derive(Debug, Clone)]
+pub struct Build {
+    /// Shown in reports.
+    pub name: String,
+    /// Battles already won; each one added +3 attack, defense and speed.
+    pub wins: i32,
+    /// Equipped in order; a later piece for the same slot replaces an earlier one.
+    pub gear: Vec<Equipment>,
+    /// Carried in the pack, with how many of each.
+    pub consumables: Vec<(Consumable, u32)>,
+    /// When the consumables get used.
+    pub policy: Policy,
+}
+
+impl Build {
+    /// Starting stats, empty hands.
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), wins: 0, gear: Vec::new(), consumables: Vec::new(), policy: Policy::default() }
+    }
+
+    /// Adds a piece of gear.
+    pub fn with_gear(mut self, eq: Equipment) -> Self {
+        self.gear.push(eq);
+        self
+    }
+
+    /// Adds `qty` of a consumable to the pack.
+    pub fn with_consumables(mut self, item: Consumable, qty: u32) -> Self {
+        self.consumables.push((item, qty));
+        self
+    }
+
+    /// Sets how many battles have already been won.
+    pub fn with_wins(mut self, wins: i32) -> Self {
+        self.wins = wins;
+        self
+    }
+
+    /// Sets when consumables get used.
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The routes through the story worth comparing.
+    pub fn presets() -> Vec<Build> {
+        let jerky = named_consumable("Sunny Jerky").expect("Sunny Jerky is a known consumable");
+        vec![
+            Build::new("bare"),
+            // Lamp offers one or the other.
+            Build::new("basic-sword").with_gear(World::basic_sword()),
+            Build::new("basic-shield").with_gear(World::basic_shield()),
+            // The shield route after Shab, Krad and Mah, with Dorosht's axe.
+            Build::new("shield-axe").with_wins(3).with_gear(World::basic_shield()).with_gear(World::willow_axe()),
+            // Everything the story hands out, plus supplies.
+            Build::new("endgame")
+                .with_wins(3)
+                .with_gear(World::willow_axe())
+                .with_gear(World::shield_of_healing())
+                .with_consumables(jerky, 3)
+                .with_policy(Policy { heal_below: Some(50), buff_first: false }),
+        ]
+    }
+
+    /// A full-health player at `(x, y)` with this build's stats, gear and pack, and nothing else.
+    pub fn player_at(&self, x: i32, y: i32) -> Player {
+        let mut p = Player::new(x, y);
+        p.base_attack += WIN_BONUS * self.wins;
+        p.base_defense += WIN_BONUS * self.wins;
+        p.base_speed += WIN_BONUS * self.wins;
+        for eq in &self.gear { p.equip(eq.clone()); }
+        for (item, qty) in &self.consumables {
+            for _ in 0..*qty { let _ = p.inventory.add_consumable(item.clone()); }
+        }
+        p.hp = p.max_hp;
+        p
+    }
+}
+
+/// The gear the story hands out by name.
+fn story_gear() -> [Equipment; 5] {
+    [World::basic_sword(), World::basic_shield(), World::willow_axe(), World::shield_of_healing(), World::weeping_dagger()]
+}
+
+/// Names of every item a build can be given.
+pub fn known_gear() -> Vec<String> {
+    story_gear().into_iter().map(|e| e.name).chain(loot::base_names().map(String::from)).collect()
+}
+
+/// A story item, or a plain base item from the loot tables. Case doesn't matter.
+pub fn named_gear(name: &str) -> Option<Equipment> {
+    story_gear().into_iter().find(|e| e.name.eq_ignore_ascii_case(name)).or_else(|| loot::base_item(name))
+}
+
+/// Names of every consumable a build can carry.
+pub fn known_consumables() -> Vec<String> {
+    loot::consumables().into_iter().map(|c| c.name).collect()
+}
+
+/// A consumable from the loot tables by name. Case doesn't matter.
+pub fn named_consumable(name: &str) -> Option<Consumable> {
+    loot::consumables().into_iter().find(|c| c.name.eq_ignore_ascii_case(name))
+}
+
+/// An enemy by name: "shab", "mah", "mayor-sol" (or "mayor").
+pub fn named_enemy(name: &str) -> Option<NpcId> {
+    let name = name.to_ascii_lowercase();
+    World::ENEMIES.into_iter().find(|&id| {
+        let full = World::enemy_stats(id).map_or(String::new(), |s| s.0.to_ascii_lowercase().replace(' ', "-"));
+        full == name || full.split('-').next() == Some(name.as_str())
+    })
+}
+
+/// How a single battle ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// The enemy dropped.
+    Won {
+        /// Turns the fight took.
+        turns: u32,
+        /// The player's HP at the end.
+        hp_left: i32,
+    },
+    /// The player dropped.
+    Lost {
+        /// Turns the fight took.
+        turns: u32,
+    },
+    /// The player got away.
+    Fled {
+        /// Turns it took to get away.
+        turns: u32,
+    },
+    /// Nobody dropped within `MAX_TURNS`.
+    Stalemate,
+}
+
+/// Totals over a batch of battles against one enemy.
+#[derive(Debug, Clone, Default)]
+pub struct Report {
+    /// Fights to the end; as many again were spent running.
+    pub battles: u32,
+    /// Fights the player won.
+    pub wins: u32,
+    /// Fights still going after `MAX_TURNS`.
+    pub stalemates: u32,
+    /// Turns, summed over every fight that ended.
+    pub turns: u64,
+    /// HP left, summed over the wins.
+    pub hp_left: i64,
+    /// Of as many fights spent running from the first turn, how many got away alive.
+    pub escapes: u32,
+}
+
+impl Report {
+    /// Wins over battles.
+    pub fn win_rate(&self) -> f64 {
+        ratio(self.wins as f64, self.battles as f64)
+    }
+
+    /// Turns per fight that ended.
+    pub fn avg_turns(&self) -> f64 {
+        ratio(self.turns as f64, (self.battles - self.stalemates) as f64)
+    }
+
+    /// HP left per win.
+    pub fn avg_hp_left(&self) -> f64 {
+        ratio(self.hp_left as f64, self.wins as f64)
+    }
+
+    /// Escapes over battles.
+    pub fn escape_rate(&self) -> f64 {
+        ratio(self.escapes as f64, self.battles as f64)
+    }
+}
+
+fn ratio(a: f64, b: f64) -> f64 {
+    if b == 0.0 { 0.0 } else { a / b }
+}
+
+/// Runs battles against copies of one template world.
+pub struct Sim {
+    template: World,
+}
+
+impl Sim {
+    /// The template is the world from `seed`, past the title screens.
+    pub fn new(seed: u64) -> Self {
+        let mut template = World::new(seed, 80, 45);
+        template.apply_action(Action::Confirm);
+        template.apply_action(Action::Confirm);
+        Self { template }
+    }
+
+    /// `battles` fights to the end, then as many spent running, on dice seeds `seed`, `seed + 1`, ...
+    pub fn run(&self, build: &Build, enemy: NpcId, battles: u32, seed: u64) -> Report {
+        let mut report = Report { battles, ..Report::default() };
+        for i in 0..battles as u64 {
+            match self.fight(build, enemy, seed.wrapping_add(i)) {
+                Outcome::Won { turns, hp_left } => {
+                    report.wins += 1;
+                    report.turns += turns as u64;
+                    report.hp_left += hp_left as i64;
+                }
+                Outcome::Lost { turns } | Outcome::Fled { turns } => report.turns += turns as u64,
+                Outcome::Stalemate => report.stalemates += 1,
+            }
+            if let Outcome::Fled { .. } = self.flee(build, enemy, seed.wrapping_add(i)) {
+                report.escapes += 1;
+            }
+        }
+        report
+    }
+
+    /// Swing every turn (using consumables as the build's policy says) until someone drops.
+    pub fn fight(&self, build: &Build, enemy: NpcId, seed: u64) -> Outcome {
+        self.battle(build, enemy, seed, 1)
+    }
+
+    /// Try to run every turn until away or dead. Buffs are no use on the way out; heals still are.
+    pub fn flee(&self, build: &Build, enemy: NpcId, seed: u64) -> Outcome {
+        self.battle(build, enemy, seed, 3)
+    }
+
+    fn battle(&self, build: &Build, enemy: NpcId, seed: u64, option: u8) -> Outcome {
+        let mut w = self.template.clone();
+        w.player = build.player_at(w.player.x, w.player.y);
+        w.reseed_dice(seed);
+        w.start_battle(enemy);
+        let policy = if option == 3 { Policy { buff_first: false, ..build.policy } } else { build.policy };
+        let mut drunk: Vec<String> = Vec::new();
+
+        for turn in 1..=MAX_TURNS {
+            match pick_consumable(&w.player, &policy, &mut drunk) {
+                Some(name) => use_consumable(&mut w, &name),
+                None => { w.apply_action(Action::BattleOption(option)); }
+            }
+            if w.battle.is_none() {
+                return match w.last_battle {
+                    Some(end) if end.won => Outcome::Won { turns: turn, hp_left: end.hp_left },
+                    Some(end) if end.fled => Outcome::Fled { turns: turn },
+                    _ => Outcome::Lost { turns: turn },
+                };
+            }
+            for _ in 0..GameClock::secs(TURN_SECS) { w.tick(); }
+        }
+        Outcome::Stalemate
+    }
+}
+
+/// What the policy reaches for this turn, if anything.
+fn pick_consumable(player: &Player, policy: &Policy, drunk: &mut Vec<String>) -> Option<String> {
+    let stacks = &player.inventory.consumables;
+    if policy.buff_first {
+        let buff = stacks.iter().find(|s| (s.item.atk_bonus > 0 || s.item.def_bonus > 0) && !drunk.contains(&s.item.name));
+        if let Some(s) = buff {
+            drunk.push(s.item.name.clone());
+            return Some(s.item.name.clone());
+        }
+    }
+    let pct = policy.heal_below?;
+    if player.hp * 100 >= player.max_hp * pct { return None; }
+    stacks.iter().filter(|s| s.item.heal > 0).max_by_key(|s| s.item.heal).map(|s| s.item.name.clone())
+}
+
+/// Open the pack from the battle menu and use `name`, the way a player would.
+fn use_consumable(w: &mut World, name: &str) {
+    w.apply_action(Action::BattleOption(2));
+    let inv = &mut w.player.inventory;
+    let Some(pos) = inv.visible_consumables().iter().position(|&i| inv.consumables[i].item.name == name) else { return };
+    inv.consumable_cursor = pos;
+    w.apply_action(Action::UseConsumable);
+}
@@ -0,0 +1,416 @@
+//! A bot that plays the story start to finish, to catch progression blockers.
+//!
+//! It only sends `Action`s, like a player at the keyboard, and only walks where it has seen:
+//! paths run over explored tiles and model what a step really does (ice slides included, lava
+//! avoided where possible). Someone it hasn't seen yet is found by walking to the nearest edge
+//! of the explored map. On the way it opens the chests it passes, wears the best gear it has,
+//! and eats or drinks from a fountain before a fight, then takes its buffs next to the enemy.
+//! A fight that's going badly is fled if there's a way to heal up afterwards; otherwise the bot
+//! eats mid-fight when the food out-heals the enemy's worst hit.
+
+use crate::engine::action::Action;
+use crate::engine::entity::{Equipment, GearSlot, InvTab};
+use crate::engine::objects::ObjectKind;
+use crate::engine::world::{AwaitingChoice, GameState, NpcId, StoryFlags, World};
+use crate::map::tile::Tile;
+
+use std::collections::VecDeque;
+
+const DIRS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+/// Known chests at most this many steps away are worth a detour.
+const LOOT_DETOUR: u32 = 40;
+/// Heal before a fight when below this share of max HP.
+const TOP_UP_PERCENT: i32 = 75;
+const DAGGER: &str = "Weeping Dagger";
+
+/// How one seed went.
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// The world seed played.
+    pub seed: u64,
+    /// Reached the end of the story.
+    pub finished: bool,
+    /// Lost a fight; the dice, rather than the map or the story, stopped this one.
+    pub died: bool,
+    /// Inputs sent, whether or not it finished.
+    pub actions: u32,
+    /// What the bot was trying to do when it gave up, and why.
+    pub stuck: Option<String>,
+    /// How far the story got.
+    pub flags: StoryFlags,
+    /// Room and position at the end.
+    pub at: (usize, i32, i32),
+}
+
+/// Play `seed` from the title screen until the end, a dead end, or `max_actions`.
+pub fn play(seed: u64, max_actions: u32) -> Run {
+    let mut bot = Bot { w: World::new(seed, 80, 45), left_behind: Vec::new() };
+    let mut actions = 0;
+    let stuck = loop {
+        if bot.w.state == GameState::Fin { break None; }
+        let goal = bot.goal();
+        if bot.w.player.hp <= 0 { break Some(format!("died trying to {}", goal.describe())); }
+        if actions >= max_actions { break Some(format!("ran out of actions trying to {}", goal.describe())); }
+        let action = match bot.next_action(goal) {
+            Ok(a) => a,
+            Err(why) => break Some(format!("couldn't {}: {}", goal.describe(), why)),
+        };
+        bot.w.apply_action(action);
+        bot.w.tick();
+        actions += 1;
+    };
+    let w = &bot.w;
+    Run { seed, finished: stuck.is_none(), died: w.player.hp <= 0, actions, stuck, flags: w.flags().clone(), at: (w.current, w.player.x, w.player.y) }
+}
+
+/// The next thing the story needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Goal {
+    Talk(NpcId),
+    Fight(NpcId),
+    FetchDagger,
+}
+
+impl Goal {
+    fn describe(self) -> String {
+        match self {
+            Goal::Talk(id) => format!("talk to {:?}", id),
+            Goal::Fight(id) => format!("beat {:?}", id),
+            Goal::FetchDagger => "fetch the Weeping Dagger".to_string(),
+        }
+    }
+}
+
+struct Bot {
+    w: World,
+    /// Chests whose contents didn't fit and were left where they were.
+    left_behind: Vec<(usize, i32, i32)>,
+}
+
+impl Bot {
+    fn goal(&self) -> Goal {
+        let f = self.w.flags();
+        if !f.mayor_done { return Goal::Talk(NpcId::MayorSol); }
+        if !f.noor_done { return Goal::Talk(NpcId::Noor); }
+        // Lamp only hands over what's missing; with both hands already full there's nothing to fetch.
+        let inv = &self.w.player.inventory;
+        let armed = inv.equipped(GearSlot::Sword).is_some() && inv.equipped(GearSlot::Shield).is_some();
+        if !f.lamp_done && !armed { return Goal::Talk(NpcId::Lamp); }
+        if !f.dorosht_accepted { return Goal::Talk(NpcId::Dorosht); }
+        if !f.shab_defeated { return Goal::Fight(NpcId::Shab); }
+        if !f.krad_defeated { return Goal::Fight(NpcId::Krad); }
+        if !f.mah_defeated { return Goal::Fight(NpcId::Mah); }
+        if !f.dorosht_completed {
+            return if self.carries(DAGGER) { Goal::Talk(NpcId::Dorosht) } else { Goal::FetchDagger };
+        }
+        if !f.mayor_defeated { return Goal::Fight(NpcId::MayorSol); }
+        Goal::Talk(NpcId::MayorSol)
+    }
+
+    fn next_action(&mut self, goal: Goal) -> Result<Action, String> {
+        Ok(match self.w.state {
+            GameState::Title | GameState::Intro => Action::Confirm,
+            GameState::Dialogue => self.answer(),
+            GameState::Battle => self.battle_move(),
+            GameState::Shop => Action::LeaveShop,
+            GameState::Playing => return self.walk(goal),
+            GameState::Fin => Action::None,
+        })
+    }
+
+    // --- Talking ---
+
+    fn answer(&mut self) -> Action {
+        let Some(d) = &self.w.dialogue else { return Action::Confirm };
+        if !(d.on_last_page() && d.fully_revealed()) { return Action::Confirm; }
+        match &d.awaiting {
+            Some(AwaitingChoice::YesNoMayor | AwaitingChoice::YesNoDorosht) => Action::Choice('Y'),
+            Some(AwaitingChoice::ABNoorWeapon | AwaitingChoice::Chest { .. }) => Action::Choice('A'),
+            // The dagger is worth any slot; anything else stays in the chest.
+            Some(AwaitingChoice::PackFull { weapon: Some(eq), .. }) if eq.name == DAGGER => Action::Choice('A'),
+            Some(AwaitingChoice::PackFull { room, x, y, .. }) => {
+                self.left_behind.push((*room, *x, *y));
+                Action::Choice(AwaitingChoice::LEAVE_KEY)
+            }
+            None => Action::Confirm,
+        }
+    }
+
+    // --- Fighting ---
+
+    fn battle_move(&self) -> Action {
+        let Some(bs) = &self.w.battle else { return Action::Confirm };
+        let inv = &self.w.player.inventory;
+        let mut worst_hit = World::calc_damage(bs.enemy_atk);
+        if bs.terrain == Tile::Lava { worst_hit += 2; }
+        // Eating costs a turn, so only food that heals more than the hit it lets through helps.
+        let food = self.best_food()
+            .filter(|&i| inv.consumables[i].item.heal > worst_hit)
+            .and_then(|i| inv.visible_consumables().iter().position(|&j| j == i));
+        if self.w.inventory_open {
+            return match food {
+                Some(pos) => self.select(InvTab::Consumables, inv.consumable_cursor, pos),
+                None => Action::ToggleInventory,
+            };
+        }
+        let losing = self.w.player.hp <= worst_hit && bs.enemy_hp > World::calc_damage(self.w.player.attack());
+        // Fleeing to heal outside is cheaper than eating under fire, when the enemy allows it.
+        if losing && !bs.player_initiated && self.can_heal() { Action::BattleOption(3) }
+        else if losing && food.is_some() { Action::BattleOption(2) }
+        else { Action::BattleOption(1) }
+    }
+
+    fn can_heal(&self) -> bool {
+        self.best_food().is_some() || self.fountain().is_some()
+    }
+
+    // --- Walking about ---
+
+    fn walk(&mut self, goal: Goal) -> Result<Action, String> {
+        if let Some(a) = self.tend_inventory(goal) { return Ok(a); }
+        // Buffs drunk next to the enemy cost no turns in the fight.
+        if let Goal::Fight(id) = goal
+            && self.talk_spot(id)(self.w.player.x, self.w.player.y)
+            && let Some(a) = self.drink_buffs()
+        {
+            return Ok(a);
+        }
+        if self.w.inventory_open { return Ok(Action::ToggleInventory); }
+        if self.w.stats_open { return Ok(Action::ToggleStats); }
+
+        if let Goal::Fight(_) = goal
+            && self.hp_percent() < TOP_UP_PERCENT
+            && let Some((x, y)) = self.fountain()
+            && let Some(a) = self.use_at(x, y)
+        {
+            return Ok(a);
+        }
+        if let Some(a) = self.loot_nearby() { return Ok(a); }
+
+        let (room, target) = match goal {
+            Goal::Talk(id) | Goal::Fight(id) => {
+                let npc = self.w.npcs().iter().find(|n| n.id == id).ok_or(format!("{:?} isn't in the world", id))?;
+                (npc.room, Target::Npc(id))
+            }
+            Goal::FetchDagger => {
+                let (room, chest) = self.w.levels.iter().enumerate()
+                    .find_map(|(r, l)| l.chests().iter().find(|c| !c.opened && c.weapon.as_ref().is_some_and(|e| e.name == DAGGER)).map(|c| (r, c)))
+                    .ok_or("Mah left no dagger behind")?;
+                (room, Target::Tile(chest.x, chest.y))
+            }
+        };
+        if room != self.w.current { return self.go_through_door(); }
+
+        if let Target::Npc(id) = target {
+            if self.talk_spot(id)(self.w.player.x, self.w.player.y) { return Ok(Action::Interact); }
+            let npc = self.w.npcs().iter().find(|n| n.id == id).expect("checked above");
+            if !self.w.npc_known(npc) {
+                return self.explore().ok_or(format!("explored everything reachable and never saw {:?}", id));
+            }
+        }
+        let reach = match target {
+            Target::Npc(id) => self.step_towards(&self.talk_spot(id), u32::MAX),
+            Target::Tile(x, y) => self.step_towards(&|px, py| (px, py) == (x, y), u32::MAX),
+        };
+        match reach {
+            Some((a, _)) => Ok(a),
+            None => self.explore().ok_or(format!("no way through to {}", target.describe())),
+        }
+    }
+
+    fn go_through_door(&self) -> Result<Action, String> {
+        let door = self.w.current_level().door;
+        let inv = &self.w.player.inventory;
+        if inv.equipped(GearSlot::Sword).is_none() || inv.equipped(GearSlot::Shield).is_none() {
+            return Err("the door wants a sword and a shield equipped".to_string());
+        }
+        match self.use_at(door.0, door.1) {
+            Some(a) => Ok(a),
+            None => self.explore().ok_or_else(|| match self.crowding_door() {
+                Some(id) => format!("{:?} stands in front of the door", id),
+                None => "no way through to the door".to_string(),
+            }),
+        }
+    }
+
+    /// Walk next to `(x, y)` and press E there, where nothing else would answer first.
+    fn use_at(&self, x: i32, y: i32) -> Option<Action> {
+        let w = &self.w;
+        let spot = |px: i32, py: i32| {
+            let near = |(ox, oy): (i32, i32)| (ox - px).abs().max((oy - py).abs()) <= 1 && (ox, oy) != (px, py);
+            near((x, y))
+                && !w.npcs().iter().any(|n| n.room == w.current && near((n.x, n.y)))
+                && w.pickup_at(px, py).is_none()
+                // A door next to you wins over anything else but people.
+                && (w.current_map().get(x as usize, y as usize) == Tile::Door || !near(w.current_level().door))
+        };
+        if spot(w.player.x, w.player.y) { return Some(Action::Interact); }
+        self.step_towards(&spot, u32::MAX).map(|(a, _)| a)
+    }
+
+    /// Someone standing so close to the door that E talks to them from every side of it.
+    fn crowding_door(&self) -> Option<NpcId> {
+        let w = &self.w;
+        let (dx, dy) = w.current_level().door;
+        let map = w.current_map();
+        let npc_near = |x: i32, y: i32| w.npcs().iter().find(|n| n.room == w.current && (n.x - x).abs().max((n.y - y).abs()) <= 1);
+        let mut crowd = None;
+        for (x, y) in (-1..=1).flat_map(|y| (-1..=1).map(move |x| (dx + x, dy + y))) {
+            if (x, y) == (dx, dy) || !map.in_bounds(x, y) || !map.is_walkable(x as usize, y as usize) { continue; }
+            crowd = Some(npc_near(x, y)?.id);
+        }
+        crowd
+    }
+
+    /// Tiles from which E reaches `id` and nobody else.
+    fn talk_spot(&self, id: NpcId) -> impl Fn(i32, i32) -> bool + '_ {
+        move |x, y| {
+            let near = self.w.npcs().iter().find(|n| n.room == self.w.current && (n.x - x).abs().max((n.y - y).abs()) <= 1);
+            near.is_some_and(|n| n.id == id)
+        }
+    }
+
+    fn loot_nearby(&self) -> Option<Action> {
+        let room = self.w.current;
+        let level = self.w.current_level();
+        let wanted = |x: i32, y: i32| {
+            level.chest_at(x, y).is_some_and(|c| !c.opened) && level.is_explored(x, y)
+                && !self.left_behind.contains(&(room, x, y))
+        };
+        self.step_towards(&wanted, LOOT_DETOUR).map(|(a, _)| a)
+    }
+
+    /// A step towards the closest explored tile next to unexplored ground.
+    fn explore(&self) -> Option<Action> {
+        let level = self.w.current_level();
+        let frontier = |x: i32, y: i32| DIRS.iter().any(|(dx, dy)| level.map.in_bounds(x + dx, y + dy) && !level.is_explored(x + dx, y + dy));
+        self.step_towards(&frontier, u32::MAX).map(|(a, _)| a)
+    }
+
+    /// First move of the shortest walk over explored ground to a tile where `goal` holds, and
+    /// how many moves it takes. Lava is only crossed when there's no other way.
+    fn step_towards(&self, goal: &dyn Fn(i32, i32) -> bool, max_len: u32) -> Option<(Action, u32)> {
+        self.search(goal, max_len, false).or_else(|| self.search(goal, max_len, true))
+    }
+
+    fn search(&self, goal: &dyn Fn(i32, i32) -> bool, max_len: u32, through_lava: bool) -> Option<(Action, u32)> {
+        let level = self.w.current_level();
+        let map = &level.map;
+        let start = (self.w.player.x, self.w.player.y);
+        let open = |x: i32, y: i32| {
+            map.in_bounds(x, y) && map.is_walkable(x as usize, y as usize) && self.w.npc_at(self.w.current, x, y).is_none()
+        };
+
+        let mut first: Vec<Option<(i32, i32)>> = vec![None; map.width * map.height];
+        let mut queue = VecDeque::from([(start, 0u32)]);
+        first[map.idx(start.0 as usize, start.1 as usize)] = Some((0, 0));
+        while let Some(((x, y), len)) = queue.pop_front() {
+            if len >= max_len { continue; }
+            for (dx, dy) in DIRS {
+                if !open(x + dx, y + dy) || !level.is_explored(x + dx, y + dy) { continue; }
+                // Where the step really ends: ice carries you on.
+                let (mut nx, mut ny) = (x + dx, y + dy);
+                while map.get(nx as usize, ny as usize) == Tile::Ice && open(nx + dx, ny + dy) {
+                    nx += dx;
+                    ny += dy;
+                }
+                if !through_lava && map.get(nx as usize, ny as usize) == Tile::Lava { continue; }
+                let i = map.idx(nx as usize, ny as usize);
+                if first[i].is_some() { continue; }
+                let dir = if (x, y) == start { (dx, dy) } else { first[map.idx(x as usize, y as usize)]? };
+                first[i] = Some(dir);
+                if goal(nx, ny) { return Some((Action::Move(dir.0, dir.1), len + 1)); }
+                queue.push_back(((nx, ny), len + 1));
+            }
+        }
+        None
+    }
+
+    fn hp_percent(&self) -> i32 {
+        self.w.player.hp * 100 / self.w.player.max_hp.max(1)
+    }
+
+    /// A fountain with water left in the current level.
+    fn fountain(&self) -> Option<(i32, i32)> {
+        self.w.current_level().objects.iter().find_map(|o| match o.kind {
+            ObjectKind::Fountain { charges } if charges > 0 => Some((o.x, o.y)),
+            _ => None,
+        })
+    }
+
+    // --- The pack ---
+
+    fn carries(&self, name: &str) -> bool {
+        let inv = &self.w.player.inventory;
+        inv.equipped_items().chain(&inv.backpack).any(|e| e.unbroken_name() == name)
+    }
+
+    /// Index of the food that heals most.
+    fn best_food(&self) -> Option<usize> {
+        let stacks = &self.w.player.inventory.consumables;
+        (0..stacks.len()).filter(|&i| stacks[i].item.heal > 0).max_by_key(|&i| stacks[i].item.heal)
+    }
+
+    /// Wear anything better than what's on; eat whatever heals without going to waste, and
+    /// before a fight, anything that heals at all.
+    fn tend_inventory(&self, goal: Goal) -> Option<Action> {
+        let p = &self.w.player;
+        let inv = &p.inventory;
+        let upgrade = inv.backpack.iter().position(|eq| !eq.is_broken() && self.is_upgrade(eq));
+        if let Some(i) = upgrade {
+            let pos = inv.visible_backpack().iter().position(|&j| j == i)?;
+            return Some(self.select(InvTab::Backpack, inv.backpack_cursor, pos));
+        }
+        let food = self.best_food()?;
+        let heal = inv.consumables[food].item.heal;
+        let hungry = p.hp + heal <= p.max_hp || (matches!(goal, Goal::Fight(_)) && self.hp_percent() < TOP_UP_PERCENT);
+        if !hungry { return None; }
+        let pos = inv.visible_consumables().iter().position(|&j| j == food)?;
+        Some(self.select(InvTab::Consumables, inv.consumable_cursor, pos))
+    }
+
+    /// One of each attack and defense buff on hand that isn't already running.
+    fn drink_buffs(&self) -> Option<Action> {
+        let p = &self.w.player;
+        let atk_on = p.buffs.iter().any(|b| b.atk_bonus > 0);
+        let def_on = p.buffs.iter().any(|b| b.def_bonus > 0);
+        let inv = &p.inventory;
+        let buff = (0..inv.consumables.len()).find(|&i| {
+            let c = &inv.consumables[i].item;
+            (c.atk_bonus > 0 && !atk_on) || (c.def_bonus > 0 && !def_on)
+        })?;
+        let pos = inv.visible_consumables().iter().position(|&j| j == buff)?;
+        Some(self.select(InvTab::Consumables, inv.consumable_cursor, pos))
+    }
+
+    fn is_upgrade(&self, eq: &Equipment) -> bool {
+        let inv = &self.w.player.inventory;
+        let Some(slot) = inv.slot_for(eq) else { return false };
+        inv.equipped(slot).is_none_or(|on| eq.power() > on.power())
+    }
+
+    /// The key press that gets the cursor to row `pos` of `tab` and uses what's there.
+    fn select(&self, tab: InvTab, cursor: usize, pos: usize) -> Action {
+        let inv = &self.w.player.inventory;
+        if !self.w.inventory_open { return Action::ToggleInventory; }
+        if inv.tab != tab { return Action::ToggleInvTab; }
+        if cursor < pos { return Action::InventoryDown; }
+        if cursor > pos { return Action::InventoryUp; }
+        Action::UseConsumable
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Npc(NpcId),
+    Tile(i32, i32),
+}
+
+impl Target {
+    fn describe(self) -> String {
+        match self {
+            Target::Npc(id) => format!("{:?}", id),
+            Target::Tile(x, y) => format!("({}, {})", x, y),
+        }
+    }
+}
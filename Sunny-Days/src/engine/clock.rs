@@ -0,0 +1,33 @@
+//! The game's own clock, counted in ticks of play rather than wall time.
+//!
+//! The loop advances it once per tick, but only while the player is exploring or fighting:
+//! behind dialogue, shops and menus it stands still, and so does everything timed against it.
+//! Tests step it by hand.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct GameClock {
+    now: u64,
+}
+
+impl GameClock {
+    /// Length of one tick of play.
+    pub const TICK_MS: u32 = 60;
+
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    pub fn advance(&mut self) {
+        self.now += 1;
+    }
+
+    /// The tick `ticks` from now.
+    pub fn after(&self, ticks: u64) -> u64 {
+        self.now + ticks
+    }
+
+    /// Ticks in `secs` seconds of play.
+    pub const fn secs(secs: u32) -> u64 {
+        secs as u64 * 1000 / Self::TICK_MS as u64
+    }
+}
@@ -0,0 +1,128 @@
+//! The developer console: one-line commands that poke at a running world, for trying things
+//! out by hand without playing up to them.
+//!
+//! ```text
+//! teleport 12 7
+//! give Willow Axe
+//! set_flag mah_defeated
+//! start_battle mayor
+//! ```
+//!
+//! Commands change the world directly rather than through `Action`s, so they can't be recorded.
+
+use crate::engine::balance;
+use crate::engine::entity::{ConsumableStack, SlotItem};
+use crate::engine::world::{GameState, World};
+
+use std::collections::VecDeque;
+
+pub const HELP: &str = "teleport <x> <y> | give <item> | set_flag <name> [true|false] | heal | start_battle <npc> | goto_room <n>";
+/// Lines of output kept for the console panel.
+const OUTPUT_LINES: usize = 6;
+
+/// What's typed so far, what came back, and what was run before.
+#[derive(Debug, Clone, Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: VecDeque<String>,
+    history: Vec<String>,
+    /// How far back Up has gone into `history`.
+    recall: usize,
+}
+
+impl Console {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.input.clear();
+        self.recall = 0;
+    }
+
+    /// Run what's been typed and keep the answer.
+    pub fn submit(&mut self, world: &mut World) {
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        self.recall = 0;
+        if line.is_empty() { return; }
+        let reply = match execute(world, line) {
+            Ok(msg) => msg,
+            Err(msg) => format!("error: {}", msg),
+        };
+        self.print(format!("> {}", line));
+        self.print(reply);
+        if self.history.last().map(String::as_str) != Some(line) { self.history.push(line.to_string()); }
+    }
+
+    /// Up and Down walk through earlier commands.
+    pub fn recall(&mut self, older: bool) {
+        self.recall = if older { (self.recall + 1).min(self.history.len()) } else { self.recall.saturating_sub(1) };
+        self.input = match self.recall {
+            0 => String::new(),
+            n => self.history[self.history.len() - n].clone(),
+        };
+    }
+
+    fn print(&mut self, line: String) {
+        self.output.push_back(line);
+        while self.output.len() > OUTPUT_LINES { self.output.pop_front(); }
+    }
+}
+
+/// Run one command against `world`; Ok is what to tell the user.
+pub fn execute(world: &mut World, line: &str) -> Result<String, String> {
+    let (cmd, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    match cmd {
+        "help" => Ok(HELP.to_string()),
+        "teleport" | "tp" => {
+            let [x, y] = args[..] else { return Err("usage: teleport <x> <y>".to_string()) };
+            let x = x.parse().map_err(|_| format!("not a number: {}", x))?;
+            let y = y.parse().map_err(|_| format!("not a number: {}", y))?;
+            world.teleport(x, y)?;
+            Ok(format!("Teleported to ({}, {}).", x, y))
+        }
+        "give" => {
+            if rest.is_empty() { return Err("usage: give <item>".to_string()); }
+            let item = match balance::named_gear(rest) {
+                Some(eq) => SlotItem::Gear(eq),
+                None => {
+                    let c = balance::named_consumable(rest).ok_or(format!("unknown item: {}", rest))?;
+                    SlotItem::Stack(ConsumableStack { item: c, qty: 1 })
+                }
+            };
+            let label = item.label();
+            world.player.inventory.add_item(item).map_err(|_| "the pack is full".to_string())?;
+            Ok(format!("Gave {}.", label))
+        }
+        "set_flag" => {
+            let (name, value) = match args[..] {
+                [name] => (name, true),
+                [name, value] => (name, value.parse().map_err(|_| format!("not true or false: {}", value))?),
+                _ => return Err("usage: set_flag <name> [true|false]".to_string()),
+            };
+            world.set_flag(name, value)?;
+            Ok(format!("{} = {}", name, value))
+        }
+        "heal" => {
+            world.player.hp = world.player.max_hp;
+            Ok(format!("HP {}/{}.", world.player.hp, world.player.max_hp))
+        }
+        "start_battle" => {
+            let [name] = args[..] else { return Err("usage: start_battle <npc>".to_string()) };
+            let enemy = balance::named_enemy(name).ok_or(format!("{} doesn't fight", name))?;
+            if world.state != GameState::Playing { return Err(format!("can't start a fight during {:?}", world.state)); }
+            world.inventory_open = false;
+            world.stats_open = false;
+            world.start_battle(enemy);
+            Ok(format!("Fighting {:?}.", enemy))
+        }
+        "goto_room" => {
+            let [n] = args[..] else { return Err("usage: goto_room <n>".to_string()) };
+            let room: usize = n.parse().ok().filter(|&r| r >= 1).ok_or(format!("rooms are numbered from 1: {}", n))?;
+            world.goto_room(room - 1)?;
+            Ok(format!("Now in room {}.", room))
+        }
+        _ => Err(format!("unknown command: {} (try help)", cmd)),
+    }
+}
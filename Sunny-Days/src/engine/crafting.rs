@@ -0,0 +1,150 @@
+//! Combining items into new ones.
+//!
+//! Recipes live in `data/recipes.txt`, compiled into the binary and parsed once, on first use. The player starts
+//! knowing none of them: marking items in the Crafting tab and combining them is how they
+//! are found.
+
+use crate::engine::entity::{Consumable, ConsumableStack, EquipSlot, Equipment, Rarity, SlotItem};
+use crate::engine::loot;
+
+use std::sync::OnceLock;
+
+const RECIPES_SRC: &str = include_str!("../../data/recipes.txt");
+
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    /// Ingredient names (gear by base type) and how many of each.
+    pub inputs: Vec<(String, u32)>,
+    pub output: SlotItem,
+}
+
+impl Recipe {
+    pub fn output_name(&self) -> &str {
+        match &self.output {
+            SlotItem::Stack(s) => &s.item.name,
+            SlotItem::Gear(eq) => &eq.name,
+        }
+    }
+
+    /// "Fiery ale + 2 x Frozen tears".
+    pub fn inputs_label(&self) -> String {
+        self.inputs
+            .iter()
+            .map(|(name, n)| if *n > 1 { format!("{} x {}", n, name) } else { name.clone() })
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// Every recipe in the game, in file order. src/tests/crafting.rs checks the shipped file; if a
+/// bad one got through anyway, there is just nothing to craft.
+pub fn recipes() -> &'static [Recipe] {
+    static BOOK: OnceLock<Vec<Recipe>> = OnceLock::new();
+    BOOK.get_or_init(|| parse(RECIPES_SRC).unwrap_or_default())
+}
+
+/// The name an item goes by as an ingredient: rolled gear counts as its base type.
+pub fn ingredient_name(name: &str) -> &str {
+    loot::base_name_in(name).unwrap_or(name)
+}
+
+/// Index of the recipe these ingredients make, if any. Order doesn't matter, amounts do.
+pub fn find(ingredients: &[(&str, u32)]) -> Option<usize> {
+    let mut have: Vec<(&str, u32)> = Vec::new();
+    for &(name, n) in ingredients {
+        let name = ingredient_name(name);
+        match have.iter_mut().find(|(h, _)| *h == name) {
+            Some((_, count)) => *count += n,
+            None => have.push((name, n)),
+        }
+    }
+    recipes().iter().position(|r| {
+        r.inputs.len() == have.len()
+            && r.inputs.iter().all(|(name, n)| have.iter().any(|(h, count)| h == name && count == n))
+    })
+}
+
+/// Read a recipe file; the error names the first bad line.
+pub fn parse(src: &str) -> Result<Vec<Recipe>, String> {
+    let mut out = Vec::new();
+    for (n, raw) in src.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() { continue; }
+        out.push(parse_line(line).map_err(|e| format!("line {}: {}", n + 1, e))?);
+    }
+    Ok(out)
+}
+
+fn parse_line(line: &str) -> Result<Recipe, String> {
+    let (lhs, rhs) = line.split_once("=>").ok_or("expected `=>`")?;
+
+    let mut inputs: Vec<(String, u32)> = Vec::new();
+    for part in lhs.split('+') {
+        let part = part.trim();
+        let (count, name) = match part.split_once(" x ") {
+            Some((count, name)) => (count.trim().parse::<u32>().map_err(|_| format!("bad count: {}", count))?, name.trim()),
+            None => (1, part),
+        };
+        if name.is_empty() { return Err("empty ingredient".to_string()); }
+        inputs.push((name.to_string(), count));
+    }
+    if inputs.iter().map(|(_, n)| n).sum::<u32>() < 2 {
+        return Err("a recipe needs at least two ingredients".to_string());
+    }
+
+    let (name, stats) = rhs.split_once(':').ok_or("expected `Result: stats`")?;
+    let name = name.trim().to_string();
+    let mut slot = None;
+    let mut rarity = Rarity::Common;
+    let (mut hp, mut atk, mut def, mut spd, mut weight, mut repair) = (0, 0, 0, 0, 1, 0);
+    for pair in stats.split_whitespace() {
+        let (key, value) = pair.split_once('=').ok_or(format!("expected key=value, got {}", pair))?;
+        let num = || value.parse::<i32>().map_err(|_| format!("bad number for {}: {}", key, value));
+        match key {
+            "heal" | "hp" => hp = num()?,
+            "atk" => atk = num()?,
+            "def" => def = num()?,
+            "spd" => spd = num()?,
+            "weight" => weight = value.parse::<u32>().map_err(|_| format!("bad weight: {}", value))?,
+            "repair" => repair = value.parse::<u32>().map_err(|_| format!("bad repair: {}", value))?,
+            "slot" => slot = Some(parse_slot(value)?),
+            "rarity" => rarity = parse_rarity(value)?,
+            _ => return Err(format!("unknown key: {}", key)),
+        }
+    }
+
+    let output = match slot {
+        Some(slot) => SlotItem::Gear(Equipment {
+            name, slot, hp_bonus: hp, atk_bonus: atk, def_bonus: def, speed_bonus: spd, weight, rarity,
+            durability: rarity.durability(), max_durability: rarity.durability(),
+        }),
+        None => SlotItem::Stack(ConsumableStack {
+            item: Consumable { name, heal: hp, atk_bonus: atk, def_bonus: def, weight, repair },
+            qty: 1,
+        }),
+    };
+    Ok(Recipe { inputs, output })
+}
+
+fn parse_slot(value: &str) -> Result<EquipSlot, String> {
+    match value {
+        "sword" => Ok(EquipSlot::Sword),
+        "shield" => Ok(EquipSlot::Shield),
+        "helmet" => Ok(EquipSlot::Helmet),
+        "armour" => Ok(EquipSlot::Armour),
+        "boots" => Ok(EquipSlot::Boots),
+        "ring" => Ok(EquipSlot::Ring),
+        _ => Err(format!("unknown slot: {}", value)),
+    }
+}
+
+fn parse_rarity(value: &str) -> Result<Rarity, String> {
+    match value {
+        "common" => Ok(Rarity::Common),
+        "uncommon" => Ok(Rarity::Uncommon),
+        "rare" => Ok(Rarity::Rare),
+        "epic" => Ok(Rarity::Epic),
+        "legendary" => Ok(Rarity::Legendary),
+        _ => Err(format!("unknown rarity: {}", value)),
+    }
+}
@@ -1,10 +1,107 @@
-use crate::map::Map;
-use std::time::{Duration, Instant};
+use crate::map::{tile::Tile, Map};
 
+/// What kind of gear an item is, i.e. which gear slots accept it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EquipSlot {
     Sword,
     Shield,
+    Helmet,
+    Armour,
+    Boots,
+    Ring,
+}
+
+/// A place on the body gear is worn. Rings fit either ring slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GearSlot {
+    Sword,
+    Shield,
+    Helmet,
+    Armour,
+    Boots,
+    Ring1,
+    Ring2,
+}
+
+impl GearSlot {
+    pub const ALL: [GearSlot; 7] = [
+        GearSlot::Sword,
+        GearSlot::Shield,
+        GearSlot::Helmet,
+        GearSlot::Armour,
+        GearSlot::Boots,
+        GearSlot::Ring1,
+        GearSlot::Ring2,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GearSlot::Sword => "Sword",
+            GearSlot::Shield => "Shield",
+            GearSlot::Helmet => "Helmet",
+            GearSlot::Armour => "Armour",
+            GearSlot::Boots => "Boots",
+            GearSlot::Ring1 | GearSlot::Ring2 => "Ring",
+        }
+    }
+
+    pub fn accepts(self, slot: EquipSlot) -> bool {
+        match self {
+            GearSlot::Sword => slot == EquipSlot::Sword,
+            GearSlot::Shield => slot == EquipSlot::Shield,
+            GearSlot::Helmet => slot == EquipSlot::Helmet,
+            GearSlot::Armour => slot == EquipSlot::Armour,
+            GearSlot::Boots => slot == EquipSlot::Boots,
+            GearSlot::Ring1 | GearSlot::Ring2 => slot == EquipSlot::Ring,
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&s| s == self).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Rarity::Common => "Common",
+            Rarity::Uncommon => "Uncommon",
+            Rarity::Rare => "Rare",
+            Rarity::Epic => "Epic",
+            Rarity::Legendary => "Legendary",
+        }
+    }
+
+    /// Stat multiplier in percent applied to a base item's bonuses.
+    pub fn stat_percent(self) -> i32 {
+        match self {
+            Rarity::Common => 100,
+            Rarity::Uncommon => 125,
+            Rarity::Rare => 150,
+            Rarity::Epic => 200,
+            Rarity::Legendary => 300,
+        }
+    }
+
+    /// How much wear a fresh item of this rarity can take.
+    pub fn durability(self) -> u32 {
+        match self {
+            Rarity::Common => 20,
+            Rarity::Uncommon => 25,
+            Rarity::Rare => 30,
+            Rarity::Epic => 40,
+            Rarity::Legendary => 60,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +112,11 @@ pub struct Equipment {
     pub atk_bonus: i32,
     pub def_bonus: i32,
     pub speed_bonus: i32,
+    pub weight: u32,
+    pub rarity: Rarity,
+    /// Battle wear left; at zero the item breaks.
+    pub durability: u32,
+    pub max_durability: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +125,216 @@ pub struct Consumable {
     pub heal: i32,
     pub atk_bonus: i32,
     pub def_bonus: i32,
+    pub weight: u32, // per unit
+    /// Durability restored to your most worn piece of gear.
+    pub repair: u32,
+}
+
+impl Equipment {
+    pub fn kind(&self) -> ItemKind {
+        match self.slot {
+            EquipSlot::Sword => ItemKind::Sword,
+            EquipSlot::Shield => ItemKind::Shield,
+            EquipSlot::Helmet | EquipSlot::Armour | EquipSlot::Boots => ItemKind::Armour,
+            EquipSlot::Ring => ItemKind::Ring,
+        }
+    }
+
+    pub const BROKEN_PREFIX: &'static str = "Broken ";
+
+    /// The name without the "Broken " a worn-out item picks up.
+    pub fn unbroken_name(&self) -> &str {
+        self.name.strip_prefix(Self::BROKEN_PREFIX).unwrap_or(&self.name)
+    }
+
+    /// The story needs this item back, so it can't be sold.
+    pub fn is_quest_item(&self) -> bool {
+        self.unbroken_name() == "Weeping Dagger"
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability == 0
+    }
+
+    /// Below half durability, bonuses start to fall off.
+    pub fn is_damaged(&self) -> bool {
+        self.durability * 2 < self.max_durability
+    }
+
+    /// A bonus as it applies right now. Positive bonuses hold until the item `is_damaged`, then
+    /// shrink with wear, rounded, down to half at zero durability.
+    pub fn effective(&self, bonus: i32) -> i32 {
+        if bonus <= 0 || !self.is_damaged() { return bonus; }
+        // bonus * (50% + 100% * durability / max), to the nearest whole point.
+        let (dur, max) = (self.durability as i32, self.max_durability as i32);
+        (bonus * (max + 2 * dur) + max) / (2 * max)
+    }
+
+    /// Take `amount` wear; true if this broke the item.
+    pub fn wear(&mut self, amount: u32) -> bool {
+        if self.is_broken() { return false; }
+        self.durability = self.durability.saturating_sub(amount);
+        if self.is_broken() {
+            self.name = format!("{}{}", Self::BROKEN_PREFIX, self.name);
+            return true;
+        }
+        false
+    }
+
+    /// Restore up to `amount` durability, mending a broken item's name.
+    pub fn repair(&mut self, amount: u32) {
+        if self.is_broken() && amount > 0 { self.name = self.unbroken_name().to_string(); }
+        self.durability = (self.durability + amount).min(self.max_durability);
+    }
+
+    /// Sum of every bonus, used to sort by "stat".
+    pub fn power(&self) -> i32 {
+        self.hp_bonus + self.atk_bonus + self.def_bonus + self.speed_bonus
+    }
+
+    /// Flavour text for the item's base type; rolled loot keeps the base name inside its own.
+    pub fn description(&self) -> &'static str {
+        let name = self.unbroken_name();
+        let base = crate::engine::loot::base_name_in(name).unwrap_or(name);
+        match base {
+            "Basic Sword" => "A plain village blade. Light, quick and dependable.",
+            "Basic Shield" => "Planks and iron bands. Slows you down, but it holds.",
+            "Weeping Dagger" => "Forged by the cruellest of the Weeping. It drinks from whoever holds it.",
+            "Shield of healing" => "Warm to the touch. Its bearer's wounds close a little faster.",
+            "Willow Axe" => "Fine steel hafted in Weeping Willow bark. Heavy, and hits like it.",
+            "Leather Cap" => "Keeps the rain off and the worst of a blow.",
+            "Iron Helm" => "Dented by someone else's bad day.",
+            "Padded Vest" => "Quilted cloth. Better than a shirt.",
+            "Chain Armour" => "Heavy rings of iron. You will hear yourself coming.",
+            "Swift Boots" => "Soft soles for quick feet.",
+            "Hobnail Boots" => "Sturdy boots that plant you firmly on the ground.",
+            "Copper Ring" => "A green-tinged band. Oddly reassuring.",
+            "Sun Ring" => "Warm as a summer afternoon. Your grip feels surer.",
+            "Short Sword" => "Standard issue for the village watch.",
+            "Hand Axe" => "Meant for firewood. Works on other things too.",
+            "Spear" => "Keeps trouble at arm's length, and then some.",
+            "Buckler" => "A small round shield for turning blades aside.",
+            "Kite Shield" => "Tall enough to hide behind, heavy enough to regret.",
+            "Ember Blade" => "A short sword quenched in fiery ale. It never quite cools.",
+            "Rimed Buckler" => "Frost clings to the rim. Blades skid right off it.",
+            "Barkweave Vest" => "Willow bark stitched into the padding. Light and tough.",
+            "Ring of Plenty" => "Smells faintly of jerky. You feel well fed.",
+            _ => "Nothing remarkable about it.",
+        }
+    }
+}
+
+impl Consumable {
+    pub fn kind(&self) -> ItemKind {
+        if self.repair > 0 { ItemKind::Tool }
+        else if self.atk_bonus != 0 || self.def_bonus != 0 { ItemKind::Tonic }
+        else { ItemKind::Food }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self.name.as_str() {
+            "Fiery ale" => "Burns on the way down. You'll swing harder for a while.",
+            "Weeping Willow bark" => "Bitter, but chewing it eases the pain.",
+            "Sunny Jerky" => "The village's finest dried meat. A proper meal.",
+            "Frozen tears" => "Cold enough to sting. Your skin hardens against blows for a while.",
+            "Regen tonic" => "Bark steeped in ale. Bitter, warming, and it keeps you on your feet.",
+            "Willow stew" => "Jerky simmered with bark. Smells better than it sounds.",
+            "Sunny feast" => "Two helpings of jerky. You won't need to eat again for a while.",
+            "Steam draught" => "Ice and fire in one cup. It hisses as you drink.",
+            "Whetstone" => "A smooth grey stone. A few minutes with it and your gear holds up again.",
+            "Tinker's kit" => "Two whetstones, oil and a rag, rolled up in leather. Mends almost anything.",
+            "Glacier heart" => "Three tears frozen into one. Holding it hurts; so does hitting you.",
+            _ => "You're not sure what this does.",
+        }
+    }
+}
+
+/// Whatever fills one inventory slot, e.g. when it's dropped on the floor.
+#[derive(Debug, Clone)]
+pub enum SlotItem {
+    Stack(ConsumableStack),
+    Gear(Equipment),
+}
+
+impl SlotItem {
+    pub fn label(&self) -> String {
+        match self {
+            SlotItem::Stack(s) if s.qty > 1 => format!("{} x{}", s.item.name, s.qty),
+            SlotItem::Stack(s) => s.item.name.clone(),
+            SlotItem::Gear(e) => e.name.clone(),
+        }
+    }
+}
+
+/// Broad item category, for sorting and filtering inventory lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ItemKind {
+    Sword,
+    Shield,
+    Armour, // helmets, body armour and boots
+    Ring,
+    Food,  // heals only
+    Tonic, // grants a temporary buff
+    Tool,  // repairs gear
+}
+
+impl ItemKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Sword => "Sword",
+            ItemKind::Shield => "Shield",
+            ItemKind::Armour => "Armour",
+            ItemKind::Ring => "Ring",
+            ItemKind::Food => "Food",
+            ItemKind::Tonic => "Tonic",
+            ItemKind::Tool => "Tool",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    Kind,
+    Stat, // strongest first
+}
+
+impl SortKey {
+    pub fn name(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Kind => "type",
+            SortKey::Stat => "stat",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Kind,
+            SortKey::Kind => SortKey::Stat,
+            SortKey::Stat => SortKey::Name,
+        }
+    }
+}
+
+/// How a list tab is ordered and which kinds it shows. The underlying Vec is never reordered.
+#[derive(Debug, Clone, Copy)]
+pub struct ListView {
+    pub sort: SortKey,
+    pub filter: Option<ItemKind>,
+}
+
+impl ListView {
+    fn new() -> Self {
+        Self { sort: SortKey::Name, filter: None }
+    }
+}
+
+/// Identical consumables share one inventory slot.
+#[derive(Debug, Clone)]
+pub struct ConsumableStack {
+    pub item: Consumable,
+    pub qty: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -30,86 +342,184 @@ pub struct TempBuff {
     pub atk_bonus: i32,
     pub def_bonus: i32,
     pub speed_bonus: i32,
-    pub expires_at: Instant,
+    /// Game-clock tick the buff wears off on.
+    pub expires_at: u64,
+}
+
+impl TempBuff {
+    /// Ticks of play left at game-clock tick `now`.
+    pub fn remaining(&self, now: u64) -> u64 {
+        self.expires_at.saturating_sub(now)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InvTab {
-    Weapons,
+    Gear,
     Consumables,
     Backpack,
+    Crafting,
 }
 
 #[derive(Debug, Clone)]
 pub struct Inventory {
-    pub sword: Option<Equipment>,
-    pub shield: Option<Equipment>,
+    /// Equipped items, indexed in `GearSlot::ALL` order.
+    pub gear: [Option<Equipment>; 7],
 
-    pub consumables: Vec<Consumable>, 
+    pub consumables: Vec<ConsumableStack>,
     pub backpack: Vec<Equipment>,
 
+    /// Slots shared by consumable stacks and backpack items (equipped gear is free).
+    pub capacity: usize,
+    /// Optional carry limit over everything held, equipped gear included.
+    pub max_weight: Option<u32>,
+
     pub tab: InvTab,
-    pub weapon_cursor: usize,
+    pub consumable_view: ListView,
+    pub backpack_view: ListView,
+    pub gear_cursor: usize,
     pub consumable_cursor: usize,
     pub backpack_cursor: usize,
+    /// Over every occupied slot, in `slot_labels` order.
+    pub crafting_cursor: usize,
+    /// Units marked for combining, per slot in `slot_labels` order.
+    pub craft_marks: Vec<u32>,
+    /// Keys take no slot; each opens one locked door.
+    pub keys: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum InvSelection {
-    SwordSlot,
-    ShieldSlot,
+    Gear(GearSlot),
     Consumable(usize),
     BackpackItem(usize),
     None,
 }
 
 impl Inventory {
+    pub const DEFAULT_CAPACITY: usize = 10;
+    pub const MAX_STACK: u32 = 5;
+
     pub fn default_loadout() -> Self {
         Self {
-            sword: None,
-            shield: None,
+            gear: Default::default(),
             consumables: Vec::new(),
             backpack: Vec::new(),
-            tab: InvTab::Weapons,
-            weapon_cursor: 0,
+            capacity: Self::DEFAULT_CAPACITY,
+            max_weight: None,
+            tab: InvTab::Gear,
+            consumable_view: ListView::new(),
+            backpack_view: ListView::new(),
+            gear_cursor: 0,
             consumable_cursor: 0,
             backpack_cursor: 0,
+            crafting_cursor: 0,
+            craft_marks: Vec::new(),
+            keys: Vec::new(),
         }
     }
 
     pub fn toggle_tab(&mut self) {
         self.tab = match self.tab {
-            InvTab::Weapons => InvTab::Consumables,
+            InvTab::Gear => InvTab::Consumables,
             InvTab::Consumables => InvTab::Backpack,
-            InvTab::Backpack => InvTab::Weapons,
+            InvTab::Backpack => InvTab::Crafting,
+            InvTab::Crafting => InvTab::Gear,
         };
+        self.craft_marks.clear();
+        self.clamp_cursors();
+    }
 
-        if self.weapon_cursor > 1 {
-            self.weapon_cursor = 1;
-        }
-        if !self.consumables.is_empty() && self.consumable_cursor >= self.consumables.len() {
-            self.consumable_cursor = self.consumables.len() - 1;
-        }
-        if !self.backpack.is_empty() && self.backpack_cursor >= self.backpack.len() {
-            self.backpack_cursor = self.backpack.len() - 1;
+    /// Keep list cursors inside what the current sort/filter shows.
+    pub fn clamp_cursors(&mut self) {
+        let shown = self.visible_consumables().len();
+        self.consumable_cursor = self.consumable_cursor.min(shown.saturating_sub(1));
+        let shown = self.visible_backpack().len();
+        self.backpack_cursor = self.backpack_cursor.min(shown.saturating_sub(1));
+        self.crafting_cursor = self.crafting_cursor.min(self.slots_used().saturating_sub(1));
+    }
+
+    /// Indices into `consumables`, in display order.
+    pub fn visible_consumables(&self) -> Vec<usize> {
+        let view = self.consumable_view;
+        let mut idx: Vec<usize> = (0..self.consumables.len())
+            .filter(|&i| view.filter.is_none_or(|k| self.consumables[i].item.kind() == k))
+            .collect();
+        idx.sort_by(|&a, &b| {
+            let (a, b) = (&self.consumables[a].item, &self.consumables[b].item);
+            match view.sort {
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Kind => a.kind().cmp(&b.kind()).then(a.name.cmp(&b.name)),
+                SortKey::Stat => b.heal.cmp(&a.heal).then(a.name.cmp(&b.name)),
+            }
+        });
+        idx
+    }
+
+    /// Indices into `backpack`, in display order.
+    pub fn visible_backpack(&self) -> Vec<usize> {
+        let view = self.backpack_view;
+        let mut idx: Vec<usize> = (0..self.backpack.len())
+            .filter(|&i| view.filter.is_none_or(|k| self.backpack[i].kind() == k))
+            .collect();
+        idx.sort_by(|&a, &b| {
+            let (a, b) = (&self.backpack[a], &self.backpack[b]);
+            match view.sort {
+                SortKey::Name => a.name.cmp(&b.name),
+                SortKey::Kind => a.kind().cmp(&b.kind()).then(a.name.cmp(&b.name)),
+                SortKey::Stat => b.power().cmp(&a.power()).then(a.name.cmp(&b.name)),
+            }
+        });
+        idx
+    }
+
+    /// The sort/filter of the active list tab; None on the Gear and Crafting tabs.
+    fn active_view(&mut self) -> Option<&mut ListView> {
+        match self.tab {
+            InvTab::Gear | InvTab::Crafting => None,
+            InvTab::Consumables => Some(&mut self.consumable_view),
+            InvTab::Backpack => Some(&mut self.backpack_view),
         }
     }
 
+    pub fn cycle_sort(&mut self) -> Option<SortKey> {
+        let view = self.active_view()?;
+        view.sort = view.sort.next();
+        Some(view.sort)
+    }
+
+    /// Step the active tab's filter through All and the kinds that tab can hold.
+    pub fn cycle_filter(&mut self) -> Option<Option<ItemKind>> {
+        let kinds: &[ItemKind] = match self.tab {
+            InvTab::Gear | InvTab::Crafting => return None,
+            InvTab::Consumables => &[ItemKind::Food, ItemKind::Tonic, ItemKind::Tool],
+            InvTab::Backpack => &[ItemKind::Sword, ItemKind::Shield, ItemKind::Armour, ItemKind::Ring],
+        };
+        let view = self.active_view()?;
+        view.filter = match view.filter {
+            None => Some(kinds[0]),
+            Some(k) => kinds.iter().position(|&x| x == k).and_then(|i| kinds.get(i + 1)).copied(),
+        };
+        let filter = view.filter;
+        self.clamp_cursors();
+        Some(filter)
+    }
+
     pub fn move_cursor(&mut self, delta: i32) {
         match self.tab {
-            InvTab::Weapons => {
-                let len = 2;
-                let mut idx = self.weapon_cursor as i32 + delta;
+            InvTab::Gear => {
+                let len = GearSlot::ALL.len();
+                let mut idx = self.gear_cursor as i32 + delta;
                 if idx < 0 {
                     idx = len as i32 - 1;
                 } else if idx >= len as i32 {
                     idx = 0;
                 }
-                self.weapon_cursor = idx as usize;
+                self.gear_cursor = idx as usize;
             }
 
             InvTab::Consumables => {
-                let len = self.consumables.len();
+                let len = self.visible_consumables().len();
                 if len == 0 {
                     self.consumable_cursor = 0;
                     return;
@@ -124,7 +534,7 @@ impl Inventory {
             }
 
             InvTab::Backpack => {
-                let len = self.backpack.len();
+                let len = self.visible_backpack().len();
                 if len == 0 {
                     self.backpack_cursor = 0;
                     return;
@@ -137,44 +547,184 @@ impl Inventory {
                 }
                 self.backpack_cursor = idx as usize;
             }
+
+            InvTab::Crafting => {
+                let len = self.slots_used();
+                if len == 0 {
+                    self.crafting_cursor = 0;
+                    return;
+                }
+                let mut idx = self.crafting_cursor as i32 + delta;
+                if idx < 0 {
+                    idx = len as i32 - 1;
+                } else if idx >= len as i32 {
+                    idx = 0;
+                }
+                self.crafting_cursor = idx as usize;
+            }
         }
     }
 
     pub fn selection(&self) -> InvSelection {
         match self.tab {
-            InvTab::Weapons => {
-                if self.weapon_cursor == 0 {
-                    InvSelection::SwordSlot
-                } else {
-                    InvSelection::ShieldSlot
-                }
-            }
-            InvTab::Consumables => {
-                if self.consumables.is_empty() {
-                    InvSelection::None
-                } else {
-                    InvSelection::Consumable(self.consumable_cursor)
-                }
-            }
-            InvTab::Backpack => {
-                if self.backpack.is_empty() {
-                    InvSelection::None
-                } else {
-                    InvSelection::BackpackItem(self.backpack_cursor)
-                }
-            }
+            InvTab::Gear => InvSelection::Gear(GearSlot::ALL[self.gear_cursor.min(GearSlot::ALL.len() - 1)]),
+            InvTab::Consumables => match self.visible_consumables().get(self.consumable_cursor) {
+                Some(&i) => InvSelection::Consumable(i),
+                None => InvSelection::None,
+            },
+            InvTab::Backpack => match self.visible_backpack().get(self.backpack_cursor) {
+                Some(&i) => InvSelection::BackpackItem(i),
+                None => InvSelection::None,
+            },
+            InvTab::Crafting => InvSelection::None,
         }
     }
 
     pub fn take_selected_consumable(&mut self) -> Option<Consumable> {
-        if self.tab != InvTab::Consumables {
-            return None;
+        let InvSelection::Consumable(idx) = self.selection() else { return None };
+        let stack = &mut self.consumables[idx];
+        let item = stack.item.clone();
+        stack.qty -= 1;
+        if stack.qty == 0 {
+            self.consumables.remove(idx);
+            self.clamp_cursors();
         }
-        if self.consumables.is_empty() {
-            return None;
+        Some(item)
+    }
+
+    pub fn slots_used(&self) -> usize {
+        self.consumables.len() + self.backpack.len()
+    }
+
+    pub fn free_slots(&self) -> usize {
+        self.capacity.saturating_sub(self.slots_used())
+    }
+
+    pub fn equipped(&self, slot: GearSlot) -> Option<&Equipment> {
+        self.gear[slot.index()].as_ref()
+    }
+
+    pub fn gear_mut(&mut self, slot: GearSlot) -> Option<&mut Equipment> {
+        self.gear[slot.index()].as_mut()
+    }
+
+    pub fn equipped_items(&self) -> impl Iterator<Item = &Equipment> {
+        self.gear.iter().flatten()
+    }
+
+    /// Where `eq` would go: the first free slot that accepts it, else the first that does.
+    pub fn slot_for(&self, eq: &Equipment) -> Option<GearSlot> {
+        let mut fitting = GearSlot::ALL.into_iter().filter(|s| s.accepts(eq.slot));
+        let first = fitting.clone().next();
+        fitting.find(|&s| self.equipped(s).is_none()).or(first)
+    }
+
+    pub fn weight(&self) -> u32 {
+        let gear: u32 = self.equipped_items().map(|e| e.weight).sum();
+        let consumables: u32 = self.consumables.iter().map(|s| s.item.weight * s.qty).sum();
+        let backpack: u32 = self.backpack.iter().map(|e| e.weight).sum();
+        gear + consumables + backpack
+    }
+
+    fn fits_weight(&self, extra: u32) -> bool {
+        self.max_weight.is_none_or(|max| self.weight() + extra <= max)
+    }
+
+    fn stack_with_room(&self, item: &Consumable) -> Option<usize> {
+        self.consumables.iter().position(|s| s.item.name == item.name && s.qty < Self::MAX_STACK)
+    }
+
+    pub fn has_room_for_consumable(&self, item: &Consumable) -> bool {
+        self.fits_weight(item.weight) && (self.stack_with_room(item).is_some() || self.free_slots() > 0)
+    }
+
+    pub fn has_room_for_equipment(&self, eq: &Equipment) -> bool {
+        self.fits_weight(eq.weight) && self.free_slots() > 0
+    }
+
+    /// Stack onto an existing pile if possible, otherwise take a new slot. Hands the item back when full.
+    pub fn add_consumable(&mut self, item: Consumable) -> Result<(), Consumable> {
+        if !self.has_room_for_consumable(&item) {
+            return Err(item);
         }
-        let idx = self.consumable_cursor.min(self.consumables.len() - 1);
-        Some(self.consumables.remove(idx))
+        match self.stack_with_room(&item) {
+            Some(i) => self.consumables[i].qty += 1,
+            None => self.consumables.push(ConsumableStack { item, qty: 1 }),
+        }
+        Ok(())
+    }
+
+    pub fn add_equipment(&mut self, eq: Equipment) -> Result<(), Equipment> {
+        if !self.has_room_for_equipment(&eq) {
+            return Err(eq);
+        }
+        self.backpack.push(eq);
+        Ok(())
+    }
+
+    /// Labels for every occupied slot, consumable stacks first, in `remove_slot` order.
+    pub fn slot_labels(&self) -> Vec<String> {
+        let stacks = self.consumables.iter().map(|s| SlotItem::Stack(s.clone()).label());
+        stacks.chain(self.backpack.iter().map(|e| e.name.clone())).collect()
+    }
+
+    /// Name of the item in a slot, in `slot_labels` order.
+    pub fn slot_name(&self, idx: usize) -> Option<&str> {
+        match self.consumables.get(idx) {
+            Some(s) => Some(&s.item.name),
+            None => self.backpack.get(idx - self.consumables.len()).map(|e| e.name.as_str()),
+        }
+    }
+
+    /// Mark one more unit of the slot under the crafting cursor, wrapping back to none.
+    pub fn toggle_craft_mark(&mut self) {
+        let idx = self.crafting_cursor;
+        let max = match self.consumables.get(idx) {
+            Some(s) => s.qty,
+            None if idx < self.slots_used() => 1,
+            None => return,
+        };
+        self.craft_marks.resize(self.slots_used(), 0);
+        self.craft_marks[idx] = (self.craft_marks[idx] + 1) % (max + 1);
+    }
+
+    /// Marked ingredients as (slot, units).
+    pub fn marked_ingredients(&self) -> Vec<(usize, u32)> {
+        self.craft_marks.iter().enumerate().filter(|&(_, &n)| n > 0).map(|(i, &n)| (i, n)).collect()
+    }
+
+    /// Put a single item (gear, or one unit of a stack) into the pack.
+    pub fn add_item(&mut self, item: SlotItem) -> Result<(), SlotItem> {
+        match item {
+            SlotItem::Gear(eq) => self.add_equipment(eq).map_err(SlotItem::Gear),
+            SlotItem::Stack(s) => self
+                .add_consumable(s.item)
+                .map_err(|item| SlotItem::Stack(ConsumableStack { item, qty: 1 })),
+        }
+    }
+
+    /// Take a single item out of a slot (one unit of a stack), in `slot_labels` order.
+    pub fn take_one(&mut self, idx: usize) -> Option<SlotItem> {
+        if let Some(stack) = self.consumables.get_mut(idx)
+            && stack.qty > 1
+        {
+            stack.qty -= 1;
+            return Some(SlotItem::Stack(ConsumableStack { item: stack.item.clone(), qty: 1 }));
+        }
+        self.remove_slot(idx)
+    }
+
+    /// Empty one slot (a whole stack or a backpack item), in `slot_labels` order.
+    pub fn remove_slot(&mut self, idx: usize) -> Option<SlotItem> {
+        let item = if idx < self.consumables.len() {
+            SlotItem::Stack(self.consumables.remove(idx))
+        } else if idx - self.consumables.len() < self.backpack.len() {
+            SlotItem::Gear(self.backpack.remove(idx - self.consumables.len()))
+        } else {
+            return None;
+        };
+        self.clamp_cursors();
+        Some(item)
     }
 }
 
@@ -192,6 +742,20 @@ pub struct Player {
 
     pub inventory: Inventory,
     pub buffs: Vec<TempBuff>,
+    pub gold: u32,
+    /// The water tile the last step started wading into; the next step there gets in.
+    pub wading: Option<(i32, i32)>,
+}
+
+/// What a step did, so the world can react to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Blocked,
+    Moved,
+    /// Halfway into water; take the same step again to get there.
+    Wading,
+    /// Moved, then slid this many more tiles across ice.
+    Slid(u32),
 }
 
 impl Player {
@@ -207,10 +771,13 @@ impl Player {
             base_speed: 5,
             inventory: Inventory::default_loadout(),
             buffs: Vec::new(),
+            gold: 0,
+            wading: None,
         }
     }
 
-    pub fn add_temp_buff(&mut self, atk: i32, def: i32, speed: i32, duration: Duration) {
+    /// A buff that lasts until game-clock tick `expires_at`.
+    pub fn add_temp_buff(&mut self, atk: i32, def: i32, speed: i32, expires_at: u64) {
         if atk == 0 && def == 0 && speed == 0 {
             return;
         }
@@ -218,89 +785,90 @@ impl Player {
             atk_bonus: atk,
             def_bonus: def,
             speed_bonus: speed,
-            expires_at: Instant::now() + duration,
+            expires_at,
         });
     }
 
-    pub fn purge_expired_buffs(&mut self) {
-        let now = Instant::now();
+    pub fn purge_expired_buffs(&mut self, now: u64) {
         self.buffs.retain(|b| b.expires_at > now);
     }
 
+    fn gear_sum(&self, bonus: impl Fn(&Equipment) -> i32) -> i32 {
+        self.inventory.equipped_items().map(bonus).sum()
+    }
+
+    /// Buffs are purged as the game clock passes them, so every one still held counts.
     fn active_buff_sums(&self) -> (i32, i32, i32) {
-        let now = Instant::now();
         let mut atk = 0;
         let mut def = 0;
         let mut spd = 0;
 
         for b in &self.buffs {
-            if b.expires_at > now {
-                atk += b.atk_bonus;
-                def += b.def_bonus;
-                spd += b.speed_bonus;
-            }
+            atk += b.atk_bonus;
+            def += b.def_bonus;
+            spd += b.speed_bonus;
         }
         (atk, def, spd)
     }
 
     pub fn attack(&self) -> i32 {
-        let mut v = self.base_attack;
-        if let Some(sw) = &self.inventory.sword {
-            v += sw.atk_bonus;
-        }
-        if let Some(sh) = &self.inventory.shield {
-            v += sh.atk_bonus;
-        }
         let (atk_b, _, _) = self.active_buff_sums();
-        v += atk_b;
-        v
+        self.base_attack + self.gear_sum(|e| e.effective(e.atk_bonus)) + atk_b
     }
 
     pub fn defense(&self) -> i32 {
-        let mut v = self.base_defense;
-        if let Some(sw) = &self.inventory.sword {
-            v += sw.def_bonus;
-        }
-        if let Some(sh) = &self.inventory.shield {
-            v += sh.def_bonus;
-        }
         let (_, def_b, _) = self.active_buff_sums();
-        v += def_b;
-        v
+        self.base_defense + self.gear_sum(|e| e.effective(e.def_bonus)) + def_b
     }
 
     pub fn speed(&self) -> i32 {
-        let mut v = self.base_speed;
-        if let Some(sw) = &self.inventory.sword {
-            v += sw.speed_bonus;
-        }
-        if let Some(sh) = &self.inventory.shield {
-            v += sh.speed_bonus;
-        }
         let (_, _, spd_b) = self.active_buff_sums();
-        v += spd_b;
-        v
+        self.base_speed + self.gear_sum(|e| e.effective(e.speed_bonus)) + spd_b
     }
 
-    pub fn equip_sword(&mut self, eq: Equipment) {
-        self.inventory.sword = Some(eq);
+    /// Wear `eq` in the slot `Inventory::slot_for` picks, returning whatever it replaced.
+    pub fn equip(&mut self, eq: Equipment) -> Option<Equipment> {
+        let slot = self.inventory.slot_for(&eq)?;
+        let old = self.unequip(slot);
+        self.max_hp += eq.hp_bonus;
+        if self.hp > self.max_hp { self.hp = self.max_hp; }
+        self.inventory.gear[slot.index()] = Some(eq);
+        old
     }
 
-    pub fn equip_shield(&mut self, eq: Equipment) {
-        self.inventory.shield = Some(eq);
+    /// Take off whatever is in `slot`; Max HP (and HP, if needed) drop with it.
+    pub fn unequip(&mut self, slot: GearSlot) -> Option<Equipment> {
+        let old = self.inventory.gear[slot.index()].take()?;
+        self.max_hp -= old.hp_bonus;
+        if self.hp > self.max_hp { self.hp = self.max_hp; }
+        Some(old)
     }
 
-    pub fn try_move(&mut self, dx: i32, dy: i32, map: &Map) {
+    /// Step one tile, unless `occupied` (someone's standing there) or the map says no.
+    /// Water takes two steps to enter, and ice carries you on until something stops you.
+    pub fn try_move(&mut self, dx: i32, dy: i32, map: &Map, occupied: impl Fn(i32, i32) -> bool) -> Step {
+        let open = |x: i32, y: i32| map.in_bounds(x, y) && map.is_walkable(x as usize, y as usize) && !occupied(x, y);
         let nx = self.x + dx;
         let ny = self.y + dy;
 
-        if nx < 0 || ny < 0 || nx >= map.width as i32 || ny >= map.height as i32 {
-            return;
+        if !open(nx, ny) {
+            self.wading = None;
+            return Step::Blocked;
         }
-
-        if map.is_walkable(nx as usize, ny as usize) {
-            self.x = nx;
-            self.y = ny;
+        if map.get(nx as usize, ny as usize) == Tile::Water && self.wading != Some((nx, ny)) {
+            self.wading = Some((nx, ny));
+            return Step::Wading;
+        }
+        self.wading = None;
+        self.x = nx;
+        self.y = ny;
+
+        let mut slid = 0;
+        while map.get(self.x as usize, self.y as usize) == Tile::Ice && open(self.x + dx, self.y + dy) {
+            self.x += dx;
+            self.y += dy;
+            slid += 1;
         }
+        if slid > 0 { Step::Slid(slid) } else { Step::Moved }
     }
 }
\ No newline at end of file
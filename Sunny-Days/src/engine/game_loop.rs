@@ -1,24 +1,44 @@
 use crate::audio::Music;
+use crate::config::Config;
 use crate::engine::action::Action;
+use crate::engine::replay::{self, Playback, Replay};
 use crate::engine::world::{World, GameState};
-use crate::tui::{input::is_press, renderer::render};
+use crate::tui::dev::{self, DevTools};
+use crate::tui::{input::is_press, renderer::{map_tile_at, render}, theme::Theme};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    widgets::{Clear, Paragraph},
+    Terminal,
+};
 
 use std::{
-    io,
+    io::{self, Stdout},
     time::{Duration, Instant},
 };
 
 const MOVE_COOLDOWN_MS: u64 = 90;
+/// Ticks the loop will catch up on after a stall (a suspended terminal, a slow link) before
+/// it lets the lost time go.
+const MAX_CATCH_UP_TICKS: u32 = 10;
+
+type Term = Terminal<CrosstermBackend<Stdout>>;
+
+/// Take over the terminal and play until the player quits or dies (or watch a replay).
+pub fn run(config: Config) -> std::io::Result<()> {
+    // Read the recording before touching the terminal, so a bad file is a plain error.
+    let replay = match &config.replay {
+        Some(path) => Some(Replay::load(path).map_err(io::Error::other)?),
+        None => None,
+    };
 
-pub fn run() -> std::io::Result<()> {
     let _music = match Music::start_loop("assets/Background1.mp3") {
         Ok(m) => Some(m),
         Err(e) => {
@@ -34,14 +54,79 @@ pub fn run() -> std::io::Result<()> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let seed = rand::random::<u64>();
-    let mut world = World::new(seed, 80, 45);
+    let theme = config.theme();
+    let mut world = match &replay {
+        Some(replay) => replay.world(),
+        None => {
+            let mut world = World::new(config.seed.unwrap_or_else(rand::random), 80, 45);
+            world.settings = config.settings.clone();
+            world.player.inventory.max_weight = world.settings.carry_limit;
+            world
+        }
+    };
+    let mut recorder = config.record.as_ref().map(|_| Replay::new(world.seed, world.settings.clone()));
+
+    let outcome = match &replay {
+        Some(replay) => play_back(&mut terminal, &mut world, replay, config.replay_speed, &theme),
+        None => {
+            let mut dev = config.dev.then(DevTools::default);
+            play(&mut terminal, &mut world, recorder.as_mut(), dev.as_mut(), &theme).map(|()| true)
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+    let finished = outcome?;
+
+    if let (Some(path), Some(mut recording)) = (&config.record, recorder) {
+        recording.finish(&world);
+        match recording.save(path) {
+            Ok(()) => println!("Recorded {} inputs to {}.", recording.inputs.len(), path.display()),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+    if let Some(replay) = &replay {
+        if !finished {
+            println!("Replay stopped at tick {}.", world.ticks);
+        } else {
+            match replay::verify(replay, &world) {
+                Ok(hash) => println!("Replay finished: the final state matches ({:016x}).", hash),
+                Err(e) => {
+                    eprintln!("Replay diverged: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
 
-    let tick_rate = Duration::from_millis(60);
+    if world.player.hp <= 0 {
+        println!("You died.");
+    }
+
+    Ok(())
+}
+
+/// The live game. Every input the world accepts also goes to `recorder`, if there is one;
+/// with `dev`, F1 and ` open the developer overlay and console.
+fn play(
+    terminal: &mut Term,
+    world: &mut World,
+    mut recorder: Option<&mut Replay>,
+    mut dev: Option<&mut DevTools>,
+    theme: &Theme,
+) -> io::Result<()> {
+    let tick_rate = Duration::from_millis(World::TICK_MS as u64);
+    let mut last_tick = Instant::now();
     let mut last_move_time = Instant::now() - Duration::from_millis(MOVE_COOLDOWN_MS);
-    
-    // Track last battle input for 10s penalty
-    let mut last_battle_input = Instant::now();
+    let (mut frames, mut fps_since) = (0u32, Instant::now());
+    // Only draw when something on screen may have changed: an input, a resize, or a tick that
+    // moved an animation or timer. Idle in the overworld, the loop just waits for keys.
+    let mut dirty = true;
 
     let mut running = true;
     while running {
@@ -52,32 +137,71 @@ pub fn run() -> std::io::Result<()> {
             break;
         }
 
-        if let Err(_) = terminal.draw(|f| render(f, &world)) {
-            terminal.autoresize()?;
-            terminal.clear()?;
-            continue;
+        // Fixed time-step: animations and timers advance once per 60ms of real time, however
+        // often frames are drawn; ticks missed while the loop was busy are caught up on.
+        let mut behind = 0;
+        while last_tick.elapsed() >= tick_rate {
+            dirty |= world.tick();
+            last_tick += tick_rate;
+            behind += 1;
+            if behind == MAX_CATCH_UP_TICKS { last_tick = Instant::now(); }
+        }
+        if behind > 0 && dev.as_deref().is_some_and(|d| d.overlay) { dirty = true; }
+
+        if dirty {
+            let drawn = terminal.draw(|f| {
+                render(f, world, theme);
+                if let Some(dev) = dev.as_deref() { dev::draw(f, world, dev, theme); }
+            });
+            if drawn.is_err() {
+                terminal.autoresize()?;
+                terminal.clear()?;
+                continue;
+            }
+            dirty = false;
+            frames += 1;
+        }
+        if let Some(dev) = dev.as_deref_mut() {
+            let elapsed = fps_since.elapsed();
+            if elapsed >= Duration::from_secs(1) {
+                dev.fps = frames as f32 / elapsed.as_secs_f32();
+                (frames, fps_since) = (0, Instant::now());
+            }
         }
 
-        if event::poll(tick_rate)? {
+        if event::poll(tick_rate.saturating_sub(last_tick.elapsed()))? {
             match event::read()? {
                 Event::Resize(_, _) => {
                     terminal.autoresize()?;
                     terminal.clear()?;
+                    dirty = true;
+                }
+
+                Event::Mouse(mouse) => {
+                    if let (Some(dev), MouseEventKind::Moved) = (dev.as_deref_mut(), mouse.kind) {
+                        let cursor = map_tile_at(terminal.size()?, world, mouse.column, mouse.row);
+                        dirty |= dev.overlay && cursor != dev.cursor;
+                        dev.cursor = cursor;
+                    }
                 }
 
                 Event::Key(key) => {
                     if !is_press(&key) {
                         continue;
                     }
+                    dirty = true;
 
                     // Quit with Ctrl+C anywhere
-                    if key.modifiers.contains(KeyModifiers::CONTROL) {
-                        if let KeyCode::Char('c') | KeyCode::Char('q') = key.code {
-                            running = world.apply_action(Action::Quit);
-                            continue;
-                        }
+                    if key.modifiers.contains(KeyModifiers::CONTROL)
+                        && let KeyCode::Char('c') | KeyCode::Char('q') = key.code
+                    {
+                        if let Some(r) = recorder.as_deref_mut() { r.record(world.ticks, Action::Quit); }
+                        running = world.apply_action(Action::Quit);
+                        continue;
                     }
 
+                    if let Some(dev) = dev.as_deref_mut() && dev_key(dev, world, key.code) { continue; }
+
                     let mut action = match world.state {
                         GameState::Title | GameState::Intro => match key.code {
                             KeyCode::Char(' ') | KeyCode::Enter | KeyCode::Char('e') | KeyCode::Char('E') => Action::Confirm,
@@ -90,6 +214,15 @@ pub fn run() -> std::io::Result<()> {
                             _ => Action::None,
                         },
                         
+                        GameState::Shop => match key.code {
+                            KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => Action::InventoryUp,
+                            KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => Action::InventoryDown,
+                            KeyCode::Char('t') | KeyCode::Char('T') | KeyCode::Tab => Action::ToggleInvTab,
+                            KeyCode::Char(' ') | KeyCode::Enter => Action::Confirm,
+                            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => Action::LeaveShop,
+                            _ => Action::None,
+                        },
+
                         GameState::Fin => match key.code {
                             // Wait for quit via Ctrl+C (handled above) or maybe 'q'
                             KeyCode::Char('q') | KeyCode::Char('Q') => Action::Quit,
@@ -106,21 +239,13 @@ pub fn run() -> std::io::Result<()> {
                                     _ => Action::None,
                                 }
                             } else {
-                                let now = Instant::now();
-                                let elapsed = now.duration_since(last_battle_input);
-                                let penalty = elapsed.as_secs() >= 10;
-                                
-                                let act = match key.code {
-                                    KeyCode::Char('1') => Action::BattleOption(1, penalty),
-                                    KeyCode::Char('2') => Action::BattleOption(2, penalty),
-                                    KeyCode::Char('3') => Action::BattleOption(3, penalty),
+                                match key.code {
+                                    KeyCode::Char('1') => Action::BattleOption(1),
+                                    KeyCode::Char('2') => Action::BattleOption(2),
+                                    KeyCode::Char('3') => Action::BattleOption(3),
+                                    KeyCode::Char('p') | KeyCode::Char('P') => Action::ToggleBattleTimer,
                                     _ => Action::None,
-                                };
-                                
-                                if !matches!(act, Action::None) {
-                                    last_battle_input = now;
                                 }
-                                act
                             }
                         },
 
@@ -138,12 +263,18 @@ pub fn run() -> std::io::Result<()> {
                                     KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => Action::InventoryUp,
                                     KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => Action::InventoryDown,
                                     KeyCode::Char(' ') => Action::UseConsumable,
+                                    KeyCode::Char('e') | KeyCode::Char('E') => Action::InspectItem,
+                                    KeyCode::Char('d') | KeyCode::Char('D') => Action::DropItem,
+                                    KeyCode::Char('o') | KeyCode::Char('O') => Action::CycleSort,
+                                    KeyCode::Char('f') | KeyCode::Char('F') => Action::CycleFilter,
+                                    KeyCode::Char('c') | KeyCode::Char('C') => Action::Craft,
                                     _ => Action::None,
                                 }
                             } else {
                                 match key.code {
                                     KeyCode::Char('q') | KeyCode::Char('Q') => Action::ToggleStats,
                                     KeyCode::Char('i') | KeyCode::Char('I') => Action::ToggleInventory,
+                                    KeyCode::Char('m') | KeyCode::Char('M') => Action::ToggleOverview,
                                     KeyCode::Char('e') | KeyCode::Char('E') => Action::Interact,
 
                                     KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => Action::Move(0, -1),
@@ -166,12 +297,8 @@ pub fn run() -> std::io::Result<()> {
                         }
                     }
 
-                    // If transitioning INTO Battle, reset timer
-                    let old_state = world.state.clone();
+                    if let Some(r) = recorder.as_deref_mut() { r.record(world.ticks, action); }
                     running = world.apply_action(action);
-                    if old_state != GameState::Battle && world.state == GameState::Battle {
-                        last_battle_input = Instant::now();
-                    }
                 }
 
                 _ => {}
@@ -180,18 +307,64 @@ pub fn run() -> std::io::Result<()> {
             running = world.apply_action(Action::None);
         }
     }
+    Ok(())
+}
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
-    
-    if world.player.hp <= 0 {
-        println!("You died.");
+/// Keys for the developer overlay and console. Returns whether the key was theirs.
+fn dev_key(dev: &mut DevTools, world: &mut World, code: KeyCode) -> bool {
+    let console = &mut dev.console;
+    match code {
+        KeyCode::F(1) => dev.overlay = !dev.overlay,
+        KeyCode::Char('`') => console.toggle(),
+        _ if !console.open => return false,
+        KeyCode::Esc => console.toggle(),
+        KeyCode::Enter => console.submit(world),
+        KeyCode::Backspace => { console.input.pop(); }
+        KeyCode::Up => console.recall(true),
+        KeyCode::Down => console.recall(false),
+        KeyCode::Char(c) => console.input.push(c),
+        _ => {}
     }
+    true
+}
 
-    Ok(())
-}
\ No newline at end of file
+/// Watch a recording at `speed` times real time (0: as fast as possible). Q or Esc stops early.
+/// Returns whether it played to the end.
+fn play_back(terminal: &mut Term, world: &mut World, replay: &Replay, speed: f32, theme: &Theme) -> io::Result<bool> {
+    let tick_rate = if speed > 0.0 {
+        Duration::from_millis(World::TICK_MS as u64).div_f32(speed)
+    } else {
+        Duration::ZERO
+    };
+    let mut playback = Playback::new(replay);
+
+    loop {
+        let started = Instant::now();
+        if !playback.step(world) {
+            return Ok(playback.is_done(world));
+        }
+
+        // Flat out, only every 50th tick is worth drawing.
+        if speed > 0.0 || world.ticks.is_multiple_of(50) {
+            let (done, total) = playback.progress();
+            let badge = format!(" REPLAY {}/{} x{} (Q to stop) ", done, total, speed);
+            terminal.draw(|f| {
+                render(f, world, theme);
+                let size = f.size();
+                let w = (badge.chars().count() as u16).min(size.width);
+                let area = Rect { x: size.width - w, y: 0, width: w, height: 1.min(size.height) };
+                f.render_widget(Clear, area);
+                f.render_widget(Paragraph::new(badge.as_str()).style(theme.title), area);
+            })?;
+        }
+
+        if event::poll(tick_rate.saturating_sub(started.elapsed()))?
+            && let Event::Key(key) = event::read()?
+        {
+            let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c');
+            if is_press(&key) && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc)) {
+                return Ok(false);
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Rolled chest loot.
+//!
+//! Each level gets its own RNG seeded from the level seed, so the same world seed always
+//! fills the same chests with the same things. Deeper levels roll rarer, stronger items.
+
+use crate::engine::entity::{Consumable, EquipSlot, Equipment, Rarity};
+
+use rand::Rng;
+
+/// What a chest holds.
+#[derive(Debug, Clone)]
+pub enum Loot {
+    Gear(Equipment),
+    Consumable(Consumable),
+    Gold(u32),
+}
+
+/// Relative odds of each kind of chest content.
+#[derive(Debug, Clone, Copy)]
+pub struct LootTable {
+    pub gear: u32,
+    pub consumable: u32,
+    pub gold: u32,
+}
+
+impl LootTable {
+    /// The first room is mostly supplies; further in, gear and gold get likelier.
+    pub fn for_depth(depth: usize) -> Self {
+        match depth {
+            0 => Self { gear: 3, consumable: 5, gold: 2 },
+            _ => Self { gear: 5, consumable: 3, gold: 3 },
+        }
+    }
+
+    pub fn roll<R: Rng>(&self, rng: &mut R, depth: usize) -> Loot {
+        let pick = rng.gen_range(0..self.gear + self.consumable + self.gold);
+        if pick < self.gear {
+            Loot::Gear(roll_equipment(rng, depth))
+        } else if pick < self.gear + self.consumable {
+            Loot::Consumable(roll_consumable(rng))
+        } else {
+            let depth = depth as u32;
+            Loot::Gold(rng.gen_range(5 + 10 * depth..=15 + 20 * depth))
+        }
+    }
+}
+
+struct Base {
+    name: &'static str,
+    slot: EquipSlot,
+    hp: i32,
+    atk: i32,
+    def: i32,
+    spd: i32,
+    weight: u32,
+}
+
+const fn base(name: &'static str, slot: EquipSlot, hp: i32, atk: i32, def: i32, spd: i32, weight: u32) -> Base {
+    Base { name, slot, hp, atk, def, spd, weight }
+}
+
+const BASES: &[Base] = &[
+    base("Short Sword", EquipSlot::Sword, 0, 3, 0, 1, 3),
+    base("Hand Axe", EquipSlot::Sword, 0, 5, 0, -1, 5),
+    base("Spear", EquipSlot::Sword, 0, 4, 1, 0, 4),
+    base("Buckler", EquipSlot::Shield, 0, 0, 2, 0, 2),
+    base("Kite Shield", EquipSlot::Shield, 2, 0, 4, -2, 7),
+    base("Leather Cap", EquipSlot::Helmet, 0, 0, 1, 0, 1),
+    base("Iron Helm", EquipSlot::Helmet, 2, 0, 2, -1, 3),
+    base("Padded Vest", EquipSlot::Armour, 2, 0, 2, 0, 3),
+    base("Chain Armour", EquipSlot::Armour, 4, 0, 4, -2, 8),
+    base("Swift Boots", EquipSlot::Boots, 0, 0, 0, 2, 1),
+    base("Hobnail Boots", EquipSlot::Boots, 0, 0, 2, -1, 2),
+    base("Copper Ring", EquipSlot::Ring, 3, 0, 0, 0, 0),
+    base("Sun Ring", EquipSlot::Ring, 0, 2, 0, 0, 0),
+];
+
+/// A named modifier added on top of a base item: HP, attack, defense and speed, each
+/// multiplied by the depth strength before it is added.
+struct Affix {
+    name: &'static str,
+    hp: i32,
+    atk: i32,
+    def: i32,
+    spd: i32,
+}
+
+const fn affix(name: &'static str, hp: i32, atk: i32, def: i32, spd: i32) -> Affix {
+    Affix { name, hp, atk, def, spd }
+}
+
+const PREFIXES: &[Affix] = &[
+    affix("Fleet", 0, 0, 0, 1),
+    affix("Keen", 0, 1, 0, 0),
+    affix("Sturdy", 1, 0, 1, 0),
+    affix("Brutal", 0, 2, 0, -1),
+    affix("Sunlit", 1, 1, 0, 0),
+];
+
+const SUFFIXES: &[Affix] = &[
+    affix("of the Willow", 2, 0, 0, 0),
+    affix("of the Sun", 0, 1, 0, 1),
+    affix("of Tears", 0, 0, 2, -1),
+    affix("of the Wind", 0, 0, 0, 2),
+    affix("of Embers", 0, 2, -1, 0),
+];
+
+/// Rarity odds shift upward the deeper you go.
+fn roll_rarity<R: Rng>(rng: &mut R, depth: usize) -> Rarity {
+    let roll = rng.gen_range(0..100) + 8 * depth as i32;
+    match roll {
+        ..=54 => Rarity::Common,
+        55..=81 => Rarity::Uncommon,
+        82..=93 => Rarity::Rare,
+        94..=98 => Rarity::Epic,
+        _ => Rarity::Legendary,
+    }
+}
+
+/// A random base item with a rarity, affixes scaled by depth, and stats scaled by rarity.
+pub fn roll_equipment<R: Rng>(rng: &mut R, depth: usize) -> Equipment {
+    let b = &BASES[rng.gen_range(0..BASES.len())];
+    let rarity = roll_rarity(rng, depth);
+
+    let (mut hp, mut atk, mut def, mut spd) = (b.hp, b.atk, b.def, b.spd);
+    let strength = 1 + depth as i32;
+    let mut add = |a: &Affix| {
+        hp += a.hp * strength;
+        atk += a.atk * strength;
+        def += a.def * strength;
+        spd += a.spd * strength;
+        a.name
+    };
+
+    // Uncommon gets one affix, Rare and up get both.
+    let (want_prefix, want_suffix) = match rarity {
+        Rarity::Common => (false, false),
+        Rarity::Uncommon => { let p = rng.gen_bool(0.5); (p, !p) }
+        _ => (true, true),
+    };
+    let prefix = want_prefix.then(|| add(&PREFIXES[rng.gen_range(0..PREFIXES.len())]));
+    let suffix = want_suffix.then(|| add(&SUFFIXES[rng.gen_range(0..SUFFIXES.len())]));
+
+    let scale = |v: i32| if v > 0 { v * rarity.stat_percent() / 100 } else { v };
+    let name = match (prefix, suffix) {
+        (Some(p), Some(s)) => format!("{} {} {}", p, b.name, s),
+        (Some(p), None) => format!("{} {}", p, b.name),
+        (None, Some(s)) => format!("{} {}", b.name, s),
+        (None, None) => b.name.to_string(),
+    };
+
+    Equipment {
+        name,
+        slot: b.slot,
+        hp_bonus: scale(hp),
+        atk_bonus: scale(atk),
+        def_bonus: scale(def),
+        speed_bonus: scale(spd),
+        weight: b.weight,
+        rarity,
+        durability: rarity.durability(),
+        max_durability: rarity.durability(),
+    }
+}
+
+pub fn roll_consumable<R: Rng>(rng: &mut R) -> Consumable {
+    let mut all = consumables();
+    all.swap_remove(rng.gen_range(0..all.len()))
+}
+
+/// Every consumable a chest can hold.
+pub fn consumables() -> Vec<Consumable> {
+    vec![
+        Consumable { name: "Fiery ale".to_string(), heal: 2, atk_bonus: 2, def_bonus: 0, weight: 2, repair: 0 },
+        Consumable { name: "Weeping Willow bark".to_string(), heal: 3, atk_bonus: 0, def_bonus: 0, weight: 1, repair: 0 },
+        Consumable { name: "Sunny Jerky".to_string(), heal: 5, atk_bonus: 0, def_bonus: 0, weight: 1, repair: 0 },
+        Consumable { name: "Whetstone".to_string(), heal: 0, atk_bonus: 0, def_bonus: 0, weight: 1, repair: 10 },
+        Consumable { name: "Frozen tears".to_string(), heal: -2, atk_bonus: 0, def_bonus: 5, weight: 1, repair: 0 },
+    ]
+}
+
+/// A plain Common base item ("Spear", "Kite Shield", ...), with no affixes.
+pub fn base_item(name: &str) -> Option<Equipment> {
+    let b = BASES.iter().find(|b| b.name.eq_ignore_ascii_case(name))?;
+    let rarity = Rarity::Common;
+    Some(Equipment {
+        name: b.name.to_string(),
+        slot: b.slot,
+        hp_bonus: b.hp,
+        atk_bonus: b.atk,
+        def_bonus: b.def,
+        speed_bonus: b.spd,
+        weight: b.weight,
+        rarity,
+        durability: rarity.durability(),
+        max_durability: rarity.durability(),
+    })
+}
+
+/// Names of all the base items, for listing.
+pub fn base_names() -> impl Iterator<Item = &'static str> {
+    BASES.iter().map(|b| b.name)
+}
+
+/// The base item type inside a rolled name like "Keen Spear of Tears".
+pub fn base_name_in(name: &str) -> Option<&'static str> {
+    BASES.iter().map(|b| b.name).find(|b| name.contains(b))
+}
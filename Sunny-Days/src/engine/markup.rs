@@ -0,0 +1,97 @@
+//! Inline markup for dialogue pages.
+//!
+//! Every tag is spelled out in braces: `{b:text}` is bold, `{i:text}` is italic and
+//! `{red:text}` tints a run. Anything else, asterisks included, is plain text, so lines
+//! like " *sniffles* " read as written. A backslash escapes the next character. Tags may
+//! nest: `{b:{red:NO}}`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tint {
+    Red,
+    Gold,
+    Blue,
+    Green,
+    Grey,
+}
+
+/// An open `{name:` waiting for its closing brace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    Bold,
+    Italic,
+    Tint(Tint),
+}
+
+impl Tag {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "b" => Some(Tag::Bold),
+            "i" => Some(Tag::Italic),
+            "red" => Some(Tag::Tint(Tint::Red)),
+            "gold" | "yellow" => Some(Tag::Tint(Tint::Gold)),
+            "blue" => Some(Tag::Tint(Tint::Blue)),
+            "green" => Some(Tag::Tint(Tint::Green)),
+            "grey" | "gray" => Some(Tag::Tint(Tint::Grey)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub tint: Option<Tint>,
+}
+
+/// Split marked-up text into styled runs. Newlines are kept inside the runs.
+pub fn parse(src: &str) -> Vec<Segment> {
+    let mut out: Vec<Segment> = Vec::new();
+    let mut open: Vec<Tag> = Vec::new();
+    let mut buf = String::new();
+
+    let flush = |buf: &mut String, out: &mut Vec<Segment>, open: &[Tag]| {
+        if !buf.is_empty() {
+            out.push(Segment {
+                text: std::mem::take(buf),
+                bold: open.contains(&Tag::Bold),
+                italic: open.contains(&Tag::Italic),
+                tint: open.iter().rev().find_map(|t| match t { Tag::Tint(t) => Some(*t), _ => None }),
+            });
+        }
+    };
+
+    let mut chars = src.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() { buf.push(next); }
+            }
+            '{' => {
+                // Only treat it as a tag when it names a known one; otherwise it's literal text.
+                let rest: String = chars.clone().take_while(|&ch| ch != ':' && ch != '}').collect();
+                match Tag::from_name(&rest) {
+                    Some(t) if chars.clone().nth(rest.chars().count()) == Some(':') => {
+                        flush(&mut buf, &mut out, &open);
+                        for _ in 0..=rest.chars().count() { chars.next(); }
+                        open.push(t);
+                    }
+                    _ => buf.push(c),
+                }
+            }
+            '}' if !open.is_empty() => {
+                flush(&mut buf, &mut out, &open);
+                open.pop();
+            }
+            _ => buf.push(c),
+        }
+    }
+    flush(&mut buf, &mut out, &open);
+    out
+}
+
+/// Number of characters a reader actually sees once markup is stripped.
+pub fn visible_len(src: &str) -> usize {
+    parse(src).iter().map(|s| s.text.chars().count()).sum()
+}
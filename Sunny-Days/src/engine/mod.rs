@@ -1,4 +1,15 @@
-pub mod action;
-pub mod entity;
-pub mod game_loop;
-pub mod world;
+pub(crate) mod action;
+pub mod balance;
+pub mod bot;
+pub(crate) mod clock;
+pub(crate) mod console;
+pub(crate) mod crafting;
+pub(crate) mod entity;
+pub(crate) mod game_loop;
+pub(crate) mod loot;
+pub(crate) mod markup;
+pub(crate) mod objects;
+pub(crate) mod replay;
+pub(crate) mod settings;
+pub(crate) mod shop;
+pub(crate) mod world;
@@ -0,0 +1,159 @@
+//! Things in a level you can use besides chests and the room door.
+//!
+//! Each `Level` owns its objects, so a pulled lever or a spent trap stays that way when you
+//! come back. The tile under an object says how it blocks movement; the object holds its state.
+
+use crate::map::{tile::Tile, Map};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const OBJECT_SALT: u64 = 0x0B1E;
+
+/// The key the first room's hidden vault holds, and the second room's locked vault wants.
+pub const VAULT_KEY: &str = "Willow Key";
+
+#[derive(Debug, Clone)]
+pub enum ObjectKind {
+    LockedDoor { key: String },
+    /// Opens the gate at the given tile; it stays open.
+    Lever { gate: (i32, i32), pulled: bool },
+    CrackedWall,
+    Fountain { charges: u32 },
+    /// Hidden until it goes off; it only goes off once.
+    Trap { damage: i32, sprung: bool },
+    Sign { text: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct WorldObject {
+    pub x: i32,
+    pub y: i32,
+    pub kind: ObjectKind,
+}
+
+/// A sealed 3x3 room carved out of solid wall, with a chest in the middle.
+#[derive(Debug, Clone, Copy)]
+pub struct Vault {
+    pub entrance: (i32, i32),
+    pub chest: (i32, i32),
+}
+
+/// Objects for one level, plus where its vault chests go (the world fills those).
+#[derive(Debug, Clone, Default)]
+pub struct Furnishing {
+    pub objects: Vec<WorldObject>,
+    /// Chest position and the key it holds, if any.
+    pub vault_chests: Vec<((i32, i32), Option<String>)>,
+}
+
+/// Carve vaults and place objects. `taken` are tiles that must stay clear (spawn, door, chests).
+/// Everything goes where a walk from `entry`, the player's first step in the level, gets to.
+/// The second room only gets its locked vault if `key_placed` says the first room holds the key.
+pub fn furnish(map: &mut Map, seed: u64, depth: usize, entry: (i32, i32), key_placed: bool, taken: &[(i32, i32)]) -> Furnishing {
+    let mut rng = StdRng::seed_from_u64(seed ^ OBJECT_SALT);
+    let mut out = Furnishing::default();
+    let mut taken = taken.to_vec();
+    // Vaults dig into solid wall and objects sit in the open, so none of them cut this off.
+    let reach = map.reachable_from(entry);
+
+    if depth == 0 {
+        // A cracked wall hides the key; fall back to an ordinary chest if there's no room to dig.
+        match carve_vault(map, &mut rng, &reach, &taken, Tile::CrackedWall) {
+            Some(v) => {
+                out.objects.push(WorldObject { x: v.entrance.0, y: v.entrance.1, kind: ObjectKind::CrackedWall });
+                out.vault_chests.push((v.chest, Some(VAULT_KEY.to_string())));
+            }
+            None => {
+                if let Some(p) = open_floor(map, &mut rng, &reach, &taken) {
+                    map.set(p.0 as usize, p.1 as usize, Tile::Chest);
+                    out.vault_chests.push((p, Some(VAULT_KEY.to_string())));
+                }
+            }
+        }
+    } else {
+        // Without the key in the first room, a locked vault would stay sealed for good.
+        if key_placed && let Some(v) = carve_vault(map, &mut rng, &reach, &taken, Tile::LockedDoor) {
+            out.objects.push(WorldObject {
+                x: v.entrance.0,
+                y: v.entrance.1,
+                kind: ObjectKind::LockedDoor { key: VAULT_KEY.to_string() },
+            });
+            out.vault_chests.push((v.chest, None));
+        }
+        if let Some(v) = carve_vault(map, &mut rng, &reach, &taken, Tile::Gate) {
+            out.vault_chests.push((v.chest, None));
+            if let Some(p) = place(map, &mut rng, &reach, &mut taken, Tile::Lever) {
+                out.objects.push(WorldObject { x: p.0, y: p.1, kind: ObjectKind::Lever { gate: v.entrance, pulled: false } });
+            }
+        }
+    }
+    for ((x, y), _) in &out.vault_chests { taken.push((*x, *y)); }
+
+    if let Some(p) = place(map, &mut rng, &reach, &mut taken, Tile::Fountain) {
+        out.objects.push(WorldObject { x: p.0, y: p.1, kind: ObjectKind::Fountain { charges: 3 } });
+    }
+
+    let text = match depth {
+        0 => "Sunny Days, pop. 41.\n{i:Mind the cracked stonework; the old builders hid things in the walls.}",
+        _ => "Weeping Willow Forest.\n{i:The old gate answers to a lever. The vault answers only to its key.}",
+    };
+    if let Some(p) = place(map, &mut rng, &reach, &mut taken, Tile::Sign) {
+        out.objects.push(WorldObject { x: p.0, y: p.1, kind: ObjectKind::Sign { text: text.to_string() } });
+    }
+
+    for _ in 0..2 + depth {
+        if let Some(p) = place(map, &mut rng, &reach, &mut taken, Tile::Trap) {
+            out.objects.push(WorldObject { x: p.0, y: p.1, kind: ObjectKind::Trap { damage: 3 + 2 * depth as i32, sprung: false } });
+        }
+    }
+    out
+}
+
+/// A reachable floor tile in the open (all eight neighbours floor), so an object there never
+/// blocks a path.
+fn open_floor(map: &Map, rng: &mut StdRng, reach: &[bool], taken: &[(i32, i32)]) -> Option<(i32, i32)> {
+    let mut spots = Vec::new();
+    for y in 1..map.height as i32 - 1 {
+        for x in 1..map.width as i32 - 1 {
+            let open = (-1..=1).all(|dy| (-1..=1).all(|dx| map.get((x + dx) as usize, (y + dy) as usize) == Tile::Floor));
+            let clear = taken.iter().all(|&(tx, ty)| (tx - x).abs().max((ty - y).abs()) > 2);
+            if open && clear && reach[map.idx(x as usize, y as usize)] { spots.push((x, y)); }
+        }
+    }
+    if spots.is_empty() { return None; }
+    Some(spots[rng.gen_range(0..spots.len())])
+}
+
+fn place(map: &mut Map, rng: &mut StdRng, reach: &[bool], taken: &mut Vec<(i32, i32)>, tile: Tile) -> Option<(i32, i32)> {
+    let p = open_floor(map, rng, reach, taken)?;
+    map.set(p.0 as usize, p.1 as usize, tile);
+    taken.push(p);
+    Some(p)
+}
+
+/// Dig a vault into solid rock next to a reachable floor tile, sealed by `entrance`.
+fn carve_vault(map: &mut Map, rng: &mut StdRng, reach: &[bool], taken: &[(i32, i32)], entrance: Tile) -> Option<Vault> {
+    let (w, h) = (map.width as i32, map.height as i32);
+    for _ in 0..500 {
+        let (fx, fy) = (rng.gen_range(1..w - 1), rng.gen_range(1..h - 1));
+        let (dx, dy) = [(0, -1), (0, 1), (-1, 0), (1, 0)][rng.gen_range(0..4)];
+        if map.get(fx as usize, fy as usize) != Tile::Floor || !reach[map.idx(fx as usize, fy as usize)] || taken.contains(&(fx, fy)) { continue; }
+
+        let (ex, ey) = (fx + dx, fy + dy);
+        let (cx, cy) = (ex + 2 * dx, ey + 2 * dy);
+        // The whole 5x5 block around the chest must be solid wall, away from the map edge.
+        let solid = (-2..=2).all(|oy| (-2..=2).all(|ox| {
+            let (x, y) = (cx + ox, cy + oy);
+            x > 0 && y > 0 && x < w - 1 && y < h - 1 && map.get(x as usize, y as usize) == Tile::Wall
+        }));
+        if !solid { continue; }
+
+        for oy in -1..=1 {
+            for ox in -1..=1 { map.set((cx + ox) as usize, (cy + oy) as usize, Tile::Floor); }
+        }
+        map.set(ex as usize, ey as usize, entrance);
+        map.set(cx as usize, cy as usize, Tile::Chest);
+        return Some(Vault { entrance: (ex, ey), chest: (cx, cy) });
+    }
+    None
+}
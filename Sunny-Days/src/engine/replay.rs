@@ -0,0 +1,317 @@
+//! Recording a game's inputs and playing them back.
+//!
+//! A replay is the seed, the settings, and every `Action` the world accepted together with the
+//! tick it arrived on. The world's own dice are seeded from the seed, so feeding the same
+//! actions on the same ticks rebuilds the same game; the final state hash says whether it did.
+//!
+//! The file is plain text, one entry per line:
+//!
+//! ```text
+//! sunny-days replay 1
+//! seed 42
+//! settings difficulty=normal battle-timer=10 timer-pause=yes carry-limit=0
+//! 0 Confirm
+//! 17 Move 1 0
+//! 30 Choice y
+//! end 912 9f3c2a77d01e4b6a
+//! ```
+
+use crate::engine::action::Action;
+use crate::engine::settings::{Difficulty, Settings};
+use crate::engine::world::World;
+
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+const HEADER: &str = "sunny-days replay 1";
+
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub settings: Settings,
+    /// Inputs in the order they were applied, each with the tick it arrived on.
+    pub inputs: Vec<(u64, Action)>,
+    /// The tick the recording stopped on and the state hash at that point.
+    pub end: Option<(u64, u64)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, settings: Settings) -> Self {
+        Self { seed, settings, inputs: Vec::new(), end: None }
+    }
+
+    /// Remember an input the world is about to apply. `Action::None` changes nothing, so it's skipped.
+    pub fn record(&mut self, tick: u64, action: Action) {
+        if matches!(action, Action::None) { return; }
+        self.inputs.push((tick, action));
+    }
+
+    /// Seal the recording with the world's final tick and state.
+    pub fn finish(&mut self, world: &World) {
+        self.end = Some((world.ticks, state_hash(world)));
+    }
+
+    /// A fresh world set up the way the recording started.
+    pub fn world(&self) -> World {
+        let mut world = World::new(self.seed, 80, 45);
+        world.settings = self.settings.clone();
+        world.player.inventory.max_weight = self.settings.carry_limit;
+        world
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_text()).map_err(|e| format!("cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn to_text(&self) -> String {
+        let s = &self.settings;
+        let mut out = format!("{}\nseed {}\n", HEADER, self.seed);
+        out.push_str(&format!(
+            "settings difficulty={} battle-timer={} timer-pause={} carry-limit={}\n",
+            s.difficulty.name(),
+            s.battle_timer_secs.unwrap_or(0),
+            if s.allow_timer_pause { "yes" } else { "no" },
+            s.carry_limit.unwrap_or(0),
+        ));
+        for (tick, action) in &self.inputs {
+            out.push_str(&format!("{} {}\n", tick, encode(*action)));
+        }
+        if let Some((tick, hash)) = self.end {
+            out.push_str(&format!("end {} {:016x}\n", tick, hash));
+        }
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        match lines.next() {
+            Some((_, l)) if l.trim() == HEADER => {}
+            _ => return Err(format!("not a replay (expected `{}` first)", HEADER)),
+        }
+
+        let mut replay = Replay::new(0, Settings::default());
+        let mut seen_seed = false;
+        for (n, raw) in lines {
+            let at = |e: String| format!("line {}: {}", n + 1, e);
+            let line = raw.trim();
+            let (head, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match head {
+                "seed" => {
+                    replay.seed = rest.trim().parse().map_err(|_| at(format!("bad seed: {}", rest)))?;
+                    seen_seed = true;
+                }
+                "settings" => replay.settings = parse_settings(rest).map_err(at)?,
+                "end" => {
+                    let (tick, hash) = rest.split_once(' ').ok_or_else(|| at("expected `end <tick> <hash>`".to_string()))?;
+                    let tick = tick.parse().map_err(|_| at(format!("bad tick: {}", tick)))?;
+                    let hash = u64::from_str_radix(hash.trim(), 16).map_err(|_| at(format!("bad hash: {}", hash)))?;
+                    replay.end = Some((tick, hash));
+                }
+                _ => {
+                    let tick: u64 = head.parse().map_err(|_| at(format!("unexpected `{}`", head)))?;
+                    let action = decode(rest).ok_or_else(|| at(format!("unknown action: {}", rest)))?;
+                    if replay.inputs.last().is_some_and(|&(last, _)| tick < last) {
+                        return Err(at("ticks go backwards".to_string()));
+                    }
+                    replay.inputs.push((tick, action));
+                }
+            }
+        }
+        if !seen_seed { return Err("missing `seed` line".to_string()); }
+        Ok(replay)
+    }
+}
+
+/// Feeds a replay's inputs into a world, one tick at a time.
+pub struct Playback<'a> {
+    replay: &'a Replay,
+    next: usize,
+}
+
+impl<'a> Playback<'a> {
+    pub fn new(replay: &'a Replay) -> Self {
+        Self { replay, next: 0 }
+    }
+
+    /// Apply every input due on the world's current tick, then advance it one tick.
+    /// Returns false once the recording is over (or the world asked to quit).
+    pub fn step(&mut self, world: &mut World) -> bool {
+        while let Some(&(tick, action)) = self.replay.inputs.get(self.next) {
+            if tick > world.ticks { break; }
+            self.next += 1;
+            if !world.apply_action(action) { return false; }
+        }
+        if self.is_done(world) { return false; }
+        world.tick();
+        true
+    }
+
+    pub fn is_done(&self, world: &World) -> bool {
+        let end_tick = match self.replay.end {
+            Some((tick, _)) => tick,
+            None => self.replay.inputs.last().map_or(0, |&(t, _)| t),
+        };
+        self.next >= self.replay.inputs.len() && world.ticks >= end_tick
+    }
+
+    /// Share of the inputs applied so far, for a progress readout.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.next, self.replay.inputs.len())
+    }
+}
+
+/// Play a replay start to finish without a terminal and check the final state.
+#[cfg(test)]
+pub fn run_headless(replay: &Replay) -> Result<World, String> {
+    let mut world = replay.world();
+    let mut playback = Playback::new(replay);
+    while playback.step(&mut world) {}
+    verify(replay, &world)?;
+    Ok(world)
+}
+
+/// Whether the world ended up where the recording did.
+pub fn verify(replay: &Replay, world: &World) -> Result<u64, String> {
+    let actual = state_hash(world);
+    match replay.end {
+        None => Err("the replay has no `end` line to check against".to_string()),
+        Some((tick, _)) if tick != world.ticks => Err(format!("stopped at tick {} but the recording ended at {}", world.ticks, tick)),
+        Some((_, expected)) if expected != actual => {
+            Err(format!("final state differs: expected hash {:016x}, got {:016x}", expected, actual))
+        }
+        Some(_) => Ok(actual),
+    }
+}
+
+/// A fingerprint of everything the player could notice: where they are, what they carry,
+/// how the story and any fight stand, and what's left in the levels.
+pub fn state_hash(world: &World) -> u64 {
+    let mut h = Fnv64::new();
+    format!("{:?}", world.state).hash(&mut h);
+    world.ticks.hash(&mut h);
+    world.clock.hash(&mut h);
+    world.current.hash(&mut h);
+
+    let p = &world.player;
+    (p.x, p.y, p.hp, p.max_hp, p.gold).hash(&mut h);
+    (p.base_attack, p.base_defense, p.base_speed).hash(&mut h);
+    for eq in p.inventory.equipped_items().chain(&p.inventory.backpack) {
+        (&eq.name, eq.durability).hash(&mut h);
+    }
+    for stack in &p.inventory.consumables {
+        (&stack.item.name, stack.qty).hash(&mut h);
+    }
+    p.inventory.keys.hash(&mut h);
+    for b in &p.buffs {
+        (b.atk_bonus, b.def_bonus, b.speed_bonus, b.expires_at).hash(&mut h);
+    }
+
+    world.flags().hash(&mut h);
+    for npc in world.npcs() {
+        (format!("{:?}", npc.id), npc.room, npc.x, npc.y).hash(&mut h);
+    }
+    if let Some(bs) = &world.battle {
+        (&bs.enemy_name, bs.enemy_hp, bs.penalty_mode, bs.player_initiated).hash(&mut h);
+    }
+    for level in &world.levels {
+        level.map.tiles.iter().map(|t| format!("{:?}", t)).collect::<String>().hash(&mut h);
+        for c in level.chests() {
+            (c.x, c.y, c.opened).hash(&mut h);
+        }
+        format!("{:?}", level.objects).hash(&mut h);
+    }
+    h.finish()
+}
+
+/// FNV-1a. Unlike `DefaultHasher` its output is fixed, so hashes in saved replays stay valid.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Actions without data, by the name they're written under.
+const SIMPLE_ACTIONS: [(&str, Action); 18] = [
+    ("ToggleInventory", Action::ToggleInventory),
+    ("ToggleInvTab", Action::ToggleInvTab),
+    ("InventoryUp", Action::InventoryUp),
+    ("InventoryDown", Action::InventoryDown),
+    ("UseConsumable", Action::UseConsumable),
+    ("InspectItem", Action::InspectItem),
+    ("DropItem", Action::DropItem),
+    ("CycleSort", Action::CycleSort),
+    ("CycleFilter", Action::CycleFilter),
+    ("Craft", Action::Craft),
+    ("ToggleStats", Action::ToggleStats),
+    ("ToggleOverview", Action::ToggleOverview),
+    ("Confirm", Action::Confirm),
+    ("Interact", Action::Interact),
+    ("LeaveShop", Action::LeaveShop),
+    ("ToggleBattleTimer", Action::ToggleBattleTimer),
+    ("Quit", Action::Quit),
+    ("None", Action::None),
+];
+
+fn encode(action: Action) -> String {
+    match action {
+        Action::Move(dx, dy) => format!("Move {} {}", dx, dy),
+        Action::Choice(c) => format!("Choice {}", c),
+        Action::BattleOption(n) => format!("BattleOption {}", n),
+        other => format!("{:?}", other),
+    }
+}
+
+fn decode(text: &str) -> Option<Action> {
+    let mut words = text.split_whitespace();
+    let name = words.next()?;
+    let action = match name {
+        "Move" => Action::Move(words.next()?.parse().ok()?, words.next()?.parse().ok()?),
+        "Choice" => {
+            let mut chars = words.next()?.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() { return None; }
+            Action::Choice(c)
+        }
+        "BattleOption" => Action::BattleOption(words.next()?.parse().ok()?),
+        _ => SIMPLE_ACTIONS.iter().find(|(n, _)| *n == name)?.1,
+    };
+    if words.next().is_some() { return None; }
+    Some(action)
+}
+
+fn parse_settings(text: &str) -> Result<Settings, String> {
+    let mut settings = Settings::default();
+    for pair in text.split_whitespace() {
+        let (key, value) = pair.split_once('=').ok_or(format!("expected key=value, got {}", pair))?;
+        let number = || value.parse::<u32>().map_err(|_| format!("bad number for {}: {}", key, value));
+        match key {
+            "difficulty" => settings.difficulty = Difficulty::from_name(value).ok_or(format!("unknown difficulty: {}", value))?,
+            "battle-timer" => settings.battle_timer_secs = Some(number()?).filter(|&s| s > 0),
+            "timer-pause" => settings.allow_timer_pause = value == "yes",
+            "carry-limit" => settings.carry_limit = Some(number()?).filter(|&w| w > 0),
+            _ => return Err(format!("unknown setting: {}", key)),
+        }
+    }
+    Ok(settings)
+}
@@ -0,0 +1,52 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Story,  // no battle timer at all
+    Normal,
+    Hard,   // shorter timer, cannot be paused
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "story" | "easy" => Some(Difficulty::Story),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Story => "story",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    /// Seconds without a battle input before the enemy seizes initiative. None disables the timer.
+    pub battle_timer_secs: Option<u32>,
+    /// Whether P may pause the battle timer mid-fight.
+    pub allow_timer_pause: bool,
+    /// Optional carry-weight limit on top of the inventory's slot count.
+    pub carry_limit: Option<u32>,
+}
+
+impl Settings {
+    pub fn for_difficulty(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Story => Self { difficulty, battle_timer_secs: None, allow_timer_pause: true, carry_limit: None },
+            Difficulty::Normal => Self { difficulty, battle_timer_secs: Some(10), allow_timer_pause: true, carry_limit: None },
+            Difficulty::Hard => Self { difficulty, battle_timer_secs: Some(6), allow_timer_pause: false, carry_limit: None },
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::for_difficulty(Difficulty::Normal)
+    }
+}
@@ -0,0 +1,124 @@
+//! Tajer's shop: stock, prices and buyback.
+
+use crate::engine::entity::{Consumable, ConsumableStack, Equipment, Rarity, SlotItem};
+use crate::engine::loot;
+
+use rand::{rngs::StdRng, SeedableRng};
+use std::collections::VecDeque;
+
+/// How many recently sold items Tajer keeps aside for you to buy back.
+pub const BUYBACK_LEN: usize = 5;
+
+const SHOP_SALT: u64 = 0x7A7E;
+const STOCK_GEAR: usize = 4;
+const STOCK_STACK: u32 = 5;
+/// The consumables Tajer sells, by their name in the loot tables.
+pub(crate) const STOCK_CONSUMABLES: [&str; 4] = ["Sunny Jerky", "Fiery ale", "Frozen tears", "Whetstone"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopTab {
+    Buy,
+    Sell,
+    Buyback,
+    Repair,
+}
+
+impl ShopTab {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShopTab::Buy => "Buy",
+            ShopTab::Sell => "Sell",
+            ShopTab::Buyback => "Buyback",
+            ShopTab::Repair => "Repair",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Shop {
+    pub stock: Vec<SlotItem>,
+    /// Most recently sold first, with what Tajer paid for it.
+    pub buyback: VecDeque<(SlotItem, u32)>,
+    pub tab: ShopTab,
+    pub cursor: usize,
+}
+
+impl Shop {
+    /// Stock is rolled from the world seed, like chest loot, at second-room strength.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed ^ SHOP_SALT);
+        let mut stock: Vec<SlotItem> = (0..STOCK_GEAR)
+            .map(|_| SlotItem::Gear(loot::roll_equipment(&mut rng, 1)))
+            .collect();
+        let consumables = loot::consumables();
+        for name in STOCK_CONSUMABLES {
+            let Some(c) = consumables.iter().find(|c| c.name == name) else { continue };
+            stock.push(SlotItem::Stack(ConsumableStack { item: c.clone(), qty: STOCK_STACK }));
+        }
+        Self { stock, buyback: VecDeque::new(), tab: ShopTab::Buy, cursor: 0 }
+    }
+
+    pub fn toggle_tab(&mut self) {
+        self.tab = match self.tab {
+            ShopTab::Buy => ShopTab::Sell,
+            ShopTab::Sell => ShopTab::Buyback,
+            ShopTab::Buyback => ShopTab::Repair,
+            ShopTab::Repair => ShopTab::Buy,
+        };
+        self.cursor = 0;
+    }
+
+    /// Wrap the cursor within a list of `len` entries.
+    pub fn move_cursor(&mut self, delta: i32, len: usize) {
+        if len == 0 { self.cursor = 0; return; }
+        self.cursor = (self.cursor as i32 + delta).rem_euclid(len as i32) as usize;
+    }
+
+    pub fn remember_sold(&mut self, item: SlotItem, price: u32) {
+        self.buyback.push_front((item, price));
+        self.buyback.truncate(BUYBACK_LEN);
+    }
+}
+
+fn rarity_premium(rarity: Rarity) -> u32 {
+    match rarity {
+        Rarity::Common => 100,
+        Rarity::Uncommon => 130,
+        Rarity::Rare => 170,
+        Rarity::Epic => 230,
+        Rarity::Legendary => 320,
+    }
+}
+
+pub fn equipment_value(eq: &Equipment) -> u32 {
+    let score = eq.hp_bonus / 2 + eq.atk_bonus * 3 + eq.def_bonus * 3 + eq.speed_bonus * 2;
+    (5 + score.max(0) as u32 * 3) * rarity_premium(eq.rarity) / 100
+}
+
+pub fn consumable_value(c: &Consumable) -> u32 {
+    3 + c.heal.max(0) as u32 * 2 + (c.atk_bonus + c.def_bonus).max(0) as u32 * 2 + c.repair
+}
+
+/// Tajer's fee to make `eq` as good as new; broken gear costs extra.
+pub fn repair_price(eq: &Equipment) -> u32 {
+    let missing = eq.max_durability - eq.durability;
+    let fee = (equipment_value(eq) * missing).div_ceil(eq.max_durability.max(1) * 2);
+    if eq.is_broken() { fee + 5 } else { fee.max(1) }
+}
+
+/// What Tajer charges for one of `item` (one unit, for stacks).
+pub fn buy_price(item: &SlotItem) -> u32 {
+    match item {
+        SlotItem::Gear(eq) => equipment_value(eq),
+        SlotItem::Stack(s) => consumable_value(&s.item),
+    }
+}
+
+/// What Tajer pays for one of `item`: half its price, less for worn gear, never nothing.
+pub fn sell_price(item: &SlotItem) -> u32 {
+    let price = match item {
+        SlotItem::Gear(eq) => buy_price(item) * eq.durability / eq.max_durability.max(1),
+        SlotItem::Stack(_) => buy_price(item),
+    };
+    (price / 2).max(1)
+}
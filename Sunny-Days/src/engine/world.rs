@@ -1,14 +1,21 @@
 use crate::engine::action::Action;
+use crate::engine::clock::GameClock;
+use crate::engine::crafting;
 use crate::engine::entity::{
-    Equipment, Player, InvSelection, InvTab, Consumable, EquipSlot as Slot,
+    Equipment, Player, InvSelection, InvTab, Consumable, EquipSlot as Slot, SlotItem,
+    ConsumableStack, GearSlot, ItemKind, Rarity, Step,
 };
-use crate::map::{generator::generate_rooms_and_corridors, tile::Tile, Map};
+use crate::engine::loot::{Loot, LootTable};
+use crate::engine::markup;
+use crate::engine::objects::{self, ObjectKind, WorldObject};
+use crate::engine::settings::Settings;
+use crate::engine::shop::{self, Shop, ShopTab};
+use crate::map::{fov::{self, Fov}, generator::generate_rooms_and_corridors, occupancy::Occupancy, tile::Tile, Map};
 
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 
 use std::collections::VecDeque;
-use std::time::Duration;
 
 #[derive(Clone)]
 pub struct Chest {
@@ -16,51 +23,139 @@ pub struct Chest {
     pub y: i32,
     pub item: Option<Consumable>,
     pub weapon: Option<Equipment>,
+    pub gold: u32,
+    /// Goes straight onto your key ring, like gold.
+    pub key: Option<String>,
     pub opened: bool,
 }
 
+/// Something the player dropped; E picks it back up.
+#[derive(Debug, Clone)]
+pub struct Pickup {
+    pub x: i32,
+    pub y: i32,
+    pub item: SlotItem,
+}
+
 #[derive(Clone)]
 pub struct Level {
     pub map: Map,
     pub door: (i32, i32),
-    pub chests: Vec<Chest>,
+    chests: Vec<Chest>,
+    pub pickups: Vec<Pickup>,
+    /// Levers, fountains, traps and the like, with whatever state they're in.
+    pub objects: Vec<WorldObject>,
+    /// Tiles the player has seen, indexed like `map.tiles`.
+    pub explored: Vec<bool>,
+    /// Which of `World::npcs` stands on each tile; kept up by `World::add_npc` and `remove_npc`.
+    npc_grid: Occupancy,
+    /// Which of `chests` sits on each tile; kept up by `add_chest`.
+    chest_grid: Occupancy,
 }
 
+impl Level {
+    fn new(map: Map, door: (i32, i32), objects: Vec<WorldObject>) -> Self {
+        let (w, h) = (map.width, map.height);
+        Self {
+            explored: vec![false; map.tiles.len()],
+            map,
+            door,
+            chests: Vec::new(),
+            pickups: Vec::new(),
+            objects,
+            npc_grid: Occupancy::new(w, h),
+            chest_grid: Occupancy::new(w, h),
+        }
+    }
+
+    pub fn add_chest(&mut self, chest: Chest) {
+        self.chest_grid.set(chest.x, chest.y, Some(self.chests.len()));
+        self.chests.push(chest);
+    }
+
+    pub fn chests(&self) -> &[Chest] { &self.chests }
+
+    pub fn chest_at(&self, x: i32, y: i32) -> Option<&Chest> {
+        self.chest_grid.get(x, y).map(|i| &self.chests[i])
+    }
+
+    pub fn object_at(&self, x: i32, y: i32) -> Option<&WorldObject> {
+        self.objects.iter().find(|o| o.x == x && o.y == y)
+    }
+
+    pub fn is_explored(&self, x: i32, y: i32) -> bool {
+        self.map.in_bounds(x, y) && self.explored[self.map.idx(x as usize, y as usize)]
+    }
+}
+
+/// Which screen the game is on; decides what each `Action` does.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GameState {
+    /// The title screen.
     Title,
+    /// The story so far, before the first level.
     Intro,
+    /// Walking about a level.
     Playing,
+    /// Someone is talking (or a chest is open).
     Dialogue,
+    /// Fighting an enemy.
     Battle,
+    /// At Tajer's counter.
+    Shop,
+    /// The story is over.
     Fin,
 }
 
+/// Everyone the story knows about.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NpcId {
+    /// Asks for help, then turns out to be the final boss.
     MayorSol,
+    /// Gives the first weapon.
     Noor,
+    /// Gives whichever of sword and shield Noor didn't.
     Lamp,
+    /// A villager in the first level.
     Random1,
+    /// A villager in the first level.
     Random2,
+    /// A villager in the first level.
     Random3,
+    /// A villager in the Weeping Willow Woods.
     Weeping1,
+    /// A villager in the Weeping Willow Woods.
     Weeping2,
+    /// A villager in the Weeping Willow Woods.
     Weeping3,
+    /// A villager in the Weeping Willow Woods.
     Weeping4,
+    /// The first of Mah's men.
     Shab,
+    /// The second of Mah's men.
     Krad,
+    /// Leads the Weeping; carries the dagger.
     Mah,
+    /// Trades the Willow Axe for the Weeping Dagger.
     Dorosht,
+    /// Runs the shop.
+    Tajer,
 }
 
+/// Someone standing in one of the levels.
 #[derive(Debug, Clone)]
 pub struct Npc {
+    /// Who this is to the story.
     pub id: NpcId,
+    /// Shown in dialogue.
     pub name: String,
+    /// Which level they're in, as for `World::npc_at`.
     pub room: usize,
+    /// Column on the level's map.
     pub x: i32,
+    /// Row on the level's map.
     pub y: i32,
+    /// Drawn on the map in their place.
     pub symbol: char,
 }
 
@@ -76,15 +171,129 @@ pub enum AwaitingChoice {
         item: Option<Consumable>,
         weapon: Option<Equipment>,
     },
+    /// The chest's contents don't fit: drop one of `slots` to make room, or leave it.
+    PackFull {
+        room: usize,
+        x: i32,
+        y: i32,
+        item: Option<Consumable>,
+        weapon: Option<Equipment>,
+        slots: Vec<String>,
+    },
+}
+
+impl AwaitingChoice {
+    /// Keys for dropping a pack slot when a chest's contents don't fit, in slot order. X is
+    /// left out for leaving the item, so only the first 25 slots can be dropped from here.
+    pub const DROP_KEYS: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWYZ";
+    pub const LEAVE_KEY: char = 'X';
+
+    /// Letters the player can press, with a label for the visible choice list.
+    pub fn options(&self) -> Vec<(char, String)> {
+        match self {
+            AwaitingChoice::YesNoMayor => vec![('Y', "Yes, I'll help".to_string()), ('N', "No".to_string())],
+            AwaitingChoice::YesNoDorosht => vec![('Y', "Sure, I'll fetch it".to_string()), ('N', "Not right now".to_string())],
+            AwaitingChoice::ABNoorWeapon => vec![('A', "Basic Sword".to_string()), ('B', "Basic Shield".to_string())],
+            AwaitingChoice::Chest { item, .. } => {
+                let mut opts = vec![('A', "Put in inventory".to_string())];
+                if item.is_some() { opts.push(('B', "Use now".to_string())); }
+                opts.push(('C', "Throw away".to_string()));
+                opts
+            }
+            AwaitingChoice::PackFull { slots, .. } => {
+                let mut opts: Vec<(char, String)> = slots.iter().zip(Self::DROP_KEYS)
+                    .map(|(name, &key)| (key as char, format!("Drop {}", name)))
+                    .collect();
+                opts.push((Self::LEAVE_KEY, "Leave it in the chest".to_string()));
+                opts
+            }
+        }
+    }
+}
+
+/// Who is talking, for the portrait and colour of the dialogue box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speaker {
+    Npc(NpcId),
+    Chest,
+    Sign,
 }
 
 #[derive(Debug, Clone)]
 pub struct DialogueSession {
-    pub npc: NpcId,
+    /// Whose story moves on when this closes; None for chests, signs and one-off lines.
+    pub npc: Option<NpcId>,
+    pub speaker: Speaker,
     pub title: String,
     pub pages: Vec<String>,
     pub page_index: usize,
     pub awaiting: Option<AwaitingChoice>,
+    /// Visible characters of the current page revealed so far (typewriter effect).
+    pub revealed: usize,
+}
+
+impl DialogueSession {
+    pub fn new(npc: NpcId, title: &str, pages: Vec<String>) -> Self {
+        Self { npc: Some(npc), ..Self::aside(Speaker::Npc(npc), title, pages) }
+    }
+
+    /// Lines that don't belong to anyone's story, so nothing happens when they close.
+    pub fn aside(speaker: Speaker, title: &str, pages: Vec<String>) -> Self {
+        Self {
+            npc: None,
+            speaker,
+            title: title.to_string(),
+            pages,
+            page_index: 0,
+            awaiting: None,
+            revealed: 0,
+        }
+    }
+
+    pub fn with_choice(mut self, choice: AwaitingChoice) -> Self {
+        self.awaiting = Some(choice);
+        self
+    }
+
+    pub fn page(&self) -> &str {
+        &self.pages[self.page_index]
+    }
+
+    pub fn page_len(&self) -> usize {
+        markup::visible_len(self.page())
+    }
+
+    pub fn fully_revealed(&self) -> bool {
+        self.revealed >= self.page_len()
+    }
+
+    pub fn on_last_page(&self) -> bool {
+        self.page_index + 1 >= self.pages.len()
+    }
+
+    fn next_page(&mut self) {
+        self.page_index += 1;
+        self.revealed = 0;
+    }
+
+    fn set_pages(&mut self, pages: Vec<String>) {
+        self.pages = pages;
+        self.page_index = 0;
+        self.revealed = 0;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitKind {
+    Hit,
+    Deflect,
+}
+
+/// Short-lived hit feedback shown on a combatant; counts down once per game tick.
+#[derive(Debug, Clone, Copy)]
+pub struct HitFx {
+    pub kind: HitKind,
+    pub ticks_left: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -99,46 +308,173 @@ pub struct BattleSession {
     
     pub penalty_mode: bool,
     pub player_initiated: bool,
+
+    pub enemy_fx: Option<HitFx>,
+    pub player_fx: Option<HitFx>,
+
+    /// Ticks since the player's last battle input; drives the hesitation penalty.
+    pub idle_ticks: u32,
+    pub timer_paused: bool,
+
+    /// What the player was standing on when the fight started.
+    pub terrain: Tile,
+}
+
+/// How a fight ended, taken before the victory heal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BattleEnd {
+    pub enemy_id: NpcId,
+    pub won: bool,
+    pub fled: bool,
+    pub hp_left: i32,
+}
+
+impl BattleSession {
+    /// Initiative goes to the faster combatant; hesitating (penalty mode) hands it to the enemy.
+    pub fn player_acts_first(&self, player_speed: i32) -> bool {
+        let player_speed = if self.terrain == Tile::Water { player_speed - World::WADE_SLOWDOWN } else { player_speed };
+        if self.penalty_mode { false } else { player_speed >= self.enemy_speed }
+    }
+
+    /// How the ground changes the fight, shown on the battle screen.
+    pub fn terrain_note(&self) -> Option<&'static str> {
+        match self.terrain {
+            Tile::Water => Some("You're knee-deep in water: slower to act."),
+            Tile::Grass => Some("The tall grass gives you cover: easier to deflect."),
+            Tile::Lava => Some("The lava scorches you both every round."),
+            Tile::Ice => Some("Slippery footing: nobody deflects well on ice."),
+            _ => None,
+        }
+    }
+}
+
+/// How far the story has got. Only the world changes these; `World::flags` reads them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StoryFlags {
+    /// Agreed to help the Mayor.
+    pub mayor_done: bool,
+    /// Took Noor's weapon.
+    pub noor_done: bool,
+    /// Got the other one from Lamp.
+    pub lamp_done: bool,
+
+    /// Said yes to fetching Dorosht's dagger.
+    pub dorosht_accepted: bool,
+    /// Traded the dagger for the Willow Axe.
+    pub dorosht_completed: bool,
+
+    /// Beat Shab.
+    pub shab_defeated: bool,
+    /// Beat Krad.
+    pub krad_defeated: bool,
+    /// Beat Mah.
+    pub mah_defeated: bool,
+    /// Beat the Mayor; the story ends once he has had his last word.
+    pub mayor_defeated: bool,
+}
+
+impl StoryFlags {
+    /// Every flag by name, in story order.
+    pub fn entries(&self) -> [(&'static str, bool); 9] {
+        [
+            ("mayor_done", self.mayor_done),
+            ("noor_done", self.noor_done),
+            ("lamp_done", self.lamp_done),
+            ("dorosht_accepted", self.dorosht_accepted),
+            ("dorosht_completed", self.dorosht_completed),
+            ("shab_defeated", self.shab_defeated),
+            ("krad_defeated", self.krad_defeated),
+            ("mah_defeated", self.mah_defeated),
+            ("mayor_defeated", self.mayor_defeated),
+        ]
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        Some(match name {
+            "mayor_done" => &mut self.mayor_done,
+            "noor_done" => &mut self.noor_done,
+            "lamp_done" => &mut self.lamp_done,
+            "dorosht_accepted" => &mut self.dorosht_accepted,
+            "dorosht_completed" => &mut self.dorosht_completed,
+            "shab_defeated" => &mut self.shab_defeated,
+            "krad_defeated" => &mut self.krad_defeated,
+            "mah_defeated" => &mut self.mah_defeated,
+            "mayor_defeated" => &mut self.mayor_defeated,
+            _ => return None,
+        })
+    }
 }
 
+/// The whole game: levels, people, the player and the story. Drive it with `apply_action`
+/// and `tick`.
+#[derive(Clone)]
 pub struct World {
-    pub levels: Vec<Level>,
-    pub current: usize,
-    pub player: Player,
+    pub(crate) levels: Vec<Level>,
+    pub(crate) current: usize,
+    pub(crate) player: Player,
 
-    pub logs: VecDeque<String>,
+    pub(crate) logs: VecDeque<String>,
+    /// What the levels, loot and dice were rolled from.
     pub seed: u64,
 
-    pub inventory_open: bool,
-    pub stats_open: bool,
+    pub(crate) inventory_open: bool,
+    pub(crate) stats_open: bool,
+    pub(crate) overview_open: bool,
+    /// Detail panel for the selected inventory item.
+    pub(crate) inspect_open: bool,
+    /// The screen the game is on.
     pub state: GameState,
 
     intro_lines: Vec<String>,
 
-    pub npcs: Vec<Npc>,
-    
-    mayor_done: bool,
-    noor_done: bool,
-    lamp_done: bool,
+    npcs: Vec<Npc>,
     
-    dorosht_accepted: bool,
-    dorosht_completed: bool,
-    
-    shab_defeated: bool,
-    krad_defeated: bool,
-    mah_defeated: bool,
-    mayor_defeated: bool,
-
-    pub dialogue: Option<DialogueSession>,
-    pub battle: Option<BattleSession>,
+    story: StoryFlags,
+
+    pub(crate) dialogue: Option<DialogueSession>,
+    pub(crate) battle: Option<BattleSession>,
+    pub(crate) last_battle: Option<BattleEnd>,
+    /// Tajer's stock and buyback shelf; kept between visits.
+    pub(crate) shop: Shop,
+    /// Recipes found by experimenting, as indices into `crafting::recipes()`.
+    pub(crate) known_recipes: Vec<usize>,
+
+    /// Number of 60ms game ticks elapsed; drives animations.
+    pub(crate) ticks: u64,
+    /// Time in play; stops behind dialogue, shops and menus. Buffs run on this.
+    pub(crate) clock: GameClock,
+    /// Dice for combat, seeded from the world seed so a replay rolls the same.
+    rng: StdRng,
+    pub(crate) settings: Settings,
 }
 
 impl World {
     const NPC_MIN_SEP: i32 = 5;
-
+    /// Anyone within this many tiles of a door stands next to a tile the door is used from.
+    const DOOR_CLEARANCE: i32 = 2;
+    /// Half-extent of the area revealed around the player (matches the map's zoom window).
+    pub(crate) const SIGHT: (i32, i32) = (17, 10);
+    /// Speed lost while fighting in water.
+    const WADE_SLOWDOWN: i32 = 3;
+    /// Defense added to deflect rolls while fighting from tall grass.
+    const GRASS_COVER: i32 = 5;
+    const LAVA_STEP_DAMAGE: i32 = 4;
+    /// Damage both sides take each battle round on lava.
+    const LAVA_ROUND_DAMAGE: i32 = 2;
+    const HIT_FX_TICKS: u8 = 5;
+    /// How much time each `tick` stands for.
+    pub const TICK_MS: u32 = GameClock::TICK_MS;
+    const POTION_BUFF_SECS: u32 = 30;
+    const TIMER_WARN_SECS: u32 = 3;
+    const TYPEWRITER_CHARS_PER_TICK: usize = 3;
+    const LOOT_SALT: u64 = 0x1007;
+    const COMBAT_SALT: u64 = 0xC0BA7;
+
+    /// A new game on the title screen. The same seed always builds the same two levels.
     pub fn new(seed: u64, width: usize, height: usize) -> Self {
-        let (level0, spawn0) = Self::make_level(seed, 0, width, height);
-        let (level1, _spawn1) = Self::make_level(seed, 1, width, height);
+        let (level0, spawn0) = Self::make_level(seed, 0, width, height, false);
+        let key_placed = level0.chests.iter().any(|c| c.key.is_some());
+        let (level1, _spawn1) = Self::make_level(seed, 1, width, height, key_placed);
 
         let mut logs = VecDeque::new();
         logs.push_back(format!("Seed: {}", seed));
@@ -167,26 +503,29 @@ impl World {
 
             inventory_open: false,
             stats_open: false,
+            overview_open: false,
+            inspect_open: false,
             state: GameState::Title,
 
             intro_lines,
 
             npcs: Vec::new(),
-            mayor_done: false,
-            noor_done: false,
-            lamp_done: false,
-            dorosht_accepted: false,
-            dorosht_completed: false,
-            shab_defeated: false,
-            krad_defeated: false,
-            mah_defeated: false,
-            mayor_defeated: false,
+            story: StoryFlags::default(),
 
             dialogue: None,
             battle: None,
+            last_battle: None,
+            shop: Shop::new(seed),
+            known_recipes: Vec::new(),
+
+            ticks: 0,
+            clock: GameClock::default(),
+            rng: StdRng::seed_from_u64(seed ^ Self::COMBAT_SALT),
+            settings: Settings::default(),
         };
 
         world.spawn_npcs(spawn0);
+        world.reveal_around_player();
         world
     }
 
@@ -222,21 +561,19 @@ impl World {
 
     fn spawn_npcs(&mut self, spawn0: (i32, i32)) {
         // --- ROOM 1 ---
-        let mut mx = spawn0.0 + 5;
-        let mut my = spawn0.1;
-
-        if !self.is_floor(0, mx, my) {
-            let candidates = [(mx, my), (mx, my + 1), (mx, my - 1), (mx + 1, my), (mx - 1, my)];
-            for (cx, cy) in candidates {
-                if self.is_floor(0, cx, cy) {
-                    mx = cx;
-                    my = cy;
-                    break;
-                }
-            }
-        }
+        // The Mayor waits a few steps from the start, on floor, and far enough from the door
+        // that pressing E beside it never reaches him instead.
+        let door0 = self.levels[0].door;
+        let fits = |&(x, y): &(i32, i32)| {
+            self.is_floor(0, x, y) && (x - door0.0).abs().max((y - door0.1).abs()) > Self::DOOR_CLEARANCE
+        };
+        let (sx, sy) = spawn0;
+        let (mx, my) = [(sx + 5, sy), (sx + 5, sy + 1), (sx + 5, sy - 1), (sx + 6, sy), (sx + 4, sy)]
+            .into_iter()
+            .find(fits)
+            .unwrap_or_else(|| self.random_floor_spaced(0, &[spawn0, door0], Self::NPC_MIN_SEP));
 
-        self.npcs.push(Npc {
+        self.add_npc(Npc {
             id: NpcId::MayorSol,
             name: "Mayor Sol".to_string(),
             room: 0,
@@ -252,16 +589,16 @@ impl World {
         ];
         for ch in &self.levels[0].chests { taken_r0.push((ch.x, ch.y)); }
 
-        for (id, sym, name) in [(NpcId::Noor, 'N', "Noor"), (NpcId::Lamp, 'L', "Lamp"), (NpcId::Dorosht, 'D', "Dorosht")] {
+        for (id, sym, name) in [(NpcId::Noor, 'N', "Noor"), (NpcId::Lamp, 'L', "Lamp"), (NpcId::Dorosht, 'D', "Dorosht"), (NpcId::Tajer, 'T', "Tajer")] {
             let (x, y) = self.random_floor_spaced(0, &taken_r0, Self::NPC_MIN_SEP);
             taken_r0.push((x, y));
-            self.npcs.push(Npc { id, name: name.to_string(), room: 0, x, y, symbol: sym });
+            self.add_npc(Npc { id, name: name.to_string(), room: 0, x, y, symbol: sym });
         }
 
         for id in [NpcId::Random1, NpcId::Random2, NpcId::Random3] {
             let (vx, vy) = self.random_floor_spaced(0, &taken_r0, Self::NPC_MIN_SEP);
             taken_r0.push((vx, vy));
-            self.npcs.push(Npc { id, name: "Villager".to_string(), room: 0, x: vx, y: vy, symbol: '●' });
+            self.add_npc(Npc { id, name: "Villager".to_string(), room: 0, x: vx, y: vy, symbol: '●' });
         }
 
         // --- ROOM 2 ---
@@ -271,20 +608,20 @@ impl World {
         for id in [NpcId::Weeping1, NpcId::Weeping2, NpcId::Weeping3, NpcId::Weeping4] {
             let (wx, wy) = self.random_floor_spaced(1, &taken_r1, Self::NPC_MIN_SEP);
             taken_r1.push((wx, wy));
-            self.npcs.push(Npc { id, name: "Weeping Villager".to_string(), room: 1, x: wx, y: wy, symbol: '●' });
+            self.add_npc(Npc { id, name: "Weeping Villager".to_string(), room: 1, x: wx, y: wy, symbol: '●' });
         }
 
         let (sx, sy) = self.random_floor_spaced(1, &taken_r1, Self::NPC_MIN_SEP);
         taken_r1.push((sx, sy));
-        self.npcs.push(Npc { id: NpcId::Shab, name: "Shab".to_string(), room: 1, x: sx, y: sy, symbol: 'S' });
+        self.add_npc(Npc { id: NpcId::Shab, name: "Shab".to_string(), room: 1, x: sx, y: sy, symbol: 'S' });
 
         let (kx, ky) = self.random_floor_spaced(1, &taken_r1, Self::NPC_MIN_SEP);
         taken_r1.push((kx, ky));
-        self.npcs.push(Npc { id: NpcId::Krad, name: "Krad".to_string(), room: 1, x: kx, y: ky, symbol: 'K' });
+        self.add_npc(Npc { id: NpcId::Krad, name: "Krad".to_string(), room: 1, x: kx, y: ky, symbol: 'K' });
 
         let (bx, by) = self.random_floor_spaced(1, &taken_r1, Self::NPC_MIN_SEP);
         taken_r1.push((bx, by));
-        self.npcs.push(Npc { id: NpcId::Mah, name: "Mah".to_string(), room: 1, x: bx, y: by, symbol: 'M' });
+        self.add_npc(Npc { id: NpcId::Mah, name: "Mah".to_string(), room: 1, x: bx, y: by, symbol: 'M' });
     }
 
     fn is_floor(&self, room: usize, x: i32, y: i32) -> bool {
@@ -308,20 +645,52 @@ impl World {
         floors[rng.gen_range(0..floors.len())]
     }
 
-    pub fn intro_lines(&self) -> &[String] { &self.intro_lines }
-    fn current_level(&self) -> &Level { &self.levels[self.current] }
-    fn current_level_mut(&mut self) -> &mut Level { &mut self.levels[self.current] }
+    pub(crate) fn intro_lines(&self) -> &[String] { &self.intro_lines }
+    /// How far the story has got.
+    pub fn flags(&self) -> &StoryFlags { &self.story }
+    pub(crate) fn current_level(&self) -> &Level { &self.levels[self.current] }
+    /// The map of the level the player is in.
     pub fn current_map(&self) -> &Map { &self.current_level().map }
+    /// Index of the level the player is in; `Npc::room` counts the same way.
+    pub fn current_room(&self) -> usize { self.current }
+    /// Where the player stands on `current_map`.
+    pub fn player_pos(&self) -> (i32, i32) { (self.player.x, self.player.y) }
+    /// Everyone in the world, in every level.
+    pub fn npcs(&self) -> &[Npc] { &self.npcs }
+    /// Whoever stands on a tile of level `room`.
     pub fn npc_at(&self, room: usize, x: i32, y: i32) -> Option<&Npc> {
-        self.npcs.iter().find(|n| n.room == room && n.x == x && n.y == y)
+        self.npc_index_at(room, x, y).map(|i| &self.npcs[i])
     }
-    pub fn npc_near_player(&self) -> Option<&Npc> {
-        let px = self.player.x;
-        let py = self.player.y;
-        self.npcs.iter().find(|n| n.room == self.current && (n.x - px).abs().max((n.y - py).abs()) <= 1)
+    /// Where in `npcs` whoever stands on a tile of level `room` is.
+    pub(crate) fn npc_index_at(&self, room: usize, x: i32, y: i32) -> Option<usize> {
+        self.levels[room].npc_grid.get(x, y)
+    }
+    /// With several people around, whoever came first in `npcs` answers.
+    pub(crate) fn npc_near_player(&self) -> Option<&Npc> {
+        let grid = &self.current_level().npc_grid;
+        let (px, py) = (self.player.x, self.player.y);
+        (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (px + dx, py + dy))).filter_map(|(x, y)| grid.get(x, y)).min().map(|i| &self.npcs[i])
+    }
+
+    /// Put someone in the world. `npcs` is only changed through here and `remove_npc`, so the
+    /// levels' occupancy grids stay in step with it.
+    pub fn add_npc(&mut self, npc: Npc) {
+        self.levels[npc.room].npc_grid.set(npc.x, npc.y, Some(self.npcs.len()));
+        self.npcs.push(npc);
+    }
+
+    /// Take someone out of the world, if they're still in it.
+    pub fn remove_npc(&mut self, id: NpcId) -> Option<Npc> {
+        let pos = self.npcs.iter().position(|n| n.id == id)?;
+        let npc = self.npcs.remove(pos);
+        // Everyone after it moved down a place.
+        for level in &mut self.levels { level.npc_grid.clear(); }
+        for (i, n) in self.npcs.iter().enumerate() { self.levels[n.room].npc_grid.set(n.x, n.y, Some(i)); }
+        Some(npc)
     }
 
-    fn make_level(base_seed: u64, depth: usize, width: usize, height: usize) -> (Level, (i32, i32)) {
+    /// `key_placed`: the first room holds the vault key, so the second may lock a vault with it.
+    fn make_level(base_seed: u64, depth: usize, width: usize, height: usize, key_placed: bool) -> (Level, (i32, i32)) {
         let seed = base_seed.wrapping_add(depth as u64 * 9_973);
         let mut map = generate_rooms_and_corridors(width, height, seed);
         let (sx, sy) = map.find_first_floor().unwrap_or((1, 1));
@@ -329,9 +698,29 @@ impl World {
         let door = Self::place_random_door(&mut map, seed ^ 0xD00D, spawn);
         
         let count = if depth == 0 { 3 } else { 4 };
-        let chests = Self::scatter_chests(&mut map, seed ^ 0xC1E57, spawn, door, count);
-        
-        (Level { map, door, chests }, spawn)
+        let mut chests = Self::scatter_chests(&mut map, seed ^ 0xC1E57, depth, spawn, door, count);
+
+        let mut taken = vec![spawn, door];
+        taken.extend(chests.iter().map(|c| (c.x, c.y)));
+        // The first room starts at the spawn point; the second is entered through its door.
+        let entry = if depth == 0 { spawn } else { Self::arrival(&map, door) };
+        let furnishing = objects::furnish(&mut map, seed, depth, entry, key_placed, &taken);
+        // Vaults are worth the trouble: their loot rolls as if one level deeper.
+        let mut vault_rng = StdRng::seed_from_u64(seed ^ Self::LOOT_SALT ^ 0x7A17);
+        let table = LootTable::for_depth(depth + 1);
+        for ((x, y), key) in furnishing.vault_chests {
+            let mut chest = Chest { x, y, item: None, weapon: None, gold: 0, key, opened: false };
+            match table.roll(&mut vault_rng, depth + 1) {
+                Loot::Gear(eq) => chest.weapon = Some(eq),
+                Loot::Consumable(c) => chest.item = Some(c),
+                Loot::Gold(g) => chest.gold = g,
+            }
+            chests.push(chest);
+        }
+
+        let mut level = Level::new(map, door, furnishing.objects);
+        for chest in chests { level.add_chest(chest); }
+        (level, spawn)
     }
 
     fn place_random_door(map: &mut Map, seed: u64, exclude: (i32, i32)) -> (i32, i32) {
@@ -353,16 +742,43 @@ impl World {
         door
     }
 
-    fn random_consumable(rng: &mut StdRng) -> Consumable {
-        match rng.gen_range(0..4) {
-            0 => Consumable { name: "Fiery ale".to_string(), heal: 2, atk_bonus: 2, def_bonus: 0 },
-            1 => Consumable { name: "Weeping Willow bark".to_string(), heal: 3, atk_bonus: 0, def_bonus: 0 },
-            2 => Consumable { name: "Sunny Jerky".to_string(), heal: 5, atk_bonus: 0, def_bonus: 0 },
-            _ => Consumable { name: "Frozen tears".to_string(), heal: -2, atk_bonus: 0, def_bonus: 5 },
+    pub(crate) fn basic_sword() -> Equipment {
+        Equipment { name: "Basic Sword".to_string(), slot: Slot::Sword, hp_bonus: 0, atk_bonus: 3, def_bonus: 0, speed_bonus: 3, weight: 4, rarity: Rarity::Common, durability: 20, max_durability: 20 }
+    }
+
+    pub(crate) fn basic_shield() -> Equipment {
+        Equipment { name: "Basic Shield".to_string(), slot: Slot::Shield, hp_bonus: 0, atk_bonus: 0, def_bonus: 3, speed_bonus: -2, weight: 6, rarity: Rarity::Common, durability: 20, max_durability: 20 }
+    }
+
+    /// Mah's cursed blade; Dorosht trades the Willow Axe for it.
+    pub(crate) fn weeping_dagger() -> Equipment {
+        Equipment {
+            name: "Weeping Dagger".to_string(),
+            slot: Slot::Sword,
+            hp_bonus: -100, atk_bonus: -100, def_bonus: -100, speed_bonus: -100, weight: 2,
+            rarity: Rarity::Legendary, durability: 60, max_durability: 60,
+        }
+    }
+
+    pub(crate) fn willow_axe() -> Equipment {
+        Equipment {
+            name: "Willow Axe".to_string(),
+            slot: Slot::Sword,
+            hp_bonus: 5, atk_bonus: 15, def_bonus: 5, speed_bonus: -2, weight: 8, rarity: Rarity::Rare,
+            durability: 30, max_durability: 30,
+        }
+    }
+
+    pub(crate) fn shield_of_healing() -> Equipment {
+        Equipment {
+            name: "Shield of healing".to_string(),
+            slot: Slot::Shield,
+            hp_bonus: 2, atk_bonus: 0, def_bonus: 10, speed_bonus: 0, weight: 7,
+            rarity: Rarity::Epic, durability: 40, max_durability: 40,
         }
     }
 
-    fn scatter_chests(map: &mut Map, seed: u64, spawn: (i32, i32), door: (i32, i32), target_count: usize) -> Vec<Chest> {
+    fn scatter_chests(map: &mut Map, seed: u64, depth: usize, spawn: (i32, i32), door: (i32, i32), target_count: usize) -> Vec<Chest> {
         let mut floors = Vec::new();
         for y in 0..map.height {
             for x in 0..map.width {
@@ -370,6 +786,9 @@ impl World {
             }
         }
         let mut rng = StdRng::seed_from_u64(seed);
+        // Loot has its own stream so changing the loot tables never moves the chests.
+        let mut loot_rng = StdRng::seed_from_u64(seed ^ Self::LOOT_SALT);
+        let table = LootTable::for_depth(depth);
         let mut chests = Vec::new();
         let mut exclude = vec![spawn, door];
         let count = target_count.min(floors.len());
@@ -381,42 +800,102 @@ impl World {
             }
             exclude.push(pos);
             map.set(pos.0 as usize, pos.1 as usize, Tile::Chest);
-            chests.push(Chest { x: pos.0, y: pos.1, item: Some(Self::random_consumable(&mut rng)), weapon: None, opened: false });
+            let mut chest = Chest { x: pos.0, y: pos.1, item: None, weapon: None, gold: 0, key: None, opened: false };
+            match table.roll(&mut loot_rng, depth) {
+                Loot::Gear(eq) => chest.weapon = Some(eq),
+                Loot::Consumable(c) => chest.item = Some(c),
+                Loot::Gold(g) => chest.gold = g,
+            }
+            chests.push(chest);
         }
         chests
     }
 
-    pub fn push_log(&mut self, msg: impl Into<String>) {
+    pub(crate) fn push_log(&mut self, msg: impl Into<String>) {
         self.logs.push_back(msg.into());
         while self.logs.len() > 6 { self.logs.pop_front(); }
     }
 
-    fn toggle_room(&mut self) {
-        let old_room = self.current;
-        let new_room = if old_room == 0 { 1 } else { 0 };
-        self.current = new_room;
-        let door_pos = self.levels[new_room].door;
-        let map = &self.levels[new_room].map;
-        let mut spawn = door_pos; 
-        'search: for dy in -1..=1 {
+    /// Where someone coming through `door` stands: the first floor tile around it.
+    fn arrival(map: &Map, door: (i32, i32)) -> (i32, i32) {
+        for dy in -1..=1 {
             for dx in -1..=1 {
                 if dx == 0 && dy == 0 { continue; }
-                let nx = door_pos.0 + dx;
-                let ny = door_pos.1 + dy;
-                if map.in_bounds(nx, ny) && map.get(nx as usize, ny as usize) == Tile::Floor {
-                    spawn = (nx, ny);
-                    break 'search;
-                }
+                let (nx, ny) = (door.0 + dx, door.1 + dy);
+                if map.in_bounds(nx, ny) && map.get(nx as usize, ny as usize) == Tile::Floor { return (nx, ny); }
             }
         }
+        door
+    }
+
+    fn toggle_room(&mut self) {
+        let old_room = self.current;
+        let new_room = if old_room == 0 { 1 } else { 0 };
+        self.current = new_room;
+        let spawn = Self::arrival(&self.levels[new_room].map, self.levels[new_room].door);
         self.player.x = spawn.0;
         self.player.y = spawn.1;
+        self.player.wading = None;
+        self.reveal_around_player();
         if new_room == 1 { self.push_log("You step through the door into Room 2...".to_string()); } 
         else { self.push_log("You step back into Room 1...".to_string()); }
     }
 
+    // --- Developer console ---
+
+    /// Set a story flag by name (see `StoryFlags::entries`).
+    pub(crate) fn set_flag(&mut self, name: &str, value: bool) -> Result<(), String> {
+        *self.story.get_mut(name).ok_or(format!("unknown flag: {}", name))? = value;
+        Ok(())
+    }
+
+    /// Put the player on `(x, y)` in the current room.
+    pub(crate) fn teleport(&mut self, x: i32, y: i32) -> Result<(), String> {
+        let map = self.current_map();
+        if !map.in_bounds(x, y) { return Err(format!("({}, {}) is off the map", x, y)); }
+        if !map.is_walkable(x as usize, y as usize) { return Err(format!("({}, {}) is a {:?}", x, y, map.get(x as usize, y as usize))); }
+        if let Some(npc) = self.npc_at(self.current, x, y) { return Err(format!("{} is standing there", npc.name)); }
+        self.player.x = x;
+        self.player.y = y;
+        self.player.wading = None;
+        self.reveal_around_player();
+        Ok(())
+    }
+
+    /// Step through the door into `room` (0-based) from wherever the player is.
+    pub(crate) fn goto_room(&mut self, room: usize) -> Result<(), String> {
+        if room >= self.levels.len() { return Err(format!("there are only {} rooms", self.levels.len())); }
+        if room != self.current { self.toggle_room(); }
+        Ok(())
+    }
+
+    /// What the player can see right now.
+    pub(crate) fn field_of_view(&self) -> Fov {
+        fov::compute(self.current_map(), (self.player.x, self.player.y), Self::SIGHT)
+    }
+
+    fn reveal_around_player(&mut self) {
+        let fov = self.field_of_view();
+        let level = &mut self.levels[self.current];
+        for (x, y) in fov.tiles() {
+            let i = level.map.idx(x as usize, y as usize);
+            level.explored[i] = true;
+        }
+    }
+
+    /// An NPC shows up on the overview once the tile it stands on has been explored.
+    pub(crate) fn npc_known(&self, npc: &Npc) -> bool {
+        self.levels[npc.room].is_explored(npc.x, npc.y)
+    }
+
+    fn toggle_overview(&mut self) {
+        self.overview_open = !self.overview_open;
+    }
+
     fn toggle_inventory(&mut self) {
         self.inventory_open = !self.inventory_open;
+        self.inspect_open = false;
+        self.player.inventory.craft_marks.clear();
         if self.inventory_open { self.stats_open = false; self.push_log("Inventory opened.".to_string()); } 
         else { self.push_log("Inventory closed.".to_string()); }
     }
@@ -431,32 +910,267 @@ impl World {
         let tab_before = self.player.inventory.tab;
         self.player.inventory.toggle_tab();
         let tab_after = self.player.inventory.tab;
-        let name = match tab_after { InvTab::Weapons => "Weapons", InvTab::Consumables => "Consumables", InvTab::Backpack => "Backpack" };
+        let name = match tab_after { InvTab::Gear => "Gear", InvTab::Consumables => "Consumables", InvTab::Backpack => "Backpack", InvTab::Crafting => "Crafting" };
         if tab_before != tab_after { self.push_log(format!("Inventory tab: {}", name)); }
     }
 
-    fn use_or_unequip_or_equip(&mut self) {
+    fn toggle_inspect(&mut self) {
+        self.inspect_open = !self.inspect_open;
+    }
+
+    fn cycle_inventory_sort(&mut self) {
+        // Sorting only reorders rows, so the cursor is still in range.
+        if let Some(key) = self.player.inventory.cycle_sort() {
+            self.push_log(format!("Sorted by {}.", key.name()));
+        }
+    }
+
+    fn cycle_inventory_filter(&mut self) {
+        if let Some(filter) = self.player.inventory.cycle_filter() {
+            let shown = filter.map(|k| k.name()).unwrap_or("everything");
+            self.push_log(format!("Showing: {}.", shown));
+        }
+    }
+
+    /// Drop the selected item (one unit of a stack) onto the player's tile.
+    fn drop_selected(&mut self) {
+        let inv = &mut self.player.inventory;
+        let selection = inv.selection();
+        let dropped = match selection {
+            InvSelection::Gear(slot) => self.player.unequip(slot).map(SlotItem::Gear),
+            InvSelection::Consumable(_) => inv.take_selected_consumable()
+                .map(|item| SlotItem::Stack(ConsumableStack { item, qty: 1 })),
+            InvSelection::BackpackItem(i) => {
+                let eq = inv.backpack.remove(i);
+                inv.clamp_cursors();
+                Some(SlotItem::Gear(eq))
+            }
+            InvSelection::None => None,
+        };
+        let Some(item) = dropped else { self.push_log("Nothing to drop."); return };
+        self.push_log(format!("Dropped {}.", item.label()));
+        self.drop_here(item);
+    }
+
+    /// Combine the marked items. Unknown combinations leave everything as it was.
+    fn craft(&mut self) {
+        let inv = &self.player.inventory;
+        let marked = inv.marked_ingredients();
+        if marked.iter().map(|&(_, n)| n).sum::<u32>() < 2 {
+            self.push_log("Mark at least two items with Space first.");
+            return;
+        }
+        let names: Vec<(&str, u32)> = marked.iter().filter_map(|&(i, n)| Some((inv.slot_name(i)?, n))).collect();
+        let Some(idx) = crafting::find(&names) else {
+            self.push_log("Those don't combine into anything.");
+            return;
+        };
+
+        // Highest slot first, so emptying a slot doesn't shift the ones still to take from.
+        for &(slot, n) in marked.iter().rev() {
+            for _ in 0..n { self.player.inventory.take_one(slot); }
+        }
+        self.player.inventory.craft_marks.clear();
+
+        let recipe = &crafting::recipes()[idx];
+        let name = recipe.output_name().to_string();
+        if !self.known_recipes.contains(&idx) {
+            self.known_recipes.push(idx);
+            self.push_log(format!("New recipe: {} => {}!", recipe.inputs_label(), name));
+        }
+        if let Err(item) = self.player.inventory.add_item(recipe.output.clone()) {
+            self.drop_here(item);
+            self.push_log(format!("Made {}, but your pack is full. It's on the ground.", name));
+        } else {
+            self.push_log(format!("Made {}.", name));
+        }
+    }
+
+    /// Leave `item` on the player's tile. A stack joins one of the same item already there,
+    /// so repeated drops come back up with a single E.
+    fn drop_here(&mut self, item: SlotItem) {
+        let (x, y) = (self.player.x, self.player.y);
+        let pickups = &mut self.levels[self.current].pickups;
+        if let SlotItem::Stack(new) = &item
+            && let Some(i) = pickups.iter().position(|p| {
+                p.x == x && p.y == y && matches!(&p.item, SlotItem::Stack(s) if s.item.name == new.item.name)
+            })
+        {
+            // Move it to the top of the pile, where E looks first.
+            let mut pickup = pickups.remove(i);
+            if let SlotItem::Stack(s) = &mut pickup.item { s.qty += new.qty; }
+            pickups.push(pickup);
+            return;
+        }
+        pickups.push(Pickup { x, y, item });
+    }
+
+    pub(crate) fn pickup_at(&self, x: i32, y: i32) -> Option<&Pickup> {
+        self.current_level().pickups.iter().rev().find(|p| p.x == x && p.y == y)
+    }
+
+    /// Pick up the most recently dropped thing here. Stacks come up as far as the pack allows.
+    fn pick_up_here(&mut self) {
+        let (px, py) = (self.player.x, self.player.y);
+        let pickups = &mut self.levels[self.current].pickups;
+        let Some(idx) = pickups.iter().rposition(|p| p.x == px && p.y == py) else { return };
+        let inv = &mut self.player.inventory;
+
+        let msg = match &mut pickups[idx].item {
+            SlotItem::Gear(eq) => {
+                if inv.has_room_for_equipment(eq) {
+                    let eq = eq.clone();
+                    let name = eq.name.clone();
+                    let _ = inv.add_equipment(eq);
+                    pickups.remove(idx);
+                    format!("Picked up {}.", name)
+                } else { "Your pack is full.".to_string() }
+            }
+            SlotItem::Stack(stack) => {
+                let mut taken = 0;
+                while stack.qty > 0 && inv.add_consumable(stack.item.clone()).is_ok() {
+                    stack.qty -= 1;
+                    taken += 1;
+                }
+                let name = stack.item.name.clone();
+                if stack.qty == 0 { pickups.remove(idx); }
+                match taken {
+                    0 => "Your pack is full.".to_string(),
+                    1 => format!("Picked up {}.", name),
+                    n => format!("Picked up {} x{}.", name, n),
+                }
+            }
+        };
+        self.push_log(msg);
+    }
+
+    fn open_shop(&mut self) {
+        self.shop.cursor = 0;
+        self.inventory_open = false;
+        self.inspect_open = false;
+        self.state = GameState::Shop;
+        self.push_log("Tajer: \"Everything has a price, friend.\"");
+    }
+
+    fn leave_shop(&mut self) {
+        self.state = GameState::Playing;
+        self.push_log("Tajer: \"Come back with more gold!\"");
+    }
+
+    /// Entries on the active shop tab; the sell list is the pack's slots in `sellable_slots` order.
+    pub(crate) fn shop_entries(&self) -> Vec<(SlotItem, u32)> {
+        match self.shop.tab {
+            ShopTab::Buy => self.shop.stock.iter().map(|i| (i.clone(), shop::buy_price(i))).collect(),
+            ShopTab::Sell => {
+                let inv = &self.player.inventory;
+                self.sellable_slots().into_iter()
+                    .map(|i| match inv.consumables.get(i) {
+                        Some(s) => SlotItem::Stack(s.clone()),
+                        None => SlotItem::Gear(inv.backpack[i - inv.consumables.len()].clone()),
+                    })
+                    .map(|i| { let p = shop::sell_price(&i); (i, p) })
+                    .collect()
+            }
+            ShopTab::Buyback => self.shop.buyback.iter().cloned().collect(),
+            ShopTab::Repair => self.repair_targets().iter()
+                .filter_map(|sel| self.gear_at(sel))
+                .map(|eq| (SlotItem::Gear(eq.clone()), shop::repair_price(eq)))
+                .collect(),
+        }
+    }
+
+    fn shop_trade(&mut self) {
+        let idx = self.shop.cursor;
+        let msg = match self.shop.tab {
+            ShopTab::Buy => self.shop_buy(idx),
+            ShopTab::Sell => self.shop_sell(idx),
+            ShopTab::Buyback => self.shop_buy_back(idx),
+            ShopTab::Repair => self.shop_repair(idx),
+        };
+        if let Some(m) = msg { self.push_log(m); }
+        let len = self.shop_entries().len();
+        self.shop.move_cursor(0, len);
+    }
+
+    fn shop_buy(&mut self, idx: usize) -> Option<String> {
+        let item = self.shop.stock.get(idx)?;
+        let price = shop::buy_price(item);
+        if self.player.gold < price { return Some("You can't afford that.".to_string()); }
+        let unit = match item {
+            SlotItem::Gear(eq) => SlotItem::Gear(eq.clone()),
+            SlotItem::Stack(s) => SlotItem::Stack(ConsumableStack { item: s.item.clone(), qty: 1 }),
+        };
+        let name = unit.label();
+        if self.player.inventory.add_item(unit).is_err() { return Some("Your pack is full.".to_string()); }
+        self.player.gold -= price;
+        match &mut self.shop.stock[idx] {
+            SlotItem::Stack(s) if s.qty > 1 => s.qty -= 1,
+            _ => { self.shop.stock.remove(idx); }
+        }
+        Some(format!("Bought {} for {} gold.", name, price))
+    }
+
+    /// Pack slots Tajer will buy, in `slot_labels` order. Quest items are left out: once sold
+    /// they could fall off the buyback shelf for good.
+    fn sellable_slots(&self) -> Vec<usize> {
+        let inv = &self.player.inventory;
+        let stacks = 0..inv.consumables.len();
+        let gear = inv.backpack.iter().enumerate()
+            .filter(|(_, e)| !e.is_quest_item())
+            .map(|(i, _)| inv.consumables.len() + i);
+        stacks.chain(gear).collect()
+    }
+
+    fn shop_sell(&mut self, idx: usize) -> Option<String> {
+        let slot = *self.sellable_slots().get(idx)?;
+        let item = self.player.inventory.take_one(slot)?;
+        let price = shop::sell_price(&item);
+        let name = item.label();
+        self.player.gold += price;
+        self.shop.remember_sold(item, price);
+        self.player.inventory.clamp_cursors();
+        Some(format!("Sold {} for {} gold.", name, price))
+    }
+
+    fn shop_repair(&mut self, idx: usize) -> Option<String> {
+        let target = self.repair_targets().get(idx)?.clone();
+        let price = shop::repair_price(self.gear_at(&target)?);
+        if self.player.gold < price { return Some("You can't afford that.".to_string()); }
+        self.player.gold -= price;
+        let eq = self.gear_at_mut(&target)?;
+        eq.repair(eq.max_durability);
+        Some(format!("Tajer repaired your {} for {} gold.", eq.name, price))
+    }
+
+    fn shop_buy_back(&mut self, idx: usize) -> Option<String> {
+        let (item, price) = self.shop.buyback.get(idx)?.clone();
+        if self.player.gold < price { return Some("You can't afford that.".to_string()); }
+        let name = item.label();
+        if self.player.inventory.add_item(item).is_err() { return Some("Your pack is full.".to_string()); }
+        self.player.gold -= price;
+        self.shop.buyback.remove(idx);
+        Some(format!("Bought back {} for {} gold.", name, price))
+    }
+
+    /// Use, take off or put on the selected item; false if nothing changed hands.
+    fn use_or_unequip_or_equip(&mut self) -> bool {
         let selection = self.player.inventory.selection();
-        let mut log_msg: Option<String> = None;
+        let log_msg: Option<String>;
+        let mut acted = false;
 
         match selection {
-            InvSelection::SwordSlot => {
-                let eq_opt = self.player.inventory.sword.take();
-                if let Some(eq) = eq_opt {
-                    self.player.max_hp -= eq.hp_bonus; 
-                    if self.player.hp > self.player.max_hp { self.player.hp = self.player.max_hp; }
-                    self.player.inventory.backpack.push(eq.clone());
-                    log_msg = Some(format!("Unequipped {}.", eq.name));
-                } else { log_msg = Some("No sword equipped.".to_string()); }
+            InvSelection::Gear(slot) if self.player.inventory.equipped(slot).is_some() && self.player.inventory.free_slots() == 0 => {
+                log_msg = Some("Your pack is full.".to_string());
             }
-            InvSelection::ShieldSlot => {
-                let eq_opt = self.player.inventory.shield.take();
-                if let Some(eq) = eq_opt {
-                    self.player.max_hp -= eq.hp_bonus;
-                    if self.player.hp > self.player.max_hp { self.player.hp = self.player.max_hp; }
-                    self.player.inventory.backpack.push(eq.clone());
+            InvSelection::Gear(slot) => {
+                if let Some(eq) = self.player.unequip(slot) {
                     log_msg = Some(format!("Unequipped {}.", eq.name));
-                } else { log_msg = Some("No shield equipped.".to_string()); }
+                    self.player.inventory.backpack.push(eq);
+                    acted = true;
+                } else { log_msg = Some(format!("No {} equipped.", slot.name().to_lowercase())); }
+            }
+            InvSelection::Consumable(i) if self.player.inventory.consumables[i].item.kind() == ItemKind::Tool && self.repair_targets().is_empty() => {
+                log_msg = Some(format!("Nothing needs mending; you keep the {}.", self.player.inventory.consumables[i].item.name));
             }
             InvSelection::Consumable(_) => {
                 let item_opt = self.player.inventory.take_selected_consumable();
@@ -465,57 +1179,92 @@ impl World {
                     self.player.hp = (self.player.hp + item.heal).min(self.player.max_hp);
                     let healed = self.player.hp - before;
                     if item.atk_bonus != 0 || item.def_bonus != 0 {
-                        self.player.add_temp_buff(item.atk_bonus, item.def_bonus, 0, Duration::from_secs(30));
+                        let expires_at = self.clock.after(GameClock::secs(Self::POTION_BUFF_SECS));
+                        self.player.add_temp_buff(item.atk_bonus, item.def_bonus, 0, expires_at);
+                    }
+                    let mut effects = if item.heal != 0 || item.repair == 0 { vec![Self::fmt_hp_delta(healed)] } else { Vec::new() };
+                    if item.repair > 0 {
+                        effects.push(match self.repair_most_worn(item.repair) {
+                            Some(name) => format!("repaired {}", name),
+                            None => "nothing needed mending".to_string(),
+                        });
                     }
-                    let mut effects = vec![Self::fmt_hp_delta(healed)];
                     let fmt_signed = |v: i32| if v >= 0 { format!("+{}", v) } else { format!("{}", v) };
                     if item.atk_bonus != 0 { effects.push(format!("{} ATK/30sec", fmt_signed(item.atk_bonus))); }
                     if item.def_bonus != 0 { effects.push(format!("{} DEF/30sec", fmt_signed(item.def_bonus))); }
                     log_msg = Some(format!("Used {} ({}).", item.name, effects.join(", ")));
+                    acted = true;
                 } else { log_msg = Some("No consumables to use.".to_string()); }
             }
+            InvSelection::BackpackItem(i) if self.player.inventory.backpack.get(i).is_some_and(|e| e.is_broken()) => {
+                log_msg = Some(format!("{} is broken. Repair it first.", self.player.inventory.backpack[i].unbroken_name()));
+            }
             InvSelection::BackpackItem(i) => {
                 let eq_opt = if i < self.player.inventory.backpack.len() { Some(self.player.inventory.backpack.remove(i)) } else { None };
                 if let Some(eq) = eq_opt {
-                    self.player.max_hp += eq.hp_bonus;
-                    match eq.slot {
-                        Slot::Sword => {
-                            if let Some(old) = self.player.inventory.sword.take() { 
-                                self.player.max_hp -= old.hp_bonus;
-                                self.player.inventory.backpack.push(old); 
-                            }
-                            self.player.inventory.sword = Some(eq.clone());
-                            log_msg = Some(format!("Equipped sword: {}.", eq.name));
-                        }
-                        Slot::Shield => {
-                            if let Some(old) = self.player.inventory.shield.take() { 
-                                self.player.max_hp -= old.hp_bonus;
-                                self.player.inventory.backpack.push(old); 
-                            }
-                            self.player.inventory.shield = Some(eq.clone());
-                            log_msg = Some(format!("Equipped shield: {}.", eq.name));
-                        }
-                    }
-                    if self.player.hp > self.player.max_hp { self.player.hp = self.player.max_hp; }
-                    let inv = &mut self.player.inventory;
-                    if inv.backpack.is_empty() { inv.backpack_cursor = 0; } else if inv.backpack_cursor >= inv.backpack.len() { inv.backpack_cursor = inv.backpack.len() - 1; }
+                    let kind = self.player.inventory.slot_for(&eq).map(|s| s.name().to_lowercase()).unwrap_or_default();
+                    log_msg = Some(format!("Equipped {}: {}.", kind, eq.name));
+                    if let Some(old) = self.player.equip(eq) { self.player.inventory.backpack.push(old); }
+                    self.player.inventory.clamp_cursors();
+                    acted = true;
                 } else { log_msg = Some("Nothing to equip.".to_string()); }
             }
             InvSelection::None => { log_msg = Some("Nothing to use.".to_string()); }
         }
         if let Some(m) = log_msg { self.push_log(m); }
+        acted
+    }
+
+    /// Gear that could use mending, worn pieces first, as inventory selections.
+    pub(crate) fn repair_targets(&self) -> Vec<InvSelection> {
+        let inv = &self.player.inventory;
+        let worn = GearSlot::ALL.into_iter()
+            .filter(|&s| inv.equipped(s).is_some_and(|e| e.durability < e.max_durability))
+            .map(InvSelection::Gear);
+        let packed = inv.backpack.iter().enumerate()
+            .filter(|(_, e)| e.durability < e.max_durability)
+            .map(|(i, _)| InvSelection::BackpackItem(i));
+        worn.chain(packed).collect()
+    }
+
+    pub(crate) fn gear_at(&self, sel: &InvSelection) -> Option<&Equipment> {
+        match *sel {
+            InvSelection::Gear(slot) => self.player.inventory.equipped(slot),
+            InvSelection::BackpackItem(i) => self.player.inventory.backpack.get(i),
+            _ => None,
+        }
+    }
+
+    fn gear_at_mut(&mut self, sel: &InvSelection) -> Option<&mut Equipment> {
+        match *sel {
+            InvSelection::Gear(slot) => self.player.inventory.gear_mut(slot),
+            InvSelection::BackpackItem(i) => self.player.inventory.backpack.get_mut(i),
+            _ => None,
+        }
+    }
+
+    /// The piece in the worst shape, by share of durability left; what a repair kit mends.
+    pub(crate) fn most_worn(&self) -> Option<InvSelection> {
+        self.repair_targets().into_iter().min_by_key(|sel| {
+            self.gear_at(sel).map(|e| e.durability * 100 / e.max_durability.max(1)).unwrap_or(u32::MAX)
+        })
+    }
+
+    /// Put `amount` durability into `most_worn`; returns its name.
+    fn repair_most_worn(&mut self, amount: u32) -> Option<String> {
+        let target = self.most_worn()?;
+        let eq = self.gear_at_mut(&target)?;
+        eq.repair(amount);
+        Some(eq.name.clone())
     }
 
     fn start_chest_dialogue(&mut self, room: usize, x: i32, y: i32, item: Option<Consumable>, weapon: Option<Equipment>) {
-        let name = if let Some(c) = &item { c.name.clone() } else if let Some(w) = &weapon { w.name.clone() } else { "nothing".to_string() };
-        let pages = vec![format!(
-            "You found a treasure chest!\nInside is: {}\n\n(A) Put in inventory\n(B) Use now (Consumable)\n(C) Throw away",
-            name
-        )];
-        self.dialogue = Some(DialogueSession {
-            npc: NpcId::MayorSol, title: "Treasure Chest".to_string(), pages, page_index: 0,
-            awaiting: Some(AwaitingChoice::Chest { room, x, y, item, weapon }),
-        });
+        let name = Self::contents_name(&item, &weapon);
+        let pages = vec![format!("You found a treasure chest!\nInside is: {{gold:{}}}", name)];
+        self.dialogue = Some(
+            DialogueSession::aside(Speaker::Chest, "Treasure Chest", pages)
+                .with_choice(AwaitingChoice::Chest { room, x, y, item, weapon }),
+        );
         self.state = GameState::Dialogue;
     }
 
@@ -524,25 +1273,57 @@ impl World {
         let px = self.player.x;
         let py = self.player.y;
         let level = &mut self.levels[room];
-        if let Some(chest) = level.chests.iter_mut().find(|c| !c.opened && c.x == px && c.y == py) {
+        if let Some(i) = level.chest_grid.get(px, py).filter(|&i| !level.chests[i].opened) {
+            let chest = &mut level.chests[i];
             chest.opened = true;
             level.map.set(px as usize, py as usize, Tile::Floor);
             let item = chest.item.take();
             let weapon = chest.weapon.take();
-            self.start_chest_dialogue(room, px, py, item, weapon);
+            let gold = std::mem::take(&mut chest.gold);
+            let key = chest.key.take();
+            if gold > 0 {
+                self.player.gold += gold;
+                self.push_log(format!("You found {} gold!", gold));
+            }
+            if let Some(key) = &key {
+                self.player.inventory.keys.push(key.clone());
+                self.push_log(format!("You found the {}!", key));
+            }
+            if item.is_some() || weapon.is_some() || (gold == 0 && key.is_none()) {
+                self.start_chest_dialogue(room, px, py, item, weapon);
+            }
         }
     }
 
     // --- BATTLE LOGIC ---
-    fn start_battle(&mut self, enemy_id: NpcId) {
-        let (name, hp, atk, def, spd) = match enemy_id {
-            NpcId::Shab => ("Shab", 10, 3, 0, 4),
-            NpcId::Krad => ("Krad", 20, 6, 4, 0),
-            NpcId::Mah => ("Mah", 30, 12, 10, 8),
+    /// Everyone who can be fought, in story order.
+    pub const ENEMIES: [NpcId; 4] = [NpcId::Shab, NpcId::Krad, NpcId::Mah, NpcId::MayorSol];
+
+    /// Gold the player loots from an enemy they beat.
+    pub fn enemy_bounty(enemy_id: NpcId) -> u32 {
+        match enemy_id {
+            NpcId::Shab => 10,
+            NpcId::Krad => 20,
+            NpcId::Mah => 40,
+            _ => 0,
+        }
+    }
+
+    /// Name, HP, attack, defense and speed of an enemy; None for NPCs who don't fight.
+    pub fn enemy_stats(enemy_id: NpcId) -> Option<(&'static str, i32, i32, i32, i32)> {
+        match enemy_id {
+            NpcId::Shab => Some(("Shab", 10, 3, 0, 4)),
+            NpcId::Krad => Some(("Krad", 20, 6, 4, 0)),
+            NpcId::Mah => Some(("Mah", 30, 12, 10, 8)),
             // Final Boss
-            NpcId::MayorSol => ("Mayor Sol", 40, 10, 30, 0),
-            _ => return,
-        };
+            NpcId::MayorSol => Some(("Mayor Sol", 40, 10, 30, 0)),
+            _ => None,
+        }
+    }
+
+    /// Start a fight with `enemy_id` where the player stands, as if the dialogue had led to it.
+    pub(crate) fn start_battle(&mut self, enemy_id: NpcId) {
+        let Some((name, hp, atk, def, spd)) = Self::enemy_stats(enemy_id) else { return };
 
         self.battle = Some(BattleSession {
             enemy_id,
@@ -554,29 +1335,38 @@ impl World {
             enemy_speed: spd,
             penalty_mode: false,
             player_initiated: false, 
+            enemy_fx: None,
+            player_fx: None,
+            idle_ticks: 0,
+            timer_paused: false,
+            terrain: self.current_map().get(self.player.x as usize, self.player.y as usize),
         });
         self.state = GameState::Battle;
         self.push_log(format!("Battle started against {}!", name));
     }
 
-    fn calc_damage(atk: i32) -> i32 {
+    /// Re-roll the combat dice from `seed`, e.g. to fight the same battle many ways.
+    pub(crate) fn reseed_dice(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed ^ Self::COMBAT_SALT);
+    }
+
+    pub(crate) fn calc_damage(atk: i32) -> i32 {
         (atk as f32 * 1.2) as i32
     }
 
-    fn try_deflect(def: i32) -> bool {
-        let chance = (def as f32 / 10.0) * 0.2;
-        rand::random::<f32>() < chance
+    fn try_deflect(&mut self, def: i32, terrain: Tile) -> bool {
+        let mut chance = (def as f32 / 10.0) * 0.2;
+        if terrain == Tile::Ice { chance /= 2.0; }
+        self.rng.gen_range(0.0..1.0) < chance
     }
 
-    fn apply_battle_turn(&mut self, opt: u8, penalty: bool) {
+    fn apply_battle_turn(&mut self, opt: u8) {
         let mut end_battle = false;
         let mut player_won = false;
 
         if let Some(mut bs) = self.battle.take() {
-            if penalty { bs.penalty_mode = true; }
-            let p_spd = self.player.speed();
-            let e_spd = bs.enemy_speed;
-            let player_first = if bs.penalty_mode { false } else { p_spd >= e_spd };
+            bs.idle_ticks = 0;
+            let player_first = bs.player_acts_first(self.player.speed());
 
             match opt {
                 1 => { // Fight
@@ -596,7 +1386,7 @@ impl World {
                         self.push_log("You started this, finish it!");
                         self.perform_enemy_attack(&mut bs);
                     } else {
-                        if rand::random::<f32>() < 0.5 {
+                        if self.rng.gen_bool(0.5) {
                             self.push_log("You fled the battle!");
                             end_battle = true;
                         } else {
@@ -608,6 +1398,12 @@ impl World {
                 _ => {}
             }
 
+            if bs.terrain == Tile::Lava && !end_battle && bs.enemy_hp > 0 && self.player.hp > 0 {
+                bs.enemy_hp -= Self::LAVA_ROUND_DAMAGE;
+                self.player.hp -= Self::LAVA_ROUND_DAMAGE;
+                self.push_log(format!("The lava scorches you both ({}).", Self::fmt_hp_delta(-Self::LAVA_ROUND_DAMAGE)));
+            }
+
             if bs.enemy_hp <= 0 {
                 player_won = true;
                 end_battle = true;
@@ -619,6 +1415,8 @@ impl World {
             if !end_battle {
                 self.battle = Some(bs);
             } else {
+                let fled = !player_won && self.player.hp > 0;
+                self.last_battle = Some(BattleEnd { enemy_id: bs.enemy_id, won: player_won, fled, hp_left: self.player.hp });
                 if player_won {
                     self.handle_win(bs.enemy_id);
                 }
@@ -629,21 +1427,49 @@ impl World {
 
     fn perform_player_attack(&mut self, bs: &mut BattleSession) {
         let dmg = Self::calc_damage(self.player.attack());
-        if Self::try_deflect(bs.enemy_def) {
+        if self.try_deflect(bs.enemy_def, bs.terrain) {
+            bs.enemy_fx = Some(HitFx { kind: HitKind::Deflect, ticks_left: Self::HIT_FX_TICKS });
             self.push_log(format!("{} deflected your attack!", bs.enemy_name));
         } else {
             bs.enemy_hp -= dmg;
+            bs.enemy_fx = Some(HitFx { kind: HitKind::Hit, ticks_left: Self::HIT_FX_TICKS });
             self.push_log(format!("You hit {} for {} dmg.", bs.enemy_name, dmg));
         }
+        // Blocked or not, the blade takes the knock.
+        self.wear_gear(&[GearSlot::Sword]);
     }
 
     fn perform_enemy_attack(&mut self, bs: &mut BattleSession) {
         let dmg = Self::calc_damage(bs.enemy_atk);
-        if Self::try_deflect(self.player.defense()) {
+        let cover = if bs.terrain == Tile::Grass { Self::GRASS_COVER } else { 0 };
+        if self.try_deflect(self.player.defense() + cover, bs.terrain) {
+            bs.player_fx = Some(HitFx { kind: HitKind::Deflect, ticks_left: Self::HIT_FX_TICKS });
             self.push_log(format!("You deflected {}'s attack!", bs.enemy_name));
+            self.wear_gear(&[GearSlot::Shield]);
         } else {
             self.player.hp -= dmg;
+            bs.player_fx = Some(HitFx { kind: HitKind::Hit, ticks_left: Self::HIT_FX_TICKS });
             self.push_log(format!("{} hit you for {} dmg.", bs.enemy_name, dmg));
+            self.wear_gear(&[GearSlot::Helmet, GearSlot::Armour, GearSlot::Boots]);
+        }
+    }
+
+    /// One point of wear on each of these slots; anything that breaks comes off into the pack.
+    fn wear_gear(&mut self, slots: &[GearSlot]) {
+        for &slot in slots {
+            let broke = match self.player.inventory.gear_mut(slot) {
+                Some(eq) => eq.wear(1),
+                None => false,
+            };
+            if !broke { continue; }
+            let Some(eq) = self.player.unequip(slot) else { continue };
+            let name = eq.unbroken_name().to_string();
+            if let Err(eq) = self.player.inventory.add_equipment(eq) {
+                self.drop_here(SlotItem::Gear(eq));
+                self.push_log(format!("Your {} broke! It fell to the ground.", name));
+            } else {
+                self.push_log(format!("Your {} broke!", name));
+            }
         }
     }
 
@@ -654,31 +1480,33 @@ impl World {
         self.player.base_defense += 3;
         self.player.base_speed += 3;
         self.push_log("HP restored & Stats increased (+3)!");
+        let bounty = Self::enemy_bounty(id);
+        if bounty > 0 {
+            self.player.gold += bounty;
+            self.push_log(format!("You looted {} gold.", bounty));
+        }
 
         match id {
             NpcId::Shab => {
-                self.shab_defeated = true;
-                self.start_dialogue_raw("Shab", vec!["I can’t believe I lost to the likes of you…".to_string()]);
+                self.story.shab_defeated = true;
+                self.start_dialogue_raw(NpcId::Shab, "Shab", vec!["I can’t believe I lost to the likes of you…".to_string()]);
             }
             NpcId::Krad => {
-                self.krad_defeated = true;
-                self.start_dialogue_raw("Krad", vec!["My armor….".to_string()]);
+                self.story.krad_defeated = true;
+                self.start_dialogue_raw(NpcId::Krad, "Krad", vec!["My armor….".to_string()]);
             }
             NpcId::Mah => {
-                self.mah_defeated = true;
-                let boss_pos = if let Some(pos) = self.npcs.iter().position(|n| n.id == NpcId::Mah) {
-                    let npc = self.npcs.remove(pos);
+                self.story.mah_defeated = true;
+                let boss_pos = if let Some(npc) = self.remove_npc(NpcId::Mah) {
                     let chest = Chest {
                         x: npc.x, y: npc.y,
                         item: None,
-                        weapon: Some(Equipment {
-                            name: "Weeping Dagger".to_string(),
-                            slot: Slot::Sword,
-                            hp_bonus: -100, atk_bonus: -100, def_bonus: -100, speed_bonus: -100,
-                        }),
+                        weapon: Some(Self::weeping_dagger()),
+                        gold: 0,
+                        key: None,
                         opened: false
                     };
-                    self.levels[1].chests.push(chest);
+                    self.levels[1].add_chest(chest);
                     self.levels[1].map.set(npc.x as usize, npc.y as usize, Tile::Chest);
                     Some((npc.x, npc.y))
                 } else { None };
@@ -690,196 +1518,172 @@ impl World {
                     let shield_chest = Chest {
                         x: sx, y: sy,
                         item: None,
-                        weapon: Some(Equipment {
-                            name: "Shield of healing".to_string(),
-                            slot: Slot::Shield,
-                            hp_bonus: 2, atk_bonus: 0, def_bonus: 10, speed_bonus: 0,
-                        }),
+                        weapon: Some(Self::shield_of_healing()),
+                        gold: 0,
+                        key: None,
                         opened: false
                     };
-                    self.levels[1].chests.push(shield_chest);
+                    self.levels[1].add_chest(shield_chest);
                     self.levels[1].map.set(sx as usize, sy as usize, Tile::Chest);
                 }
 
-                self.start_dialogue_raw("Mah", vec![
+                self.start_dialogue_raw(NpcId::Mah, "Mah", vec![
                     "I underestimated you…".to_string(),
                     "Listen, Sol, is not…".to_string(), "what".to_string(), "you".to_string(), "thin-".to_string()
                 ]);
             }
             NpcId::MayorSol => {
-                self.mayor_defeated = true;
-                self.start_dialogue_raw("Mayor Sol", vec!["NOOOOOO, THE SHAREHOLDERSSSSSSSS".to_string()]);
+                self.story.mayor_defeated = true;
+                self.start_dialogue_raw(NpcId::MayorSol, "Mayor Sol", vec!["NOOOOOO, THE SHAREHOLDERSSSSSSSS".to_string()]);
             }
             _ => {}
         }
         self.push_log("You won the battle!");
     }
 
-    fn start_dialogue_raw(&mut self, title: &str, pages: Vec<String>) {
-        self.dialogue = Some(DialogueSession {
-            npc: NpcId::Random1,
-            title: title.to_string(),
-            pages,
-            page_index: 0,
-            awaiting: None,
-        });
+    /// One-off lines (e.g. after a battle) that must not trigger the speaker's story hooks.
+    fn start_dialogue_raw(&mut self, speaker: NpcId, title: &str, pages: Vec<String>) {
+        self.dialogue = Some(DialogueSession::aside(Speaker::Npc(speaker), title, pages));
         self.state = GameState::Dialogue;
     }
 
-    fn swap_dorosht_item(&mut self) -> bool {
-        let mut found = false;
-        let mut idx_to_remove = None;
-        let mut from_backpack = false;
-
-        if let Some(ref s) = self.player.inventory.sword {
-            if s.name == "Weeping Dagger" {
-                found = true;
-            }
-        }
-        
-        if found {
-            self.player.inventory.sword = None;
-            self.player.max_hp += 100; 
-        } else {
-            for (i, item) in self.player.inventory.backpack.iter().enumerate() {
-                if item.name == "Weeping Dagger" {
-                    idx_to_remove = Some(i);
-                    from_backpack = true;
-                    found = true;
-                    break;
-                }
-            }
-        }
-
-        if found {
-            if from_backpack {
-                if let Some(i) = idx_to_remove {
-                    self.player.inventory.backpack.remove(i);
-                }
+    /// Hand Dorosht the Weeping Dagger for the Willow Axe. Err is what he says when the trade can't happen yet.
+    fn swap_dorosht_item(&mut self) -> Result<(), String> {
+        let inv = &self.player.inventory;
+        let is_dagger = |e: &Equipment| e.unbroken_name() == "Weeping Dagger";
+        let wielded = inv.equipped(GearSlot::Sword).filter(|e| is_dagger(e));
+        let packed = inv.backpack.iter().position(is_dagger);
+        let axe = Self::willow_axe();
+
+        if let Some(dagger) = wielded {
+            // The dagger comes out of your hand, so the axe needs a place in the pack.
+            let fits = inv.free_slots() > 0 && inv.max_weight.is_none_or(|max| inv.weight() - dagger.weight + axe.weight <= max);
+            if !fits {
+                return Err("That’s the one! But where would you put the axe? Make some room in your pack and come back.".to_string());
             }
-            let axe = Equipment {
-                name: "Willow Axe".to_string(),
-                slot: Slot::Sword,
-                hp_bonus: 5, atk_bonus: 15, def_bonus: 5, speed_bonus: -2,
-            };
+            self.player.unequip(GearSlot::Sword);
+            self.player.inventory.backpack.push(axe);
+        } else if let Some(i) = packed {
+            // A straight trade: the axe takes the dagger's slot.
+            self.player.inventory.backpack.remove(i);
             self.player.inventory.backpack.push(axe);
-            return true;
+        } else {
+            return Err("Come back with the dagger, and she’s yours!".to_string());
         }
-        false
+        Ok(())
     }
 
     fn start_dialogue_for(&mut self, npc: &Npc) {
         let session = match npc.id {
             // Existing NPCs
             NpcId::MayorSol => {
-                 if self.mayor_defeated {
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["...".to_string()], page_index: 0, awaiting: None }
-                 } else if self.dorosht_completed && self.mah_defeated {
-                     DialogueSession { 
-                         npc: npc.id, 
-                         title: npc.name.clone(), 
-                         pages: vec![
+                 if self.story.mayor_defeated {
+                     DialogueSession::new(npc.id, &npc.name, vec!["...".to_string()])
+                 } else if self.story.dorosht_completed && self.story.mah_defeated {
+                     DialogueSession::new(npc.id, &npc.name, vec![
                              "GREEEAAAAT JOB!!!!!!".to_string(),
                              "YOU DID IT!!!!".to_string(),
                              "but".to_string(),
                              "It was for me...".to_string(),
-                             "You see, I LIED. To you, to this town, TO EVERYONE!".to_string(),
+                             "You see, I {b:LIED}. To you, to this town, TO EVERYONE!".to_string(),
                              "I told them, this sunshine drought was due to the Weeping, I even told them lies of their namesake, but it was for a good reason you see...".to_string(),
-                             "FOR MONEY! MUAHAHAHA".to_string(),
+                             "{red:{b:FOR MONEY! MUAHAHAHA}}".to_string(),
                              "CAPATALISM SHALL PREVAIL!!!!".to_string()
-                         ], 
-                         page_index: 0, 
-                         awaiting: None 
-                     }
-                 } else if self.mayor_done { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Well, what’re you still standing here for? GO TO NOOR!".to_string()], page_index: 0, awaiting: None } 
+                         ])
+                 } else if self.story.mayor_done { 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Well, what’re you still standing here for? GO TO NOOR!".to_string()]) 
                  } else { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Welcome to Sunny Days, visitor! I am Mayor Sol. We are normally much more able to take in tourists, but you may have arrived at a bad time. The Weeping have made it a rough time, they have completely taken over the Weeping Willow forests.".to_string(), "What’s that? The weeping sound like they belong in the Weeping Willow Forests? No! That’s nonsense, the only reason they are called the weeping, is because they WEEP before they kill! I mean, is it not right there in the name? Keep up! Ok, but my friend, you MUST help us get them out. Without our Weeping Willow bark, we are losing our health! Please will you help? (Y/N)".to_string()], page_index: 0, awaiting: Some(AwaitingChoice::YesNoMayor) } 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Welcome to Sunny Days, visitor! I am Mayor Sol. We are normally much more able to take in tourists, but you may have arrived at a bad time. The Weeping have made it a rough time, they have completely taken over the Weeping Willow forests.".to_string(), "What’s that? The weeping sound like they belong in the Weeping Willow Forests? No! That’s nonsense, the only reason they are called the weeping, is because they {b:WEEP} before they kill! I mean, is it not right there in the name? Keep up! Ok, but my friend, you {b:MUST} help us get them out. Without our {gold:Weeping Willow bark}, we are losing our health! Please will you help?".to_string()]).with_choice(AwaitingChoice::YesNoMayor) 
                  }
             },
             NpcId::Noor => {
-                 if self.dorosht_completed && self.mah_defeated {
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Always knew that SOB was no good, its up to you....".to_string()], page_index: 0, awaiting: None }
-                 } else if self.noor_done { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Scram! Go to Lamp and get whatever you’re missing!!".to_string()], page_index: 0, awaiting: None } 
+                 if self.story.dorosht_completed && self.story.mah_defeated {
+                     DialogueSession::new(npc.id, &npc.name, vec!["Always knew that SOB was no good, its up to you....".to_string()])
+                 } else if self.story.noor_done { 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Scram! Go to Lamp and get whatever you’re missing!!".to_string()]) 
                  } else { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Hey there partner!".to_string(), "What’s that, the Mayor sent you here? Damn Sol, always ruining my day. What! No not you, you seem okay… ish. So you’re gonna go and fight the Weeping ay? Well you’ll need a weapon. Grab one: (A) Basic Sword  (B) Basic Shield".to_string(), "Good choice! Now I’ll keep the other one to be fair, if you want your second choice, go see Lamp!".to_string()], page_index: 0, awaiting: Some(AwaitingChoice::ABNoorWeapon) } 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Hey there partner!".to_string(), "What’s that, the Mayor sent you here? Damn Sol, always ruining my day. What! No not you, you seem okay… {i:ish}. So you’re gonna go and fight the Weeping ay? Well you’ll need a weapon. Grab one:".to_string()]).with_choice(AwaitingChoice::ABNoorWeapon) 
                  }
             },
             NpcId::Lamp => {
-                 if self.dorosht_completed && self.mah_defeated {
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I-I can't believe it....".to_string()], page_index: 0, awaiting: None }
-                 } else if !self.noor_done { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Hey aren’t you supposed to talk to Noor first?".to_string()], page_index: 0, awaiting: None } 
-                 } else if self.lamp_done { 
-                     DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Well good luck, if you’re fighting the Weeping, you’ll need it!".to_string()], page_index: 0, awaiting: None } 
+                 if self.story.dorosht_completed && self.story.mah_defeated {
+                     DialogueSession::new(npc.id, &npc.name, vec!["I-I can't believe it....".to_string()])
+                 } else if !self.story.noor_done { 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Hey aren’t you supposed to talk to Noor first?".to_string()]) 
+                 } else if self.story.lamp_done { 
+                     DialogueSession::new(npc.id, &npc.name, vec!["Well good luck, if you’re fighting the Weeping, you’ll need it!".to_string()]) 
                  } else {
-                    let missing = if self.player.inventory.sword.is_none() { "Sword" } else { "Shield" };
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec![format!("Hey! Did Noor send you? Yeah, they’re a bit rough around the edges. So you’re missing a {}, well take this!", missing), format!("You got the {}.", missing)], page_index: 0, awaiting: None }
+                    let missing = if self.player.inventory.equipped(GearSlot::Sword).is_none() { "Sword" } else { "Shield" };
+                    DialogueSession::new(npc.id, &npc.name, vec![format!("Hey! Did Noor send you? Yeah, they’re a bit rough around the edges. So you’re missing a {}, well take this!", missing), format!("You got the {}.", missing)])
                  }
             },
             NpcId::Dorosht => {
-                if self.dorosht_completed {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Thanks again!".to_string()], page_index: 0, awaiting: None }
-                } else if self.dorosht_accepted {
-                    if self.swap_dorosht_item() {
-                        self.dorosht_completed = true;
-                        DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec![
-                            "Well is that it! My goodness, I never thought you’d come back alive, let alone with the dagger! A deal’s a deal, here is the axe!".to_string()
-                        ], page_index: 0, awaiting: None }
-                    } else {
-                        DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Come back with the dagger, and she’s yours!".to_string()], page_index: 0, awaiting: None }
+                if self.story.dorosht_completed {
+                    DialogueSession::new(npc.id, &npc.name, vec!["Thanks again!".to_string()])
+                } else if self.story.dorosht_accepted {
+                    match self.swap_dorosht_item() {
+                        Ok(()) => {
+                            self.story.dorosht_completed = true;
+                            DialogueSession::new(npc.id, &npc.name, vec![
+                                "Well is that it! My goodness, I never thought you’d come back alive, let alone with the dagger! A deal’s a deal, here is the axe!".to_string()
+                            ])
+                        }
+                        Err(line) => DialogueSession::new(npc.id, &npc.name, vec![line]),
                     }
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Hey there mighty traveler, rumor is, you’re going to go into the Weeping Willow Woods… if you do, might you fetch me something? (Y/N)".to_string()], page_index: 0, awaiting: Some(AwaitingChoice::YesNoDorosht) }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Hey there mighty traveler, rumor is, you’re going to go into the Weeping Willow Woods… if you do, might you fetch me something?".to_string()]).with_choice(AwaitingChoice::YesNoDorosht)
                 }
             },
             NpcId::Random1 => {
-                if self.dorosht_completed && self.mah_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["The sunshine, but at what cost...".to_string()], page_index: 0, awaiting: None }
+                if self.story.dorosht_completed && self.story.mah_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["The sunshine, but at what cost...".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Isn’t it bad? So gloomy, so dark, I need some vitamin D pills or something!".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Isn’t it bad? So gloomy, so dark, I need some vitamin D pills or something!".to_string()])
                 }
             },
             NpcId::Random2 => {
-                if self.dorosht_completed && self.mah_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I KNEW IT!".to_string()], page_index: 0, awaiting: None }
+                if self.story.dorosht_completed && self.story.mah_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["I KNEW IT!".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I actually overheard the Mayor talking to himself, I think he’s going a bit cukoo!!".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["I actually overheard the Mayor talking to himself, I think he’s going a bit cukoo!!".to_string()])
                 }
             },
             NpcId::Random3 => {
-                if self.dorosht_completed && self.mah_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I'd rather pay taxes than go through what they have, for the sake they have...".to_string()], page_index: 0, awaiting: None }
+                if self.story.dorosht_completed && self.story.mah_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["I'd rather pay taxes than go through what they have, for the sake they have...".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Oh please, if you think the Weeping are bad, wait until you hear from the IRS!".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Oh please, if you think the Weeping are bad, wait until you hear from the IRS!".to_string()])
                 }
             },
             
-            NpcId::Weeping1 => DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I can’t believe that’s how they think of us in here, we literally get our name from the Weeping Willow trees that we LIVE in. Like come on!".to_string()], page_index: 0, awaiting: None },
-            NpcId::Weeping2 => DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["It sure is cold out, all that global warming bibble babble is a hoax!".to_string()], page_index: 0, awaiting: None },
-            NpcId::Weeping3 => DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Have you talked to the guy who thinks global warming is fake? What a nut!".to_string()], page_index: 0, awaiting: None },
-            NpcId::Weeping4 => DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["I had a friend in that village…".to_string(), "His name meant bright, just like how he was.".to_string(), "I wonder how he’s doing…".to_string()], page_index: 0, awaiting: None },
+            NpcId::Weeping1 => DialogueSession::new(npc.id, &npc.name, vec!["I can’t believe that’s how they think of us in here, we literally get our name from the Weeping Willow trees that we LIVE in. Like come on!".to_string()]),
+            NpcId::Weeping2 => DialogueSession::new(npc.id, &npc.name, vec!["It sure is cold out, all that global warming bibble babble is a hoax!".to_string()]),
+            NpcId::Weeping3 => DialogueSession::new(npc.id, &npc.name, vec!["Have you talked to the guy who thinks global warming is fake? What a nut!".to_string()]),
+            NpcId::Weeping4 => DialogueSession::new(npc.id, &npc.name, vec!["I had a friend in that village…".to_string(), "His name meant bright, just like how he was.".to_string(), "I wonder how he’s doing…".to_string()]),
 
             NpcId::Shab => {
-                if self.shab_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Get away from me, I’m training…".to_string()], page_index: 0, awaiting: None }
+                if self.story.shab_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["Get away from me, I’m training…".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Hey! You’re not supposed to be in here, who are you?!".to_string(), "Wait, nevermind, I couldn’t care less, are you ready to die?!!!".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Hey! You’re not supposed to be in here, who are you?!".to_string(), "Wait, nevermind, I couldn’t care less, are you ready to die?!!!".to_string()])
                 }
             },
             NpcId::Krad => {
-                if self.krad_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["W-what do you want from me?!?!?!".to_string(), "LEAVE ME BE, you’ve shattered my honor, and my armor….".to_string(), " *sniffles* ".to_string()], page_index: 0, awaiting: None }
+                if self.story.krad_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["W-what do you want from me?!?!?!".to_string(), "LEAVE ME BE, you’ve shattered my honor, and my armor….".to_string(), " *sniffles* ".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Who are you…".to_string(), "Doesn’t matter… my armor…".to_string(), "IS IMPENETRABLE".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Who are you…".to_string(), "Doesn’t matter… my armor…".to_string(), "{red:{b:IS IMPENETRABLE}}".to_string()])
                 }
             },
+            NpcId::Tajer => {
+                self.open_shop();
+                return;
+            }
             NpcId::Mah => {
-                if self.shab_defeated && self.krad_defeated {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["You low-class scum".to_string(), "You come into my home, my community…".to_string(), "AND KILL MY MEN?!?!?!".to_string()], page_index: 0, awaiting: None }
+                if self.story.shab_defeated && self.story.krad_defeated {
+                    DialogueSession::new(npc.id, &npc.name, vec!["You low-class scum".to_string(), "You come into my home, my community…".to_string(), "{red:{b:AND KILL MY MEN?!?!?!}}".to_string()])
                 } else {
-                    DialogueSession { npc: npc.id, title: npc.name.clone(), pages: vec!["Insignificant being, begone from my sight, my men will handle you…".to_string()], page_index: 0, awaiting: None }
+                    DialogueSession::new(npc.id, &npc.name, vec!["Insignificant being, begone from my sight, my men will handle you…".to_string()])
                 }
             }
         };
@@ -890,15 +1694,19 @@ impl World {
     fn dialogue_continue(&mut self) {
         let mut start_battle_id = None;
         if let Some(d) = &mut self.dialogue {
-            if d.page_index + 1 < d.pages.len() {
-                d.page_index += 1;
+            if !d.fully_revealed() {
+                d.revealed = d.page_len();
+                return;
+            }
+            if !d.on_last_page() {
+                d.next_page();
             } else {
                 match d.npc {
-                    NpcId::Shab if !self.shab_defeated => start_battle_id = Some(NpcId::Shab),
-                    NpcId::Krad if !self.krad_defeated => start_battle_id = Some(NpcId::Krad),
-                    NpcId::Mah if !self.mah_defeated && self.shab_defeated && self.krad_defeated => start_battle_id = Some(NpcId::Mah),
-                    NpcId::MayorSol if !self.mayor_defeated && self.dorosht_completed && self.mah_defeated => start_battle_id = Some(NpcId::MayorSol),
-                    NpcId::MayorSol if self.mayor_defeated => {
+                    Some(NpcId::Shab) if !self.story.shab_defeated => start_battle_id = Some(NpcId::Shab),
+                    Some(NpcId::Krad) if !self.story.krad_defeated => start_battle_id = Some(NpcId::Krad),
+                    Some(NpcId::Mah) if !self.story.mah_defeated && self.story.shab_defeated && self.story.krad_defeated => start_battle_id = Some(NpcId::Mah),
+                    Some(NpcId::MayorSol) if !self.story.mayor_defeated && self.story.dorosht_completed && self.story.mah_defeated => start_battle_id = Some(NpcId::MayorSol),
+                    Some(NpcId::MayorSol) if self.story.mayor_defeated => {
                         self.state = GameState::Fin;
                         self.dialogue = None;
                         return;
@@ -919,79 +1727,219 @@ impl World {
         let up = c.to_ascii_uppercase();
 
         match awaiting {
-            Some(AwaitingChoice::YesNoMayor) => {
-                if up == 'Y' || up == 'N' {
-                    let yes = up == 'Y';
-                    self.mayor_done = true;
-                    if let Some(d) = &mut self.dialogue {
-                        d.awaiting = None;
-                        d.pages = vec![if yes { "Why thank you! Now go talk to Noor to get you started.".to_string() } else { "Aren’t you rude, I’ve been nothing but kind. Fine, go to Noor to get you started I guess…".to_string() }];
-                        d.page_index = 0;
-                    }
+            Some(AwaitingChoice::YesNoMayor) if up == 'Y' || up == 'N' => {
+                let yes = up == 'Y';
+                self.story.mayor_done = true;
+                if let Some(d) = &mut self.dialogue {
+                    d.awaiting = None;
+                    d.set_pages(vec![if yes { "Why thank you! Now go talk to {gold:Noor} to get you started.".to_string() } else { "Aren’t you rude, I’ve been nothing but kind. Fine, go to {gold:Noor} to get you started I guess…".to_string() }]);
                 }
             }
             Some(AwaitingChoice::YesNoDorosht) => {
                 if up == 'Y' {
-                    self.dorosht_accepted = true;
+                    self.story.dorosht_accepted = true;
                     if let Some(d) = &mut self.dialogue {
                         d.awaiting = None;
-                        d.pages = vec![
-                            "Do ya really mean it?! Why thank you mighty one! What I’m looking for is…. the Weeping dagger!".to_string(),
+                        d.set_pages(vec![
+                            "Do ya really mean it?! Why thank you mighty one! What I’m looking for is…. the {gold:Weeping dagger}!".to_string(),
                             "Legend states, it is a cursed blade, forged by the most brutal of the Weeping.".to_string(),
-                            "Now don’t you worry, you won’t trade for nothing, I have here is a mighty fine weapon, the Willow Axe, made with a fine steel, along with the strong bark of the Weeping Willow Forest.".to_string(),
+                            "Now don’t you worry, you won’t trade for nothing, I have here is a mighty fine weapon, the {gold:Willow Axe}, made with a fine steel, along with the strong bark of the Weeping Willow Forest.".to_string(),
                             "Come back with the dagger, and she’s yours!".to_string(),
-                        ];
-                        d.page_index = 0;
-                    }
-                } else if up == 'N' {
-                    if let Some(d) = &mut self.dialogue {
-                        d.awaiting = None;
-                        d.pages = vec!["Well, I won’t be going anywhere in case you change your mind.".to_string()];
-                        d.page_index = 0;
+                        ]);
                     }
+                } else if up == 'N' && let Some(d) = &mut self.dialogue {
+                    d.awaiting = None;
+                    d.set_pages(vec!["Well, I won’t be going anywhere in case you change your mind.".to_string()]);
                 }
             }
-            Some(AwaitingChoice::ABNoorWeapon) => {
-                if up == 'A' || up == 'B' {
-                    if up == 'A' { self.player.equip_sword(Equipment { name: "Basic Sword".to_string(), slot: Slot::Sword, hp_bonus: 0, atk_bonus: 3, def_bonus: 0, speed_bonus: 3 }); } 
-                    else { self.player.equip_shield(Equipment { name: "Basic Shield".to_string(), slot: Slot::Shield, hp_bonus: 0, atk_bonus: 0, def_bonus: 3, speed_bonus: -2 }); }
-                    self.noor_done = true;
-                    if let Some(d) = &mut self.dialogue { d.awaiting = None; d.page_index = 2; }
+            Some(AwaitingChoice::ABNoorWeapon) if up == 'A' || up == 'B' => {
+                self.player.equip(if up == 'A' { Self::basic_sword() } else { Self::basic_shield() });
+                self.story.noor_done = true;
+                if let Some(d) = &mut self.dialogue {
+                    d.awaiting = None;
+                    d.set_pages(vec!["Good choice! Now I’ll keep the other one to be fair, if you want your second choice, go see {gold:Lamp}!".to_string()]);
                 }
             }
-            Some(AwaitingChoice::Chest { item, weapon, .. }) => {
-                let mut log = None;
-                match up {
+            Some(AwaitingChoice::Chest { room, x, y, item, weapon }) => {
+                let log = match up {
                     'A' => {
-                        if let Some(w) = weapon {
-                            self.player.inventory.backpack.push(w.clone());
-                            log = Some(format!("Picked up {}.", w.name));
-                        } else if let Some(cons) = item {
-                            if self.player.inventory.consumables.len() < 10 {
-                                self.player.inventory.consumables.push(cons.clone());
-                                log = Some(format!("Picked up {}.", cons.name));
-                            } else { log = Some("Slots full.".to_string()); }
-                        }
+                        if !self.has_room_for(&item, &weapon) { self.start_pack_full_dialogue(room, x, y, item, weapon); return; }
+                        self.take_chest_contents(item, weapon)
                     }
                     'B' => {
                         if let Some(cons) = item {
                             let before = self.player.hp;
                             self.player.hp = (self.player.hp + cons.heal).min(self.player.max_hp);
                             let healed = self.player.hp - before;
-                            log = Some(format!("Used {} ({}).", cons.name, Self::fmt_hp_delta(healed)));
-                        } else { log = Some("Cannot use that.".to_string()); }
+                            Some(format!("Used {} ({}).", cons.name, Self::fmt_hp_delta(healed)))
+                        } else { Some("Cannot use that.".to_string()) }
                     }
-                    'C' => { log = Some("Left chest.".to_string()); }
+                    'C' => Some("Left chest.".to_string()),
                     _ => return,
-                }
+                };
                 if let Some(m) = log { self.push_log(m); }
                 self.dialogue = None;
                 self.state = GameState::Playing;
             }
-            None => {}
+            Some(AwaitingChoice::PackFull { room, x, y, item, weapon, slots }) => {
+                let slot = AwaitingChoice::DROP_KEYS.iter().position(|&k| k as char == up).filter(|&i| i < slots.len());
+                if up == AwaitingChoice::LEAVE_KEY {
+                    let name = Self::contents_name(&item, &weapon);
+                    self.refill_chest(room, x, y, item, weapon);
+                    self.push_log(format!("You leave the {} in the chest.", name));
+                } else if let Some(i) = slot {
+                    let Some(dropped) = self.player.inventory.remove_slot(i) else { return };
+                    let label = dropped.label();
+                    self.drop_here(dropped);
+                    if !self.has_room_for(&item, &weapon) {
+                        self.push_log(format!("Dropped {}, but it's still too heavy.", label));
+                        self.start_pack_full_dialogue(room, x, y, item, weapon);
+                        return;
+                    }
+                    self.push_log(format!("Dropped {}.", label));
+                    if let Some(m) = self.take_chest_contents(item, weapon) { self.push_log(m); }
+                } else {
+                    return;
+                }
+                self.dialogue = None;
+                self.state = GameState::Playing;
+            }
+            _ => {}
+        }
+    }
+
+    fn contents_name(item: &Option<Consumable>, weapon: &Option<Equipment>) -> String {
+        if let Some(w) = weapon { w.name.clone() } else if let Some(c) = item { c.name.clone() } else { "nothing".to_string() }
+    }
+
+    fn has_room_for(&self, item: &Option<Consumable>, weapon: &Option<Equipment>) -> bool {
+        let inv = &self.player.inventory;
+        match (weapon, item) {
+            (Some(w), _) => inv.has_room_for_equipment(w),
+            (None, Some(c)) => inv.has_room_for_consumable(c),
+            (None, None) => true,
+        }
+    }
+
+    /// Move a chest's contents into the pack; callers check for room first.
+    fn take_chest_contents(&mut self, item: Option<Consumable>, weapon: Option<Equipment>) -> Option<String> {
+        let inv = &mut self.player.inventory;
+        if let Some(w) = weapon {
+            let name = w.name.clone();
+            inv.add_equipment(w).ok()?;
+            Some(format!("Picked up {}.", name))
+        } else if let Some(c) = item {
+            let name = c.name.clone();
+            inv.add_consumable(c).ok()?;
+            Some(format!("Picked up {}.", name))
+        } else { None }
+    }
+
+    fn start_pack_full_dialogue(&mut self, room: usize, x: i32, y: i32, item: Option<Consumable>, weapon: Option<Equipment>) {
+        let inv = &self.player.inventory;
+        let reason = if inv.free_slots() == 0 { "Your pack is full!" } else { "That's too heavy to carry!" };
+        let pages = vec![format!(
+            "{}\nDrop something to make room for {{gold:{}}}, or leave it in the chest.",
+            reason,
+            Self::contents_name(&item, &weapon)
+        )];
+        let slots = inv.slot_labels();
+        self.dialogue = Some(
+            DialogueSession::aside(Speaker::Chest, "Treasure Chest", pages)
+                .with_choice(AwaitingChoice::PackFull { room, x, y, item, weapon, slots }),
+        );
+        self.state = GameState::Dialogue;
+    }
+
+    /// Put contents back and close the chest so it can be opened again later.
+    fn refill_chest(&mut self, room: usize, x: i32, y: i32, item: Option<Consumable>, weapon: Option<Equipment>) {
+        let level = &mut self.levels[room];
+        if let Some(i) = level.chest_grid.get(x, y) {
+            let chest = &mut level.chests[i];
+            chest.opened = false;
+            chest.item = item;
+            chest.weapon = weapon;
+            level.map.set(x as usize, y as usize, Tile::Chest);
+        }
+    }
+
+    /// Index into the current level's objects of something usable next to the player.
+    fn object_near_player(&self) -> Option<usize> {
+        let (px, py) = (self.player.x, self.player.y);
+        self.current_level().objects.iter().position(|o| {
+            !matches!(o.kind, ObjectKind::Trap { .. })
+                && (o.x - px).abs() <= 1 && (o.y - py).abs() <= 1
+                && (o.x, o.y) != (px, py)
+        })
+    }
+
+    fn use_object(&mut self, i: usize) {
+        let room = self.current;
+        let obj = self.levels[room].objects[i].clone();
+        let (x, y) = (obj.x as usize, obj.y as usize);
+        match obj.kind {
+            ObjectKind::LockedDoor { key } => {
+                if self.levels[room].map.get(x, y) != Tile::LockedDoor { return; }
+                match self.player.inventory.keys.iter().position(|k| *k == key) {
+                    Some(k) => {
+                        self.player.inventory.keys.remove(k);
+                        self.levels[room].map.set(x, y, Tile::Floor);
+                        self.push_log(format!("The {} turns. The door swings open.", key));
+                    }
+                    None => self.push_log(format!("It's locked. You need the {}.", key)),
+                }
+            }
+            ObjectKind::Lever { gate, pulled } => {
+                if pulled {
+                    self.push_log("The lever won't budge any further.");
+                } else {
+                    self.levels[room].objects[i].kind = ObjectKind::Lever { gate, pulled: true };
+                    self.levels[room].map.set(gate.0 as usize, gate.1 as usize, Tile::Floor);
+                    self.push_log("You pull the lever. Somewhere, a gate grinds open.");
+                }
+            }
+            ObjectKind::CrackedWall => {
+                if self.levels[room].map.get(x, y) != Tile::CrackedWall { return; }
+                if self.player.inventory.equipped(GearSlot::Sword).is_some() {
+                    self.levels[room].map.set(x, y, Tile::Floor);
+                    self.push_log("You hack at the cracks until the wall gives way.");
+                    // Stone is hard on a blade.
+                    for _ in 0..3 { self.wear_gear(&[GearSlot::Sword]); }
+                } else {
+                    self.push_log("The wall is badly cracked. A blade might break through.");
+                }
+            }
+            ObjectKind::Fountain { charges } => {
+                if charges == 0 {
+                    self.push_log("The fountain has run dry.");
+                } else if self.player.hp >= self.player.max_hp {
+                    self.push_log("You drink from the fountain. You already feel fine.");
+                } else {
+                    let healed = self.player.max_hp - self.player.hp;
+                    self.player.hp = self.player.max_hp;
+                    self.levels[room].objects[i].kind = ObjectKind::Fountain { charges: charges - 1 };
+                    let left = if charges == 1 { " It trickles to a stop." } else { "" };
+                    self.push_log(format!("The cool water restores you ({}).{}", Self::fmt_hp_delta(healed), left));
+                }
+            }
+            ObjectKind::Sign { text } => {
+                self.dialogue = Some(DialogueSession::aside(Speaker::Sign, "Signpost", vec![text]));
+                self.state = GameState::Dialogue;
+            }
+            ObjectKind::Trap { .. } => {}
         }
     }
 
+    fn spring_trap_here(&mut self) {
+        let (px, py) = (self.player.x, self.player.y);
+        let level = &mut self.levels[self.current];
+        let Some(obj) = level.objects.iter_mut().find(|o| o.x == px && o.y == py) else { return };
+        let ObjectKind::Trap { damage, sprung: false } = obj.kind else { return };
+        obj.kind = ObjectKind::Trap { damage, sprung: true };
+        self.player.hp -= damage;
+        self.push_log(format!("Click! Spikes shoot up from the floor ({}).", Self::fmt_hp_delta(-damage)));
+    }
+
     fn door_near_player(&self) -> Option<(i32, i32)> {
         let px = self.player.x;
         let py = self.player.y;
@@ -1009,62 +1957,173 @@ impl World {
         None
     }
 
+    /// Inactivity limit for the current difficulty, in ticks. None when the timer is disabled.
+    pub(crate) fn battle_timer_ticks(&self) -> Option<u32> {
+        self.settings.battle_timer_secs.map(|s| s * 1000 / Self::TICK_MS)
+    }
+
+    /// Ticks left before hesitating hands the enemy initiative, if the timer is running at all.
+    pub(crate) fn battle_time_left(&self) -> Option<u32> {
+        let limit = self.battle_timer_ticks()?;
+        let bs = self.battle.as_ref()?;
+        if bs.penalty_mode { return Some(0); }
+        Some(limit.saturating_sub(bs.idle_ticks))
+    }
+
+    /// Whether P does anything in a fight: there's a timer, and this difficulty lets it stop.
+    pub(crate) fn can_pause_battle_timer(&self) -> bool {
+        self.battle_timer_ticks().is_some() && self.settings.allow_timer_pause
+    }
+
+    fn toggle_battle_timer(&mut self) {
+        if self.battle_timer_ticks().is_none() { self.push_log("The battle timer is off."); return; }
+        if !self.can_pause_battle_timer() { self.push_log("The battle timer cannot be paused on this difficulty."); return; }
+        if let Some(bs) = &mut self.battle {
+            bs.timer_paused = !bs.timer_paused;
+            let msg = if bs.timer_paused { "Battle timer paused." } else { "Battle timer resumed." };
+            self.push_log(msg);
+        }
+    }
+
+    fn tick_battle_timer(&mut self) {
+        let Some(limit) = self.battle_timer_ticks() else { return };
+        // A timer shorter than the warning window warns on the first idle tick instead.
+        let warn_at = limit.saturating_sub(Self::TIMER_WARN_SECS * 1000 / Self::TICK_MS).max(1);
+        let mut log = None;
+
+        if let Some(bs) = &mut self.battle {
+            if bs.penalty_mode || bs.timer_paused { return; }
+            bs.idle_ticks += 1;
+            if bs.idle_ticks >= limit {
+                bs.penalty_mode = true;
+                log = Some(format!("You hesitated! {} seizes the initiative.", bs.enemy_name));
+            } else if bs.idle_ticks == warn_at {
+                let secs = ((limit - warn_at) * Self::TICK_MS).div_ceil(1000);
+                log = Some(format!("{} second{} left to act!", secs, if secs == 1 { "" } else { "s" }));
+            }
+        }
+        if let Some(m) = log { self.push_log(m); }
+    }
+
+    /// Advance the world by one fixed game tick (called by the loop every 60ms). Returns whether
+    /// anything on screen changed, so a frontend can skip redrawing a frame that looks the same.
+    pub fn tick(&mut self) -> bool {
+        self.ticks += 1;
+        let mut changed = false;
+        if let Some(bs) = &mut self.battle {
+            for fx in [&mut bs.enemy_fx, &mut bs.player_fx] {
+                if let Some(f) = fx {
+                    f.ticks_left = f.ticks_left.saturating_sub(1);
+                    if f.ticks_left == 0 { *fx = None; }
+                    changed = true;
+                }
+            }
+        }
+        if self.state == GameState::Battle {
+            let idle = self.battle.as_ref().map(|bs| bs.idle_ticks);
+            self.tick_battle_timer();
+            changed |= idle != self.battle.as_ref().map(|bs| bs.idle_ticks);
+        }
+        if self.clock_running() {
+            self.clock.advance();
+            let buffs = self.player.buffs.len();
+            self.player.purge_expired_buffs(self.clock.now());
+            changed |= buffs != self.player.buffs.len();
+        }
+        if let Some(d) = &mut self.dialogue {
+            let revealed = (d.revealed + Self::TYPEWRITER_CHARS_PER_TICK).min(d.page_len());
+            changed |= revealed != d.revealed;
+            d.revealed = revealed;
+        }
+        changed
+    }
+
+    /// Whether the game clock is running: out exploring or in a fight, with no menu open.
+    pub(crate) fn clock_running(&self) -> bool {
+        matches!(self.state, GameState::Playing | GameState::Battle)
+            && !(self.inventory_open || self.stats_open || self.overview_open)
+    }
+
+    /// Apply one player input to the current `GameState`. Returns false when the game should quit.
     pub fn apply_action(&mut self, action: Action) -> bool {
-        self.player.purge_expired_buffs();
         match self.state {
             GameState::Title => match action { Action::Confirm => self.state = GameState::Intro, Action::Quit => return false, _ => {} },
             GameState::Intro => match action { Action::Confirm => self.state = GameState::Playing, Action::Quit => return false, _ => {} },
             GameState::Dialogue => match action { Action::Confirm => self.dialogue_continue(), Action::Choice(c) => self.dialogue_choice(c), Action::Quit => return false, _ => {} },
-            GameState::Fin => match action { Action::Quit => return false, _ => {} },
+            GameState::Fin => if let Action::Quit = action { return false },
+            GameState::Shop => match action {
+                Action::InventoryUp => { let len = self.shop_entries().len(); self.shop.move_cursor(-1, len) }
+                Action::InventoryDown => { let len = self.shop_entries().len(); self.shop.move_cursor(1, len) }
+                Action::ToggleInvTab => self.shop.toggle_tab(),
+                Action::Confirm => self.shop_trade(),
+                Action::LeaveShop => self.leave_shop(),
+                Action::Quit => return false,
+                _ => {}
+            },
             
             GameState::Battle => match action {
-                Action::BattleOption(opt, penalty) => {
+                Action::BattleOption(opt) => {
                     if opt == 1 || opt == 3 {
-                        if opt == 1 && !penalty && self.battle.as_ref().map_or(false, |b| b.enemy_speed < self.player.speed()) {
-                            if let Some(bs) = &mut self.battle { bs.player_initiated = true; }
+                        let speed = self.player.speed();
+                        if opt == 1 && let Some(bs) = &mut self.battle && !bs.penalty_mode && bs.enemy_speed < speed {
+                            bs.player_initiated = true;
                         }
-                        self.apply_battle_turn(opt, penalty);
+                        self.apply_battle_turn(opt);
                     } else if opt == 2 {
+                        if let Some(bs) = &mut self.battle { bs.idle_ticks = 0; }
                         self.inventory_open = true;
                         self.player.inventory.tab = InvTab::Consumables;
                     }
                 }
-                Action::UseConsumable => {
-                    if self.inventory_open {
-                        self.use_or_unequip_or_equip();
-                        self.inventory_open = false;
-                        self.apply_battle_turn(2, false);
-                    }
+                Action::ToggleBattleTimer => self.toggle_battle_timer(),
+                Action::UseConsumable if self.inventory_open => {
+                    let acted = self.use_or_unequip_or_equip();
+                    self.inventory_open = false;
+                    // Fumbling through the pack for nothing doesn't give the enemy a free swing.
+                    if acted { self.apply_battle_turn(2); }
                 }
                 Action::ToggleInventory | Action::Quit => {
                     if self.inventory_open { self.inventory_open = false; }
                     else if matches!(action, Action::Quit) { return false; }
                 }
-                Action::InventoryUp => { if self.inventory_open { self.player.inventory.move_cursor(-1); } }
-                Action::InventoryDown => { if self.inventory_open { self.player.inventory.move_cursor(1); } }
+                Action::InventoryUp if self.inventory_open => self.player.inventory.move_cursor(-1),
+                Action::InventoryDown if self.inventory_open => self.player.inventory.move_cursor(1),
                 _ => {}
             }
 
             GameState::Playing => match action {
                 Action::ToggleStats => self.toggle_stats(),
                 Action::ToggleInventory => self.toggle_inventory(),
-                Action::ToggleInvTab => if self.inventory_open { self.toggle_inventory_tab() },
-                Action::InventoryUp => if self.inventory_open { self.player.inventory.move_cursor(-1) },
-                Action::InventoryDown => if self.inventory_open { self.player.inventory.move_cursor(1) },
-                Action::UseConsumable => if self.inventory_open { self.use_or_unequip_or_equip() },
+                Action::ToggleOverview => self.toggle_overview(),
+                Action::ToggleInvTab if self.inventory_open => self.toggle_inventory_tab(),
+                Action::InventoryUp if self.inventory_open => self.player.inventory.move_cursor(-1),
+                Action::InventoryDown if self.inventory_open => self.player.inventory.move_cursor(1),
+                Action::UseConsumable if self.inventory_open => {
+                    if self.player.inventory.tab == InvTab::Crafting { self.player.inventory.toggle_craft_mark() }
+                    else { self.use_or_unequip_or_equip(); }
+                }
+                Action::Craft if self.inventory_open && self.player.inventory.tab == InvTab::Crafting => self.craft(),
+                Action::InspectItem if self.inventory_open => self.toggle_inspect(),
+                Action::DropItem if self.inventory_open => self.drop_selected(),
+                Action::CycleSort if self.inventory_open => self.cycle_inventory_sort(),
+                Action::CycleFilter if self.inventory_open => self.cycle_inventory_filter(),
                 Action::Interact => {
                     if let Some(npc) = self.npc_near_player().cloned() {
                         self.start_dialogue_for(&npc);
-                        if self.noor_done && npc.id == NpcId::Lamp && !self.lamp_done {
-                            let ms = self.player.inventory.sword.is_none();
-                            let msh = self.player.inventory.shield.is_none();
-                            if ms { self.player.equip_sword(Equipment { name: "Basic Sword".to_string(), slot: Slot::Sword, hp_bonus: 0, atk_bonus: 3, def_bonus: 0, speed_bonus: 3 }); self.lamp_done = true; }
-                            else if msh { self.player.equip_shield(Equipment { name: "Basic Shield".to_string(), slot: Slot::Shield, hp_bonus: 0, atk_bonus: 0, def_bonus: 3, speed_bonus: -2 }); self.lamp_done = true; }
+                        if self.story.noor_done && npc.id == NpcId::Lamp && !self.story.lamp_done {
+                            let ms = self.player.inventory.equipped(GearSlot::Sword).is_none();
+                            let msh = self.player.inventory.equipped(GearSlot::Shield).is_none();
+                            if ms { self.player.equip(Self::basic_sword()); self.story.lamp_done = true; }
+                            else if msh { self.player.equip(Self::basic_shield()); self.story.lamp_done = true; }
                         }
+                    } else if self.pickup_at(self.player.x, self.player.y).is_some() {
+                        self.pick_up_here();
                     } else {
-                        if let Some(_) = self.door_near_player() {
-                             if self.player.inventory.sword.is_some() && self.player.inventory.shield.is_some() { self.toggle_room(); } 
+                        if self.door_near_player().is_some() {
+                             if self.player.inventory.equipped(GearSlot::Sword).is_some() && self.player.inventory.equipped(GearSlot::Shield).is_some() { self.toggle_room(); } 
                              else { self.push_log("Talk to the mayor and come back"); }
+                        } else if let Some(i) = self.object_near_player() {
+                             self.use_object(i);
                         } else {
                              self.open_chest_if_on_one();
                              if self.state != GameState::Dialogue { self.push_log("No one nearby."); }
@@ -1076,10 +2135,29 @@ impl World {
                     let nx = self.player.x + dx;
                     let ny = self.player.y + dy;
                     if self.npc_at(self.current, nx, ny).is_some() { return true; }
-                    let map_snap = self.current_map().clone();
-                    self.player.try_move(dx, dy, &map_snap);
+                    // Borrow the level's fields directly, so the player can move while they're held.
+                    let level = &self.levels[self.current];
+                    let before = level.map.get(self.player.x as usize, self.player.y as usize);
+                    let step = self.player.try_move(dx, dy, &level.map, |x, y| level.npc_grid.get(x, y).is_some());
                     let newp = (self.player.x, self.player.y);
-                    if self.current_map().get(newp.0 as usize, newp.1 as usize) == Tile::Chest { self.open_chest_if_on_one(); }
+                    self.reveal_around_player();
+                    let tile = self.current_map().get(newp.0 as usize, newp.1 as usize);
+                    match step {
+                        Step::Blocked | Step::Moved => {}
+                        Step::Wading => if before != Tile::Water { self.push_log("You wade in; the water drags at your legs."); },
+                        Step::Slid(_) => self.push_log("You slide across the ice."),
+                    }
+                    if tile == Tile::Dark && before != Tile::Dark { self.push_log("It's dark here. You can barely see."); }
+                    if tile == Tile::Chest { self.open_chest_if_on_one(); }
+                    else if tile == Tile::Trap { self.spring_trap_here(); }
+                    else if tile == Tile::Lava && matches!(step, Step::Moved | Step::Slid(_)) {
+                        self.player.hp -= Self::LAVA_STEP_DAMAGE;
+                        self.push_log(format!("The lava burns ({}).", Self::fmt_hp_delta(-Self::LAVA_STEP_DAMAGE)));
+                    }
+                    else if let Some(p) = self.pickup_at(newp.0, newp.1) {
+                        let msg = format!("{} lies here. Press E to pick it up.", p.item.label());
+                        self.push_log(msg);
+                    }
                 }
                 Action::Quit => return false,
                 _ => {}
@@ -0,0 +1,41 @@
+//! Sunny Days as a library: the game world, its maps, and the terminal renderer.
+//!
+//! The `Sunny-Days` binary is a thin frontend over this crate: it parses the command line into
+//! a [`Config`] and hands it to [`run`]. Tools that want the game without a terminal (map
+//! viewers, bots, balance sims) drive a [`World`] directly:
+//!
+//! ```
+//! use sunny_days::{Action, GameState, World};
+//!
+//! let mut world = World::new(42, 80, 45);
+//! world.apply_action(Action::Confirm); // title
+//! world.apply_action(Action::Confirm); // intro
+//! assert_eq!(world.state, GameState::Playing);
+//!
+//! world.apply_action(Action::Move(1, 0));
+//! ```
+//!
+//! Everything the game does happens in [`World::apply_action`]; [`World::tick`] advances
+//! timers and animations, and [`render`] draws a world into any ratatui `Frame`. Everything
+//! else is internal to the crate and free to change.
+
+#![warn(missing_docs)]
+
+pub(crate) mod audio;
+pub(crate) mod config;
+pub(crate) mod engine;
+pub(crate) mod map;
+pub(crate) mod tui;
+
+#[cfg(test)]
+mod tests;
+
+pub use config::{Config, USAGE};
+pub use engine::action::Action;
+pub use engine::game_loop::run;
+pub use engine::world::{GameState, Npc, NpcId, StoryFlags, World};
+pub use engine::{balance, bot};
+pub use map::generator::generate_rooms_and_corridors;
+pub use map::{tile::Tile, Map};
+pub use tui::renderer::render;
+pub use tui::theme::{Theme, ThemeName};
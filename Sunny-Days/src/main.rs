@@ -1,10 +1,19 @@
-mod engine;
-mod map;
-mod tui;
-mod audio;
-
-use engine::game_loop::run;
+use sunny_days::{run, Config, USAGE};
 
 fn main() -> std::io::Result<()> {
-    run()
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{USAGE}");
+        return Ok(());
+    }
+
+    let config = match Config::from_args(args) {
+        Ok(c) => c,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(2);
+        }
+    };
+
+    run(config)
 }
@@ -0,0 +1,73 @@
+//! What the player can see from where they stand.
+//!
+//! Sight is a box (terminal cells are taller than wide, so it reaches further sideways) cut
+//! down by line of sight: a straight line to a tile must not pass through anything that
+//! blocks sight. Walls and grass are themselves visible, just not what's behind them.
+
+use crate::map::{tile::Tile, Map};
+
+/// How far you see in the dark, and how close a dark tile must be before you can see it.
+pub const DARK_SIGHT: (i32, i32) = (4, 2);
+
+pub struct Fov {
+    origin: (i32, i32),
+    radius: (i32, i32),
+    visible: Vec<bool>,
+}
+
+impl Fov {
+    pub fn is_visible(&self, x: i32, y: i32) -> bool {
+        let (dx, dy) = (x - self.origin.0, y - self.origin.1);
+        if dx.abs() > self.radius.0 || dy.abs() > self.radius.1 { return false; }
+        self.visible[self.index(dx, dy)]
+    }
+
+    /// Every visible tile, in no particular order.
+    pub fn tiles(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        let (rx, ry) = self.radius;
+        (-ry..=ry)
+            .flat_map(move |dy| (-rx..=rx).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| self.visible[self.index(dx, dy)])
+            .map(|(dx, dy)| (self.origin.0 + dx, self.origin.1 + dy))
+    }
+
+    fn index(&self, dx: i32, dy: i32) -> usize {
+        ((dy + self.radius.1) * (2 * self.radius.0 + 1) + dx + self.radius.0) as usize
+    }
+}
+
+/// Field of view from `origin` out to `radius`; standing in the dark shrinks it to `DARK_SIGHT`.
+pub fn compute(map: &Map, origin: (i32, i32), radius: (i32, i32)) -> Fov {
+    let in_dark = map.in_bounds(origin.0, origin.1) && map.get(origin.0 as usize, origin.1 as usize) == Tile::Dark;
+    let radius = if in_dark { DARK_SIGHT } else { radius };
+    let mut fov = Fov { origin, radius, visible: vec![false; ((2 * radius.0 + 1) * (2 * radius.1 + 1)) as usize] };
+
+    for dy in -radius.1..=radius.1 {
+        for dx in -radius.0..=radius.0 {
+            let (x, y) = (origin.0 + dx, origin.1 + dy);
+            if !map.in_bounds(x, y) { continue; }
+            let far = dx.abs() > DARK_SIGHT.0 || dy.abs() > DARK_SIGHT.1;
+            if far && map.get(x as usize, y as usize) == Tile::Dark { continue; }
+            if clear_line(map, origin, (x, y)) {
+                let i = fov.index(dx, dy);
+                fov.visible[i] = true;
+            }
+        }
+    }
+    fov
+}
+
+/// Bresenham from `a` to `b`; true if nothing strictly between them blocks sight.
+fn clear_line(map: &Map, a: (i32, i32), b: (i32, i32)) -> bool {
+    let (dx, dy) = ((b.0 - a.0).abs(), -(b.1 - a.1).abs());
+    let (sx, sy) = ((b.0 - a.0).signum(), (b.1 - a.1).signum());
+    let (mut x, mut y) = a;
+    let mut err = dx + dy;
+    loop {
+        let e2 = 2 * err;
+        if e2 >= dy { err += dy; x += sx; }
+        if e2 <= dx { err += dx; y += sy; }
+        if (x, y) == b { return true; }
+        if map.blocks_sight(x as usize, y as usize) { return false; }
+    }
+}
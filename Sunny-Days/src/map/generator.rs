@@ -80,9 +80,89 @@ pub fn generate_rooms_and_corridors(width: usize, height: usize, seed: u64) -> M
         rooms.push(new_room);
     }
 
+    scatter_terrain(&mut map, &rooms, &mut rng);
     map
 }
 
+/// Give some rooms a feature (pond, meadow, lava pool, ice rink or darkness) and some
+/// corridors a stretch of grass, water or dark. Terrain only ever replaces floor and is all
+/// walkable, so the level stays connected; lava keeps a floor margin so it can be walked around.
+fn scatter_terrain(map: &mut Map, rooms: &[Rect], rng: &mut StdRng) {
+    // The first room is where the player usually starts; leave it plain.
+    for r in rooms.iter().skip(1) {
+        if !rng.gen_bool(0.6) { continue; }
+        match rng.gen_range(0..5) {
+            0 => paint_ellipse(map, inset(*r, 1), Tile::Water),
+            1 => paint_blobs(map, inset(*r, 1), Tile::Grass, 3, rng),
+            2 => paint_ellipse(map, inset(*r, 2), Tile::Lava),
+            3 => paint_rect(map, inset(*r, 1), Tile::Ice),
+            _ => paint_rect(map, *r, Tile::Dark),
+        }
+    }
+
+    let in_room = |x: usize, y: usize| rooms.iter().any(|r| x >= r.x1 && x <= r.x2 && y >= r.y1 && y <= r.y2);
+    let mut corridor = Vec::new();
+    for y in 0..map.height {
+        for x in 0..map.width {
+            if map.get(x, y) == Tile::Floor && !in_room(x, y) { corridor.push((x, y)); }
+        }
+    }
+    if corridor.is_empty() { return; }
+    for _ in 0..3 {
+        let start = corridor[rng.gen_range(0..corridor.len())];
+        let tile = [Tile::Grass, Tile::Water, Tile::Dark][rng.gen_range(0..3)];
+        spread(map, start, tile, 8, &|x, y| !in_room(x, y));
+    }
+}
+
+fn inset(r: Rect, by: usize) -> Rect {
+    Rect { x1: r.x1 + by, y1: r.y1 + by, x2: r.x2.saturating_sub(by), y2: r.y2.saturating_sub(by) }
+}
+
+fn paint_rect(map: &mut Map, r: Rect, tile: Tile) {
+    for y in r.y1..=r.y2 {
+        for x in r.x1..=r.x2 {
+            if map.get(x, y) == Tile::Floor { map.set(x, y, tile); }
+        }
+    }
+}
+
+fn paint_ellipse(map: &mut Map, r: Rect, tile: Tile) {
+    if r.x2 <= r.x1 || r.y2 <= r.y1 { return; }
+    let (cx, cy) = ((r.x1 + r.x2) as f32 / 2.0, (r.y1 + r.y2) as f32 / 2.0);
+    let (rx, ry) = ((r.x2 - r.x1) as f32 / 2.0 + 0.5, (r.y2 - r.y1) as f32 / 2.0 + 0.5);
+    for y in r.y1..=r.y2 {
+        for x in r.x1..=r.x2 {
+            let (nx, ny) = ((x as f32 - cx) / rx, (y as f32 - cy) / ry);
+            if nx * nx + ny * ny <= 1.0 && map.get(x, y) == Tile::Floor { map.set(x, y, tile); }
+        }
+    }
+}
+
+fn paint_blobs(map: &mut Map, r: Rect, tile: Tile, count: usize, rng: &mut StdRng) {
+    if r.x2 < r.x1 || r.y2 < r.y1 { return; }
+    let inside = |x: usize, y: usize| x >= r.x1 && x <= r.x2 && y >= r.y1 && y <= r.y2;
+    for _ in 0..count {
+        let start = (rng.gen_range(r.x1..=r.x2), rng.gen_range(r.y1..=r.y2));
+        spread(map, start, tile, 10, &inside);
+    }
+}
+
+/// Flood `tile` over up to `limit` floor tiles connected to `start`, staying where `allowed`.
+fn spread(map: &mut Map, start: (usize, usize), tile: Tile, limit: usize, allowed: &dyn Fn(usize, usize) -> bool) {
+    let mut frontier = vec![start];
+    let mut painted = 0;
+    while let Some((x, y)) = frontier.pop() {
+        if painted >= limit { break; }
+        if map.get(x, y) != Tile::Floor || !allowed(x, y) { continue; }
+        map.set(x, y, tile);
+        painted += 1;
+        for (nx, ny) in [(x + 1, y), (x.wrapping_sub(1), y), (x, y + 1), (x, y.wrapping_sub(1))] {
+            if nx < map.width && ny < map.height { frontier.push((nx, ny)); }
+        }
+    }
+}
+
 fn carve_room(map: &mut Map, r: Rect) {
     for y in r.y1..=r.y2 {
         for x in r.x1..=r.x2 {
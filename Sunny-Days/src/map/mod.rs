@@ -1,16 +1,23 @@
-pub mod generator;
-pub mod tile;
+pub(crate) mod fov;
+pub(crate) mod generator;
+pub(crate) mod occupancy;
+pub(crate) mod tile;
 
 use tile::Tile;
 
+/// A level's tiles, row by row.
 #[derive(Clone)]
 pub struct Map {
+    /// Columns.
     pub width: usize,
+    /// Rows.
     pub height: usize,
+    /// `width * height` tiles; `idx` turns a position into an index.
     pub tiles: Vec<Tile>,
 }
 
 impl Map {
+    /// A map filled with one tile.
     pub fn new(width: usize, height: usize, fill: Tile) -> Self {
         Self {
             width,
@@ -19,23 +26,28 @@ impl Map {
         }
     }
 
+    /// Index into `tiles` of a position.
     pub fn idx(&self, x: usize, y: usize) -> usize {
         y * self.width + x
     }
 
+    /// The tile at a position; panics outside the map.
     pub fn get(&self, x: usize, y: usize) -> Tile {
         self.tiles[self.idx(x, y)]
     }
 
+    /// Change the tile at a position.
     pub fn set(&mut self, x: usize, y: usize, t: Tile) {
         let i = self.idx(x, y);
         self.tiles[i] = t;
     }
 
+    /// Whether a position, possibly negative, lies on the map.
     pub fn in_bounds(&self, x: i32, y: i32) -> bool {
         x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
     }
 
+    /// The top-left-most floor tile, scanning row by row.
     pub fn find_first_floor(&self) -> Option<(usize, usize)> {
         for y in 0..self.height {
             for x in 0..self.width {
@@ -47,8 +59,37 @@ impl Map {
         None
     }
 
+    /// Tiles the player can step onto.
     pub fn is_walkable(&self, x: usize, y: usize) -> bool {
         // Door is no longer walkable; it acts like a character/NPC.
-        matches!(self.get(x, y), Tile::Floor | Tile::Chest)
+        matches!(
+            self.get(x, y),
+            Tile::Floor | Tile::Chest | Tile::Trap | Tile::Water | Tile::Grass | Tile::Lava | Tile::Ice | Tile::Dark
+        )
+    }
+
+    /// Every tile a walk from `from` can get to, indexed like `tiles`. `from` itself always counts.
+    pub fn reachable_from(&self, from: (i32, i32)) -> Vec<bool> {
+        let mut seen = vec![false; self.tiles.len()];
+        if !self.in_bounds(from.0, from.1) { return seen; }
+        seen[self.idx(from.0 as usize, from.1 as usize)] = true;
+        let mut queue = std::collections::VecDeque::from([from]);
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if !self.in_bounds(nx, ny) || !self.is_walkable(nx as usize, ny as usize) { continue; }
+                let i = self.idx(nx as usize, ny as usize);
+                if !seen[i] {
+                    seen[i] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        seen
+    }
+
+    /// Tiles you can see but not see past.
+    pub fn blocks_sight(&self, x: usize, y: usize) -> bool {
+        matches!(self.get(x, y), Tile::Wall | Tile::CrackedWall | Tile::LockedDoor | Tile::Grass)
     }
 }
\ No newline at end of file
@@ -0,0 +1,32 @@
+/// One slot per map tile holding the index (into some list kept elsewhere) of what stands there,
+/// so asking "who's on (x, y)?" costs the same however long the list grows.
+#[derive(Debug, Clone)]
+pub struct Occupancy {
+    width: usize,
+    height: usize,
+    cells: Vec<Option<u32>>,
+}
+
+impl Occupancy {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self { width, height, cells: vec![None; width * height] }
+    }
+
+    fn slot(&self, x: i32, y: i32) -> Option<usize> {
+        let inside = x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height;
+        inside.then(|| y as usize * self.width + x as usize)
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> Option<usize> {
+        self.cells[self.slot(x, y)?].map(|i| i as usize)
+    }
+
+    /// Mark `(x, y)` as taken by entry `index`, or free it with None. Off-map positions are ignored.
+    pub fn set(&mut self, x: i32, y: i32, index: Option<usize>) {
+        if let Some(s) = self.slot(x, y) { self.cells[s] = index.map(|i| i as u32); }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(None);
+    }
+}
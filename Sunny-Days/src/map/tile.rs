@@ -1,7 +1,54 @@
+/// What a map cell holds.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Tile {
+    /// Blocks movement and sight.
     Wall,
+    /// Plain ground.
     Floor,
-    Door,  // single door used to toggle between Room 1 and Room 2
-    Chest, // treasure chest (walkable; triggers dialogue)
+    /// The single door between the first and second level.
+    Door,
+    /// A treasure chest: walkable, and opens a dialogue.
+    Chest,
+
+    // Interactive objects; their state lives in the level's `WorldObject`s.
+    /// Opens for the right key, then becomes floor.
+    LockedDoor,
+    /// Opened by a lever somewhere in the level.
+    Gate,
+    /// Opens the level's gates.
+    Lever,
+    /// A sword can break it down.
+    CrackedWall,
+    /// Heals, a few times.
+    Fountain,
+    /// Walkable; hidden until stepped on.
+    Trap,
+    /// Has something to say when read.
+    Sign,
+
+    // Terrain; all walkable, each with its own catch.
+    /// Takes two moves to wade into; slows you in a fight.
+    Water,
+    /// Tall enough to hide what's behind it.
+    Grass,
+    /// Burns on every step.
+    Lava,
+    /// You slide until you hit something or reach solid ground.
+    Ice,
+    /// Cuts your sight short, and can't be seen into from afar.
+    Dark,
+}
+
+impl Tile {
+    /// Name of the terrain you're standing on, for the battle screen and log.
+    pub fn terrain_name(self) -> Option<&'static str> {
+        match self {
+            Tile::Water => Some("water"),
+            Tile::Grass => Some("tall grass"),
+            Tile::Lava => Some("lava"),
+            Tile::Ice => Some("ice"),
+            Tile::Dark => Some("darkness"),
+            _ => None,
+        }
+    }
 }
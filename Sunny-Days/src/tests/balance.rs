@@ -0,0 +1,43 @@
+use super::*;
+use crate::engine::balance::{self, Build, Outcome, Policy, Sim};
+
+#[test]
+fn builds_wear_their_gear_and_wins() {
+    let axe = balance::named_gear("willow axe").expect("the axe is a known item");
+    let build = Build::new("test").with_wins(2).with_gear(axe);
+    let p = build.player_at(0, 0);
+    let bare = Build::new("bare").player_at(0, 0);
+    assert_eq!(p.attack(), bare.attack() + 6 + 15);
+    assert_eq!(p.max_hp, bare.max_hp + 5);
+    assert_eq!(p.hp, p.max_hp);
+
+    assert_eq!(balance::named_enemy("mayor"), Some(NpcId::MayorSol));
+    assert_eq!(balance::named_enemy("mayor-sol"), Some(NpcId::MayorSol));
+    assert_eq!(Policy::parse("buff+heal:40"), Some(Policy { heal_below: Some(40), buff_first: true }));
+    assert_eq!(Policy::parse("heal:0"), None);
+}
+
+#[test]
+fn the_same_dice_give_the_same_battle() {
+    let sim = Sim::new(SEED);
+    let build = Build::presets().into_iter().find(|b| b.name == "basic-shield").unwrap();
+    let outcomes: Vec<Outcome> = (0..20).map(|s| sim.fight(&build, NpcId::Mah, s)).collect();
+    assert_eq!(outcomes, (0..20).map(|s| sim.fight(&build, NpcId::Mah, s)).collect::<Vec<_>>());
+    assert!(outcomes.iter().any(|o| matches!(o, Outcome::Won { .. })));
+    assert!(outcomes.iter().any(|o| matches!(o, Outcome::Lost { .. })));
+}
+
+#[test]
+fn every_consumable_used_costs_a_turn() {
+    let sim = Sim::new(SEED);
+    let jerky = balance::named_consumable("sunny jerky").unwrap();
+    let plain = Build::new("plain").with_gear(balance::named_gear("basic shield").unwrap());
+    let fed = plain.clone().with_consumables(jerky, 5).with_policy(Policy { heal_below: Some(60), buff_first: false });
+
+    let a = sim.run(&plain, NpcId::Mah, 300, 7);
+    let b = sim.run(&fed, NpcId::Mah, 300, 7);
+    assert_eq!(a.battles, 300);
+    assert!(b.avg_turns() > a.avg_turns(), "{} vs {}", b.avg_turns(), a.avg_turns());
+    // Shab never stands a chance.
+    assert_eq!(sim.run(&plain, NpcId::Shab, 50, 0).win_rate(), 1.0);
+}
@@ -0,0 +1,22 @@
+use super::*;
+use crate::engine::bot;
+
+#[test]
+fn bot_finishes_the_story() {
+    // A plain run of seeds rather than picked ones; 35 once put the Mayor in front of the door.
+    for seed in 35..=SEED + 3 {
+        let run = bot::play(seed, 50_000);
+        assert!(run.finished, "seed {}: {:?}", seed, run.stuck);
+        assert!(run.flags.mayor_defeated && run.flags.dorosht_completed);
+        assert!(!run.died);
+    }
+}
+
+#[test]
+fn bot_says_where_it_gave_up() {
+    let run = bot::play(SEED, 20);
+    assert!(!run.finished && !run.died);
+    let why = run.stuck.expect("a run that didn't finish says why");
+    assert!(why.starts_with("ran out of actions trying to talk to MayorSol"), "{}", why);
+    assert_eq!(run.at.0, 0);
+}
@@ -0,0 +1,55 @@
+use super::*;
+use crate::engine::clock::GameClock;
+
+#[test]
+fn a_buff_wears_off_after_its_time_in_play() {
+    let mut w = new_world();
+    let base = w.player.attack();
+    let expires_at = w.clock.after(GameClock::secs(30));
+    w.player.add_temp_buff(2, 0, 0, expires_at);
+    assert_eq!(w.player.attack(), base + 2);
+
+    while w.clock.now() < expires_at - 1 { w.tick(); }
+    assert_eq!(w.player.attack(), base + 2, "still a tick to go");
+    w.tick();
+    assert_eq!(w.player.attack(), base);
+    assert!(w.player.buffs.is_empty());
+}
+
+#[test]
+fn the_clock_stops_behind_menus_and_dialogue() {
+    let mut w = new_world();
+    w.player.add_temp_buff(2, 0, 0, w.clock.after(10));
+
+    w.apply_action(Action::ToggleInventory);
+    for _ in 0..100 { w.tick(); }
+    assert_eq!(w.clock.now(), 0);
+    w.apply_action(Action::ToggleInventory);
+
+    talk_to(&mut w, NpcId::Noor);
+    for _ in 0..100 { w.tick(); }
+    assert_eq!(w.clock.now(), 0);
+    assert_eq!(w.player.buffs[0].remaining(w.clock.now()), 10);
+    finish_dialogue(&mut w);
+
+    for _ in 0..4 { w.tick(); }
+    assert_eq!(w.player.buffs[0].remaining(w.clock.now()), 6);
+    // Animations keep going regardless.
+    assert_eq!(w.ticks, 204);
+}
+
+#[test]
+fn ticks_say_when_the_screen_needs_redrawing() {
+    let mut w = new_world();
+    assert!(!w.tick(), "standing about, nothing moves");
+
+    let expires_at = w.clock.after(2);
+    w.player.add_temp_buff(2, 0, 0, expires_at);
+    assert!(!w.tick());
+    assert!(w.tick(), "the buff ran out and the stats changed");
+
+    talk_to(&mut w, NpcId::MayorSol);
+    assert!(w.tick(), "the dialogue types itself out");
+    w.apply_action(Action::Confirm);
+    assert!(!w.tick(), "the page is all there");
+}
@@ -0,0 +1,109 @@
+use super::*;
+use crate::engine::entity::Consumable;
+
+fn start_fight(w: &mut World, id: NpcId) {
+    talk_to(w, id);
+    finish_dialogue(w);
+    assert_eq!(w.state, GameState::Battle);
+    assert_eq!(w.battle.as_ref().map(|b| b.enemy_id), Some(id));
+}
+
+#[test]
+fn fleeing_leaves_the_enemy_standing() {
+    let mut w = new_world();
+    start_fight(&mut w, NpcId::Shab);
+
+    // Running is a coin flip; keep trying.
+    for _ in 0..200 {
+        if w.state != GameState::Battle { break; }
+        w.player.hp = w.player.max_hp;
+        w.apply_action(Action::BattleOption(3));
+    }
+    assert_eq!(w.state, GameState::Playing);
+    assert!(w.battle.is_none());
+    assert!(!w.flags().shab_defeated);
+    assert!(w.logs.iter().any(|l| l == "You fled the battle!"));
+
+    // He's still up for it.
+    start_fight(&mut w, NpcId::Shab);
+}
+
+#[test]
+fn you_cannot_run_from_a_fight_you_started() {
+    let mut w = new_world();
+    // Krad is slower than you, so swinging first makes it your fight. He also has more HP
+    // than one hit takes off.
+    start_fight(&mut w, NpcId::Krad);
+    assert!(w.player.speed() > w.battle.as_ref().unwrap().enemy_speed);
+    w.apply_action(Action::BattleOption(1));
+    assert!(w.battle.as_ref().is_some_and(|b| b.player_initiated));
+
+    for _ in 0..20 {
+        w.player.hp = w.player.max_hp;
+        w.logs.clear();
+        w.apply_action(Action::BattleOption(3));
+        assert_eq!(w.state, GameState::Battle);
+        assert!(w.logs.iter().any(|l| l == "You started this, finish it!"));
+    }
+}
+
+#[test]
+fn winning_heals_and_raises_stats() {
+    let mut w = new_world();
+    let (atk, def) = (w.player.base_attack, w.player.base_defense);
+    start_fight(&mut w, NpcId::Shab);
+    // Shab has no defence to speak of; one blow ends it.
+    w.player.hp = 5;
+    w.apply_action(Action::BattleOption(1));
+
+    assert!(w.flags().shab_defeated);
+    assert_eq!(w.player.hp, w.player.max_hp);
+    assert_eq!((w.player.base_attack, w.player.base_defense), (atk + 3, def + 3));
+    assert!(w.player.gold > 0, "Shab carries a bounty");
+}
+
+#[test]
+fn a_defeated_enemy_only_talks() {
+    let mut w = new_world();
+    defeat(&mut w, NpcId::Shab);
+    talk_to(&mut w, NpcId::Shab);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Playing);
+}
+
+#[test]
+fn a_battle_timer_shorter_than_the_warning_still_warns() {
+    let mut w = new_world();
+    w.settings.battle_timer_secs = Some(2);
+    start_fight(&mut w, NpcId::Shab);
+
+    w.tick();
+    assert!(w.logs.iter().any(|l| l == "2 seconds left to act!"));
+    for _ in 0..100 {
+        if w.battle.as_ref().is_some_and(|b| b.penalty_mode) { break; }
+        w.tick();
+    }
+    assert!(w.battle.as_ref().is_some_and(|b| b.penalty_mode));
+}
+
+#[test]
+fn using_nothing_in_battle_costs_no_turn() {
+    let mut w = new_world();
+    start_fight(&mut w, NpcId::Shab);
+    let whetstone = Consumable { name: "Whetstone".to_string(), heal: 0, atk_bonus: 0, def_bonus: 0, weight: 1, repair: 10 };
+    w.player.inventory.consumables.clear();
+    w.player.inventory.add_consumable(whetstone).unwrap();
+    assert!(w.repair_targets().is_empty());
+
+    let hp = w.player.hp;
+    w.apply_action(Action::BattleOption(2));
+    w.apply_action(Action::UseConsumable);
+    assert_eq!(w.logs.back().map(String::as_str), Some("Nothing needs mending; you keep the Whetstone."));
+
+    w.player.inventory.consumables.clear();
+    w.apply_action(Action::BattleOption(2));
+    w.apply_action(Action::UseConsumable);
+    assert_eq!(w.state, GameState::Battle);
+    assert_eq!(w.player.hp, hp);
+    assert_eq!(w.player.inventory.consumables.len(), 0);
+}
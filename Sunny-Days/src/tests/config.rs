@@ -0,0 +1,19 @@
+use crate::config::Config;
+
+use std::path::PathBuf;
+
+fn parse(args: &[&str]) -> Result<Config, String> {
+    Config::from_args(args.iter().map(|a| a.to_string()))
+}
+
+#[test]
+fn a_value_spelled_like_a_flag_is_still_a_value() {
+    let config = parse(&["--record", "--config"]).expect("--config here is the file to record to");
+    assert_eq!(config.record, Some(PathBuf::from("--config")));
+}
+
+#[test]
+fn an_explicit_config_file_must_exist() {
+    let err = parse(&["--config", "no-such-file.conf"]).unwrap_err();
+    assert!(err.starts_with("cannot read no-such-file.conf"), "{}", err);
+}
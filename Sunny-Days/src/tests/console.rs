@@ -0,0 +1,56 @@
+use super::*;
+use crate::engine::console::{self, Console};
+
+#[test]
+fn commands_change_the_world() {
+    let mut w = new_world();
+    let (x, y) = (w.player.x, w.player.y + 1);
+    let (x, y) = if w.current_map().is_walkable(x as usize, y as usize) { (x, y) } else { (x + 1, w.player.y) };
+    console::execute(&mut w, &format!("teleport {} {}", x, y)).unwrap();
+    assert_eq!((w.player.x, w.player.y), (x, y));
+
+    console::execute(&mut w, "set_flag mah_defeated").unwrap();
+    assert!(w.flags().mah_defeated);
+    console::execute(&mut w, "set_flag mah_defeated false").unwrap();
+    assert!(!w.flags().mah_defeated);
+
+    console::execute(&mut w, "give willow axe").unwrap();
+    assert!(w.player.inventory.backpack.iter().any(|e| e.name == "Willow Axe"));
+    console::execute(&mut w, "give Sunny Jerky").unwrap();
+    assert!(w.player.inventory.consumables.iter().any(|c| c.item.name == "Sunny Jerky"));
+
+    w.player.hp = 1;
+    console::execute(&mut w, "heal").unwrap();
+    assert_eq!(w.player.hp, w.player.max_hp);
+
+    console::execute(&mut w, "goto_room 2").unwrap();
+    assert_eq!(w.current, 1);
+
+    console::execute(&mut w, "start_battle mayor").unwrap();
+    assert_eq!(w.state, GameState::Battle);
+    assert_eq!(w.battle.as_ref().map(|b| b.enemy_id), Some(NpcId::MayorSol));
+}
+
+#[test]
+fn bad_commands_are_refused() {
+    let mut w = new_world();
+    let before = (w.player.x, w.player.y);
+    assert!(console::execute(&mut w, "teleport 0 0").is_err(), "the corner is wall");
+    assert_eq!((w.player.x, w.player.y), before);
+    assert!(console::execute(&mut w, "set_flag nope").is_err());
+    assert!(console::execute(&mut w, "give Excalibur").is_err());
+    assert!(console::execute(&mut w, "start_battle noor").is_err());
+    assert!(console::execute(&mut w, "goto_room 3").is_err());
+    assert!(console::execute(&mut w, "dance").is_err());
+
+    let mut c = Console::default();
+    c.input = "heal".to_string();
+    c.submit(&mut w);
+    c.input = "dance".to_string();
+    c.submit(&mut w);
+    assert_eq!(c.output.back().map(String::as_str), Some("error: unknown command: dance (try help)"));
+    c.recall(true);
+    assert_eq!(c.input, "dance");
+    c.recall(true);
+    assert_eq!(c.input, "heal");
+}
@@ -0,0 +1,14 @@
+use crate::engine::crafting;
+
+#[test]
+fn the_shipped_recipes_parse() {
+    let book = crafting::parse(include_str!("../../data/recipes.txt")).unwrap_or_else(|e| panic!("data/recipes.txt: {}", e));
+    assert!(!book.is_empty());
+    assert_eq!(crafting::recipes().len(), book.len());
+}
+
+#[test]
+fn a_bad_recipe_line_is_reported_by_number() {
+    let err = crafting::parse("# comment\nSunny Jerky + Fiery ale => Snack: heal=lots").unwrap_err();
+    assert!(err.starts_with("line 2:"), "{}", err);
+}
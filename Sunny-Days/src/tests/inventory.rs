@@ -0,0 +1,65 @@
+use super::*;
+use crate::engine::entity::{Consumable, Equipment, InvTab, SlotItem};
+use crate::engine::loot;
+
+#[test]
+fn dropping_a_stack_one_at_a_time_leaves_one_pile() {
+    let mut w = new_world();
+    let food = loot::consumables().remove(0);
+    w.player.inventory.consumables.clear();
+    for _ in 0..3 { w.player.inventory.add_consumable(food.clone()).unwrap(); }
+
+    w.apply_action(Action::ToggleInventory);
+    w.player.inventory.tab = InvTab::Consumables;
+    for _ in 0..3 { w.apply_action(Action::DropItem); }
+
+    let (x, y) = (w.player.x, w.player.y);
+    let here: Vec<_> = w.current_level().pickups.iter().filter(|p| p.x == x && p.y == y).collect();
+    assert_eq!(here.len(), 1);
+    assert!(matches!(&here[0].item, SlotItem::Stack(s) if s.item.name == food.name && s.qty == 3));
+}
+
+#[test]
+fn gear_keeps_its_full_bonus_until_it_is_damaged() {
+    let mut axe = World::willow_axe();
+    let at = |axe: &mut Equipment, dur| { axe.durability = dur; axe.effective(axe.atk_bonus) };
+    assert_eq!(at(&mut axe, 30), 15);
+    assert_eq!(at(&mut axe, 15), 15);
+    assert_eq!(at(&mut axe, 14), 15);
+    assert_eq!(at(&mut axe, 7), 11);
+    // 7.5 rounds up.
+    assert_eq!(at(&mut axe, 0), 8);
+    assert_eq!(axe.effective(-3), -3);
+}
+
+#[test]
+fn a_whetstone_stays_in_the_pack_when_nothing_is_worn() {
+    let mut w = new_world();
+    let whetstone = Consumable { name: "Whetstone".to_string(), heal: 0, atk_bonus: 0, def_bonus: 0, weight: 1, repair: 10 };
+    w.player.inventory.consumables.clear();
+    w.player.inventory.add_consumable(whetstone).unwrap();
+    assert!(w.repair_targets().is_empty());
+
+    w.apply_action(Action::ToggleInventory);
+    w.player.inventory.tab = InvTab::Consumables;
+    w.apply_action(Action::UseConsumable);
+    assert_eq!(w.player.inventory.consumables.len(), 1);
+
+    let mut sword = World::basic_sword();
+    sword.durability = 5;
+    w.player.inventory.backpack.push(sword);
+    w.apply_action(Action::UseConsumable);
+    assert!(w.player.inventory.consumables.is_empty());
+    assert_eq!(w.player.inventory.backpack[0].durability, 15);
+}
+
+#[test]
+fn gear_that_lowers_max_hp_lowers_hp_with_it() {
+    let mut w = new_world();
+    let mut dagger = World::weeping_dagger();
+    dagger.hp_bonus = -w.player.max_hp - 5;
+    w.player.hp = w.player.max_hp;
+    w.player.equip(dagger);
+    assert!(w.player.max_hp < 0);
+    assert_eq!(w.player.hp, w.player.max_hp);
+}
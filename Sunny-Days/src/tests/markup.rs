@@ -0,0 +1,46 @@
+use crate::engine::markup::{self, Tint};
+
+#[test]
+fn asterisks_and_underscores_are_plain_text() {
+    let segs = markup::parse(" *sniffles* and snake_case_names");
+    assert_eq!(segs.len(), 1);
+    assert_eq!(segs[0].text, " *sniffles* and snake_case_names");
+    assert!(!segs[0].bold && !segs[0].italic);
+}
+
+#[test]
+fn tags_nest_and_unknown_braces_stay_literal() {
+    let segs = markup::parse("{red:{b:NO} *really*} {hp} \\{b:x}");
+    let runs: Vec<_> = segs.iter().map(|s| (s.text.as_str(), s.bold, s.tint)).collect();
+    assert_eq!(runs, [
+        ("NO", true, Some(Tint::Red)),
+        (" *really*", false, Some(Tint::Red)),
+        (" {hp} {b:x}", false, None),
+    ]);
+    assert_eq!(markup::visible_len("{i:{gold:Noor}}"), 4);
+}
+
+/// Whether `text` has a word wrapped in underscores, the way italics were written before `{i:...}`.
+fn has_underscore_italics(text: &str) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    let edge = |i: Option<usize>| i.and_then(|i| chars.get(i)).is_none_or(|c| !c.is_alphanumeric());
+    (0..chars.len()).any(|i| {
+        chars[i] == '_' && edge(i.checked_sub(1)) && chars.get(i + 1).is_some_and(|c| c.is_alphabetic())
+            && (i + 2..chars.len()).any(|j| chars[j] == '_' && chars[j - 1] != ' ' && edge(Some(j + 1)))
+    })
+}
+
+#[test]
+fn built_in_text_uses_tags_for_italics() {
+    assert!(has_underscore_italics("okay… _ish_."));
+    assert!(!has_underscore_italics("snake_case_names |_| let _ = x;"));
+    // Every string literal in the files that hold dialogue, sign and chest text.
+    for (file, src) in [("world.rs", include_str!("../engine/world.rs")), ("objects.rs", include_str!("../engine/objects.rs"))] {
+        for (n, line) in src.lines().enumerate() {
+            for literal in line.split('"').skip(1).step_by(2) {
+                let text = literal.replace("\\n", "\n");
+                assert!(!has_underscore_italics(&text), "{}:{}: {}", file, n + 1, literal);
+            }
+        }
+    }
+}
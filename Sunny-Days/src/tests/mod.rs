@@ -0,0 +1,112 @@
+//! Tests that drive a seeded `World` through `apply_action`, the way the game loop does.
+//!
+//! The helpers put the player where a scene happens (next to an NPC, beside a chest) and then
+//! only send `Action`s, so every flag change goes through the real dialogue and battle code.
+
+mod balance;
+mod bot;
+mod clock;
+mod combat;
+mod config;
+mod console;
+mod crafting;
+mod inventory;
+mod markup;
+mod occupancy;
+mod render;
+mod replay;
+mod shop;
+mod story;
+
+use crate::engine::action::Action;
+use crate::engine::world::{GameState, NpcId, World};
+use crate::map::tile::Tile;
+
+pub const SEED: u64 = 42;
+
+const NEIGHBOURS: [(i32, i32); 8] = [(0, 1), (0, -1), (1, 0), (-1, 0), (1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// A world past the title and intro screens.
+pub fn new_world() -> World {
+    let mut w = World::new(SEED, 80, 45);
+    w.apply_action(Action::Confirm);
+    w.apply_action(Action::Confirm);
+    assert_eq!(w.state, GameState::Playing);
+    w
+}
+
+pub fn npc_pos(w: &World, id: NpcId) -> (usize, i32, i32) {
+    let npc = w.npcs().iter().find(|n| n.id == id).unwrap_or_else(|| panic!("{:?} isn't in the world", id));
+    (npc.room, npc.x, npc.y)
+}
+
+/// Move the player onto a plain floor tile next to `(x, y)` in `room`.
+pub fn stand_beside(w: &mut World, room: usize, x: i32, y: i32) {
+    w.current = room;
+    let map = w.current_map();
+    let spot = NEIGHBOURS
+        .iter()
+        .map(|(dx, dy)| (x + dx, y + dy))
+        .find(|&(nx, ny)| map.in_bounds(nx, ny) && map.get(nx as usize, ny as usize) == Tile::Floor && w.npc_at(room, nx, ny).is_none())
+        .unwrap_or_else(|| panic!("no floor next to ({}, {}) in room {}", x, y, room));
+    w.player.x = spot.0;
+    w.player.y = spot.1;
+}
+
+/// Walk up to an NPC and press E.
+pub fn talk_to(w: &mut World, id: NpcId) {
+    let (room, x, y) = npc_pos(w, id);
+    stand_beside(w, room, x, y);
+    assert_eq!(w.npc_near_player().map(|n| n.id), Some(id), "someone else is closer");
+    w.apply_action(Action::Interact);
+    assert_eq!(w.state, GameState::Dialogue, "talking to {:?} didn't open a dialogue", id);
+}
+
+/// Page through to the last page of the current dialogue, fully revealed.
+pub fn read_to_last_page(w: &mut World) {
+    for _ in 0..100 {
+        let d = w.dialogue.as_ref().expect("no dialogue open");
+        if d.on_last_page() && d.fully_revealed() { return; }
+        w.apply_action(Action::Confirm);
+    }
+    panic!("dialogue never reached its last page");
+}
+
+/// Read up to the question, answer it, then read the reply to the end.
+pub fn answer(w: &mut World, choice: char) {
+    read_to_last_page(w);
+    assert!(w.dialogue.as_ref().is_some_and(|d| d.awaiting.is_some()), "nothing to answer");
+    w.apply_action(Action::Choice(choice));
+    finish_dialogue(w);
+}
+
+/// Press Enter until the dialogue closes (into whatever it leads to).
+pub fn finish_dialogue(w: &mut World) {
+    for _ in 0..100 {
+        if w.state != GameState::Dialogue { return; }
+        w.apply_action(Action::Confirm);
+    }
+    panic!("dialogue never closed");
+}
+
+/// Fight until someone drops. The player is healed each round: these tests are about what
+/// the story does with a result, not about the dice.
+pub fn fight_to_the_end(w: &mut World) {
+    for _ in 0..500 {
+        if w.state != GameState::Battle { return; }
+        w.player.hp = w.player.max_hp;
+        w.apply_action(Action::BattleOption(1));
+    }
+    panic!("battle never ended");
+}
+
+/// Talk to an enemy, accept the fight, win it, and read their parting words.
+pub fn defeat(w: &mut World, id: NpcId) {
+    talk_to(w, id);
+    finish_dialogue(w);
+    assert_eq!(w.state, GameState::Battle, "{:?} didn't start a fight", id);
+    fight_to_the_end(w);
+    assert!(w.player.hp > 0);
+    finish_dialogue(w);
+    assert_eq!(w.state, GameState::Playing);
+}
@@ -0,0 +1,39 @@
+use super::*;
+
+fn assert_everyone_findable(w: &World) {
+    for npc in w.npcs() {
+        assert_eq!(w.npc_at(npc.room, npc.x, npc.y).map(|n| n.id), Some(npc.id));
+    }
+}
+
+#[test]
+fn lookups_by_position_follow_removals() {
+    let mut w = new_world();
+    assert_everyone_findable(&w);
+
+    // Noor is near the front of the list, so everyone after her shifts down a place.
+    let (room, x, y) = npc_pos(&w, NpcId::Noor);
+    let noor = w.remove_npc(NpcId::Noor).expect("Noor is in the world");
+    assert_eq!(noor.id, NpcId::Noor);
+    assert!(w.npc_at(room, x, y).is_none());
+    assert_everyone_findable(&w);
+
+    // Her tile is walkable again.
+    stand_beside(&mut w, room, x, y);
+    let (px, py) = (w.player.x, w.player.y);
+    w.apply_action(Action::Move(x - px, 0));
+    w.apply_action(Action::Move(0, y - py));
+    assert_eq!((w.player.x, w.player.y), (x, y));
+}
+
+#[test]
+fn chests_are_found_where_they_sit() {
+    let w = new_world();
+    for level in &w.levels {
+        for c in level.chests() {
+            let found = level.chest_at(c.x, c.y).expect("every chest is indexed");
+            assert_eq!((found.x, found.y), (c.x, c.y));
+        }
+    }
+    assert!(w.current_level().chest_at(-1, 0).is_none());
+}
@@ -0,0 +1,135 @@
+//! Renders each screen into a `TestBackend` and compares the text with `tests/snapshots/`.
+//!
+//! Only characters are compared, not colours. After an intended layout change, regenerate
+//! with `UPDATE_SNAPSHOTS=1 cargo test` and review the diff of the snapshot files.
+
+use super::*;
+use crate::tui::renderer::render;
+use crate::tui::renderer::map_tile_at;
+use crate::tui::theme::Theme;
+
+use ratatui::{backend::TestBackend, Terminal};
+
+use std::path::PathBuf;
+
+const SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (60, 20)];
+
+fn screen_text(w: &World, width: u16, height: u16) -> String {
+    let mut term = Terminal::new(TestBackend::new(width, height)).unwrap();
+    term.draw(|f| render(f, w, &Theme::default())).unwrap();
+    let buf = term.backend().buffer();
+    let mut out = String::new();
+    for y in 0..height {
+        let line: String = (0..width).map(|x| buf.get(x, y).symbol()).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn assert_snapshot(name: &str, w: &World, width: u16, height: u16) {
+    let actual = screen_text(w, width, height);
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots", &format!("{}_{}x{}.txt", name, width, height)].iter().collect();
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot {}; run with UPDATE_SNAPSHOTS=1 to create it", path.display()));
+    if actual != expected {
+        let line = actual.lines().zip(expected.lines()).position(|(a, e)| a != e).map_or(0, |i| i + 1);
+        panic!(
+            "{} differs from {} (first at line {}).\n--- expected\n{}--- actual\n{}",
+            name, path.display(), line, expected, actual
+        );
+    }
+}
+
+fn assert_snapshots(name: &str, w: &World) {
+    for (width, height) in SIZES {
+        assert_snapshot(name, w, width, height);
+    }
+}
+
+#[test]
+fn title() {
+    assert_snapshots("title", &World::new(SEED, 80, 45));
+}
+
+#[test]
+fn intro() {
+    let mut w = World::new(SEED, 80, 45);
+    w.apply_action(Action::Confirm);
+    assert_eq!(w.state, GameState::Intro);
+    assert_snapshots("intro", &w);
+}
+
+#[test]
+fn playing() {
+    assert_snapshots("playing", &new_world());
+}
+
+#[test]
+fn playing_with_inventory_open() {
+    let mut w = new_world();
+    w.apply_action(Action::ToggleInventory);
+    assert!(w.inventory_open);
+    assert_snapshots("inventory", &w);
+}
+
+#[test]
+fn stats() {
+    let mut w = new_world();
+    w.apply_action(Action::ToggleStats);
+    assert!(w.stats_open);
+    assert_snapshots("stats", &w);
+}
+
+#[test]
+fn dialogue() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::MayorSol);
+    w.apply_action(Action::Confirm); // skip the typewriter
+    assert_snapshots("dialogue", &w);
+}
+
+#[test]
+fn battle() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Shab);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Battle);
+    assert_snapshots("battle", &w);
+}
+
+#[test]
+fn fin() {
+    let mut w = new_world();
+    w.state = GameState::Fin;
+    assert_snapshots("fin", &w);
+}
+
+#[test]
+fn terminal_too_small() {
+    let w = new_world();
+    assert_snapshot("too_small", &w, 19, 9);
+    assert_snapshot("too_small", &w, 40, 9);
+}
+
+#[test]
+fn cells_map_back_to_the_tiles_drawn_there() {
+    let w = new_world();
+    for (width, height) in SIZES {
+        let mut term = Terminal::new(TestBackend::new(width, height)).unwrap();
+        term.draw(|f| render(f, &w, &Theme::default())).unwrap();
+        let size = term.size().unwrap();
+        let buf = term.backend().buffer();
+        let cell = (0..height).flat_map(|y| (0..width).map(move |x| (x, y))).find(|&(x, y)| buf.get(x, y).symbol() == "@");
+        let (col, row) = cell.expect("the player is drawn");
+        assert_eq!(map_tile_at(size, &w, col, row), Some((w.player.x, w.player.y)), "at {}x{}", width, height);
+        assert_eq!(map_tile_at(size, &w, width - 1, height - 1), None, "the log isn't the map");
+    }
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::engine::replay::{self, Replay};
+
+/// Apply `action` the way the game loop does, writing it down first, then let a few ticks pass.
+fn play(w: &mut World, rec: &mut Replay, action: Action, ticks: u32) {
+    rec.record(w.ticks, action);
+    w.apply_action(action);
+    for _ in 0..ticks { w.tick(); }
+}
+
+/// A short walk around the first room with a look in the bags, recorded from the title screen.
+fn recorded_session() -> (Replay, World) {
+    let mut w = World::new(SEED, 80, 45);
+    let mut rec = Replay::new(w.seed, w.settings.clone());
+    play(&mut w, &mut rec, Action::Confirm, 2);
+    play(&mut w, &mut rec, Action::Confirm, 1);
+    for (i, &(dx, dy)) in [(1, 0), (1, 0), (0, 1), (0, 1), (-1, 0), (0, -1), (1, 1), (-1, -1)].iter().cycle().take(40).enumerate() {
+        play(&mut w, &mut rec, Action::Move(dx, dy), (i % 3) as u32);
+    }
+    play(&mut w, &mut rec, Action::ToggleInventory, 3);
+    play(&mut w, &mut rec, Action::InventoryDown, 0);
+    play(&mut w, &mut rec, Action::ToggleInventory, 5);
+    rec.finish(&w);
+    (rec, w)
+}
+
+#[test]
+fn a_recorded_session_plays_back_to_the_same_state() {
+    let (rec, live) = recorded_session();
+    assert_eq!(rec.inputs.len(), 45);
+
+    // Through the file format and back.
+    let parsed = Replay::parse(&rec.to_text()).expect("the replay should parse");
+    assert_eq!(parsed.inputs, rec.inputs);
+    assert_eq!(parsed.end, rec.end);
+
+    let replayed = replay::run_headless(&parsed).expect("the replay should match");
+    assert_eq!((replayed.player.x, replayed.player.y), (live.player.x, live.player.y));
+    assert_eq!(replayed.ticks, live.ticks);
+    assert_eq!(replay::state_hash(&replayed), replay::state_hash(&live));
+}
+
+#[test]
+fn a_tampered_replay_is_caught() {
+    let (mut rec, _) = recorded_session();
+    rec.inputs.retain(|&(_, a)| a != Action::Move(1, 1));
+    let err = replay::run_headless(&rec).err().expect("a changed input list should not match");
+    assert!(err.contains("final state differs"), "{}", err);
+
+    let text = recorded_session().0.to_text().replace("seed 42", "seed 43");
+    let err = replay::run_headless(&Replay::parse(&text).unwrap()).err().expect("another seed is another game");
+    assert!(err.contains("final state differs"), "{}", err);
+}
+
+#[test]
+fn bad_replay_files_are_rejected() {
+    assert!(Replay::parse("hello\n").is_err());
+    assert!(Replay::parse("sunny-days replay 1\nsettings difficulty=normal\n").unwrap_err().contains("seed"));
+    let err = Replay::parse("sunny-days replay 1\nseed 1\n5 Jump\n").unwrap_err();
+    assert!(err.starts_with("line 3"), "{}", err);
+    assert!(Replay::parse("sunny-days replay 1\nseed 1\n5 Confirm\n2 Confirm\n").unwrap_err().contains("backwards"));
+}
+
+#[test]
+fn the_same_seed_fights_the_same_fight() {
+    let fight = || {
+        let mut w = new_world();
+        talk_to(&mut w, NpcId::Shab);
+        finish_dialogue(&mut w);
+        assert_eq!(w.state, GameState::Battle);
+        for _ in 0..20 {
+            if w.state != GameState::Battle { break; }
+            w.player.hp = w.player.max_hp;
+            w.apply_action(Action::BattleOption(1));
+        }
+        (w.logs.clone(), w.battle.as_ref().map(|b| b.enemy_hp), replay::state_hash(&w))
+    };
+    assert_eq!(fight(), fight());
+}
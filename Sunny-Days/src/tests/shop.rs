@@ -0,0 +1,34 @@
+use super::*;
+use crate::engine::entity::SlotItem;
+use crate::engine::loot;
+use crate::engine::shop::{self, ShopTab};
+
+#[test]
+fn tajer_wont_buy_the_weeping_dagger() {
+    let mut w = new_world();
+    w.player.inventory.backpack.push(World::weeping_dagger());
+    let (room, x, y) = npc_pos(&w, NpcId::Tajer);
+    stand_beside(&mut w, room, x, y);
+    w.apply_action(Action::Interact);
+    assert_eq!(w.state, GameState::Shop);
+    while w.shop.tab != ShopTab::Sell { w.apply_action(Action::ToggleInvTab); }
+
+    let is_dagger = |i: &SlotItem| matches!(i, SlotItem::Gear(e) if e.name == "Weeping Dagger");
+    assert!(!w.shop_entries().iter().any(|(i, _)| is_dagger(i)));
+
+    // Sell everything Tajer will take; the dagger stays.
+    while !w.shop_entries().is_empty() { w.apply_action(Action::Confirm); }
+    assert!(w.player.inventory.backpack.iter().any(|e| e.name == "Weeping Dagger"));
+}
+
+#[test]
+fn tajer_sells_consumables_from_the_loot_tables() {
+    let w = new_world();
+    let stacks: Vec<_> = w.shop.stock.iter().filter_map(|i| match i { SlotItem::Stack(s) => Some(&s.item), _ => None }).collect();
+    assert_eq!(stacks.len(), shop::STOCK_CONSUMABLES.len());
+    let table = loot::consumables();
+    for item in stacks {
+        let listed = table.iter().find(|c| c.name == item.name);
+        assert_eq!(format!("{:?}", listed), format!("{:?}", Some(item)));
+    }
+}
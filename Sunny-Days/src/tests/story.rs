@@ -0,0 +1,195 @@
+use super::*;
+use crate::engine::entity::GearSlot;
+use crate::engine::objects::{ObjectKind, VAULT_KEY};
+use crate::engine::world::AwaitingChoice;
+use crate::engine::world::StoryFlags;
+
+#[test]
+fn the_whole_story_reaches_the_end() {
+    let mut w = new_world();
+    assert_eq!(*w.flags(), StoryFlags::default());
+
+    talk_to(&mut w, NpcId::MayorSol);
+    answer(&mut w, 'Y');
+    assert!(w.flags().mayor_done);
+
+    talk_to(&mut w, NpcId::Noor);
+    answer(&mut w, 'A');
+    assert!(w.flags().noor_done);
+    assert!(w.player.inventory.equipped(GearSlot::Sword).is_some());
+
+    talk_to(&mut w, NpcId::Lamp);
+    finish_dialogue(&mut w);
+    assert!(w.flags().lamp_done);
+    assert!(w.player.inventory.equipped(GearSlot::Shield).is_some());
+
+    talk_to(&mut w, NpcId::Dorosht);
+    answer(&mut w, 'Y');
+    assert!(w.flags().dorosht_accepted);
+
+    defeat(&mut w, NpcId::Shab);
+    assert!(w.flags().shab_defeated);
+    defeat(&mut w, NpcId::Krad);
+    assert!(w.flags().krad_defeated);
+
+    let (room, mx, my) = npc_pos(&w, NpcId::Mah);
+    defeat(&mut w, NpcId::Mah);
+    assert!(w.flags().mah_defeated);
+    assert!(w.npcs().iter().all(|n| n.id != NpcId::Mah), "Mah should leave a chest behind");
+
+    // Mah drops the dagger where he stood.
+    stand_beside(&mut w, room, mx, my);
+    w.apply_action(Action::Move(mx - w.player.x, my - w.player.y));
+    assert_eq!(w.state, GameState::Dialogue);
+    answer(&mut w, 'A');
+    assert!(w.player.inventory.equipped_items().chain(&w.player.inventory.backpack).any(|e| e.name == "Weeping Dagger"));
+
+    talk_to(&mut w, NpcId::Dorosht);
+    finish_dialogue(&mut w);
+    assert!(w.flags().dorosht_completed);
+    assert!(w.player.inventory.equipped_items().chain(&w.player.inventory.backpack).any(|e| e.name == "Willow Axe"));
+
+    defeat(&mut w, NpcId::MayorSol);
+    assert!(w.flags().mayor_defeated);
+
+    talk_to(&mut w, NpcId::MayorSol);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Fin);
+}
+
+#[test]
+fn declining_the_mayor_still_moves_the_story_on() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::MayorSol);
+    answer(&mut w, 'N');
+    assert!(w.flags().mayor_done);
+}
+
+#[test]
+fn lamp_sends_you_to_noor_first() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Lamp);
+    finish_dialogue(&mut w);
+    assert!(!w.flags().lamp_done);
+    assert!(w.player.inventory.equipped(GearSlot::Sword).is_none());
+    assert!(w.player.inventory.equipped(GearSlot::Shield).is_none());
+}
+
+#[test]
+fn lamp_hands_over_whatever_noor_kept() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Noor);
+    answer(&mut w, 'B');
+    assert!(w.player.inventory.equipped(GearSlot::Shield).is_some());
+
+    talk_to(&mut w, NpcId::Lamp);
+    finish_dialogue(&mut w);
+    assert!(w.flags().lamp_done);
+    assert!(w.player.inventory.equipped(GearSlot::Sword).is_some());
+}
+
+#[test]
+fn mah_waits_until_his_men_are_beaten() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Mah);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Playing);
+
+    defeat(&mut w, NpcId::Shab);
+    talk_to(&mut w, NpcId::Mah);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Playing, "one of his men is still standing");
+
+    defeat(&mut w, NpcId::Krad);
+    talk_to(&mut w, NpcId::Mah);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Battle);
+}
+
+#[test]
+fn the_mayor_only_fights_once_the_dagger_is_traded() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::MayorSol);
+    answer(&mut w, 'Y');
+    defeat(&mut w, NpcId::Shab);
+    defeat(&mut w, NpcId::Krad);
+    defeat(&mut w, NpcId::Mah);
+
+    talk_to(&mut w, NpcId::MayorSol);
+    finish_dialogue(&mut w);
+    assert_eq!(w.state, GameState::Playing);
+    assert!(!w.flags().mayor_defeated);
+}
+
+#[test]
+fn dorosht_wants_the_dagger_before_trading() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Dorosht);
+    answer(&mut w, 'Y');
+
+    talk_to(&mut w, NpcId::Dorosht);
+    finish_dialogue(&mut w);
+    assert!(w.flags().dorosht_accepted);
+    assert!(!w.flags().dorosht_completed);
+}
+
+#[test]
+fn dorosht_keeps_the_axe_while_the_pack_is_full() {
+    let mut w = new_world();
+    talk_to(&mut w, NpcId::Dorosht);
+    answer(&mut w, 'Y');
+    w.player.equip(World::weeping_dagger());
+    while w.player.inventory.add_equipment(World::willow_axe()).is_ok() {}
+    let packed = w.player.inventory.backpack.len();
+
+    talk_to(&mut w, NpcId::Dorosht);
+    finish_dialogue(&mut w);
+    assert!(!w.flags().dorosht_completed);
+    assert_eq!(w.player.inventory.equipped(GearSlot::Sword).unwrap().name, "Weeping Dagger");
+    assert_eq!(w.player.inventory.backpack.len(), packed);
+}
+
+#[test]
+fn a_big_pack_never_shares_the_leave_key() {
+    let choice = AwaitingChoice::PackFull {
+        room: 0,
+        x: 0,
+        y: 0,
+        item: None,
+        weapon: None,
+        slots: (0..30).map(|i| format!("Rock {}", i)).collect(),
+    };
+    let keys: Vec<char> = choice.options().iter().map(|(k, _)| *k).collect();
+    assert_eq!(keys.iter().filter(|&&k| k == AwaitingChoice::LEAVE_KEY).count(), 1);
+    assert_eq!(keys.iter().collect::<std::collections::HashSet<_>>().len(), keys.len());
+}
+
+#[test]
+fn the_mayor_stands_on_floor_clear_of_the_door() {
+    // 35 put him beside the door and 147 inside a wall.
+    for seed in 1..=200 {
+        let w = World::new(seed, 80, 45);
+        let (_, x, y) = npc_pos(&w, NpcId::MayorSol);
+        let (dx, dy) = w.current_level().door;
+        assert_eq!(w.current_map().get(x as usize, y as usize), Tile::Floor, "seed {}", seed);
+        assert!((x - dx).abs().max((y - dy).abs()) > 2, "seed {}: the Mayor crowds the door", seed);
+    }
+}
+
+#[test]
+fn the_willow_key_is_in_reach_whenever_a_vault_is_locked() {
+    // 4468 used to hide the key behind a wall cut off from the spawn point.
+    for seed in (1..=200).chain([4468]) {
+        let w = World::new(seed, 80, 45);
+        let locked = w.levels[1].objects.iter().any(|o| matches!(o.kind, ObjectKind::LockedDoor { .. }));
+        if !locked { continue; }
+        let Some(key) = w.levels[0].chests().iter().find(|c| c.key.as_deref() == Some(VAULT_KEY)) else {
+            panic!("seed {}: a locked vault with no key", seed);
+        };
+        // Breaking the cracked wall is fair game; nothing else is.
+        let mut map = w.levels[0].map.clone();
+        for t in &mut map.tiles { if *t == Tile::CrackedWall { *t = Tile::Floor; } }
+        let reach = map.reachable_from(w.player_pos());
+        assert!(reach[map.idx(key.x as usize, key.y as usize)], "seed {}: the key is out of reach", seed);
+    }
+}
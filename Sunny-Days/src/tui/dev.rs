@@ -0,0 +1,97 @@
+//! The `--dev` overlay: hidden state drawn over the game, and the developer console.
+
+use crate::engine::console::Console;
+use crate::engine::world::World;
+use crate::tui::theme::Theme;
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Clear, Paragraph},
+    Frame,
+};
+
+const PANEL_W: u16 = 36;
+
+/// Frontend state the overlay shows alongside the world.
+#[derive(Debug, Clone, Default)]
+pub struct DevTools {
+    /// F1 shows or hides the state panel.
+    pub overlay: bool,
+    pub console: Console,
+    pub fps: f32,
+    /// Map tile under the mouse pointer.
+    pub cursor: Option<(i32, i32)>,
+}
+
+/// Draw the state panel (top right) and console (bottom) over whatever `render` drew.
+pub fn draw(f: &mut Frame, world: &World, dev: &DevTools, theme: &Theme) {
+    let size = f.size();
+    if dev.overlay {
+        let lines = panel_lines(world, dev, theme);
+        let w = PANEL_W.min(size.width);
+        let h = (lines.len() as u16 + 2).min(size.height);
+        let area = Rect { x: size.width - w, y: 0, width: w, height: h };
+        f.render_widget(Clear, area);
+        f.render_widget(Paragraph::new(lines).block(theme.block().title("Dev (F1)")).style(theme.text), area);
+    }
+    if dev.console.open {
+        draw_console(f, size, &dev.console, theme);
+    }
+}
+
+fn row(label: &str, value: String, style: Style, theme: &Theme) -> Line<'static> {
+    Line::from(vec![Span::styled(format!("{:<18}", label), theme.muted), Span::styled(value, style)])
+}
+
+fn panel_lines(world: &World, dev: &DevTools, theme: &Theme) -> Vec<Line<'static>> {
+    let p = &world.player;
+    let mut lines = vec![
+        row("seed", world.seed.to_string(), theme.text, theme),
+        row("tick / clock", format!("{} / {}", world.ticks, world.clock.now()), theme.text, theme),
+        row("fps", format!("{:.0}", dev.fps), theme.text, theme),
+        row("room / player", format!("{} ({}, {})", world.current + 1, p.x, p.y), theme.text, theme),
+    ];
+    let cursor = match dev.cursor {
+        Some((x, y)) => {
+            let tile = world.current_map().get(x as usize, y as usize);
+            let who = world.npc_at(world.current, x, y).map_or(String::new(), |n| format!(" {}", n.name));
+            format!("({}, {}) {:?}{}", x, y, tile, who)
+        }
+        None => "-".to_string(),
+    };
+    lines.push(row("cursor", cursor, theme.accent, theme));
+
+    lines.push(Line::from(Span::styled("Flags", theme.title)));
+    for (name, on) in world.flags().entries() {
+        lines.push(row(name, on.to_string(), if on { theme.good } else { theme.muted }, theme));
+    }
+
+    if let Some(bs) = &world.battle {
+        lines.push(Line::from(Span::styled(format!("Battle: {}", bs.enemy_name), theme.title)));
+        lines.push(row("penalty_mode", bs.penalty_mode.to_string(), if bs.penalty_mode { theme.danger } else { theme.muted }, theme));
+        lines.push(row("player_initiated", bs.player_initiated.to_string(), theme.text, theme));
+        lines.push(row("idle_ticks", bs.idle_ticks.to_string(), theme.text, theme));
+        lines.push(row("enemy hp/atk/def", format!("{}/{}/{}", bs.enemy_hp, bs.enemy_atk, bs.enemy_def), theme.text, theme));
+    }
+
+    lines.push(Line::from(Span::styled(format!("NPCs in room {}", world.current + 1), theme.title)));
+    for npc in world.npcs().iter().filter(|n| n.room == world.current) {
+        lines.push(row(&format!("{:?}", npc.id), format!("({}, {})", npc.x, npc.y), theme.text, theme));
+    }
+    lines
+}
+
+fn draw_console(f: &mut Frame, size: Rect, console: &Console, theme: &Theme) {
+    let h = (console.output.len() as u16 + 3).min(size.height);
+    let area = Rect { x: 0, y: size.height - h, width: size.width, height: h };
+    let mut lines: Vec<Line> = console.output.iter().map(|l| Line::from(Span::styled(l.clone(), theme.muted))).collect();
+    lines.push(Line::from(vec![
+        Span::styled("> ", theme.accent),
+        Span::styled(console.input.clone(), theme.text),
+        Span::styled("_", theme.accent),
+    ]));
+    f.render_widget(Clear, area);
+    f.render_widget(Paragraph::new(lines).block(theme.block().title("Console (Enter runs, Esc closes, help)")), area);
+}
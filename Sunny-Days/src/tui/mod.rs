@@ -1,2 +1,5 @@
-pub mod input;
-pub mod renderer;
+pub(crate) mod dev;
+pub(crate) mod input;
+pub(crate) mod portrait;
+pub(crate) mod renderer;
+pub(crate) mod theme;
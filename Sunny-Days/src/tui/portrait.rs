@@ -0,0 +1,145 @@
+use crate::engine::world::{NpcId, Speaker};
+
+/// Width (in columns) every portrait is padded to.
+pub const PORTRAIT_W: u16 = 13;
+
+const SHAB: &[&str] = &[
+    "    .---.",
+    "   ( >_< )",
+    "    \\_-_/",
+    "   /|   |\\",
+    "  / |___| \\",
+    "    |   |",
+    "   _|   |_",
+];
+
+const KRAD: &[&str] = &[
+    "  [#######]",
+    "  |[o] [o]|",
+    "  |  ===  |",
+    " /#########\\",
+    "|#|#######|#|",
+    "   |##|##|",
+    "  _|##|##|_",
+];
+
+const MAH: &[&str] = &[
+    "    _..._",
+    "  .'  .-'",
+    " /   /  (o)",
+    "|   |",
+    " \\   \\  (o)",
+    "  '._'-.",
+    "     '''",
+];
+
+const MAYOR_SOL: &[&str] = &[
+    "    _____",
+    "   |_____|",
+    " \\  (o o)  /",
+    "-- \\ ___ / --",
+    " /  \\ $ /  \\",
+    "     |$|",
+    "    _| |_",
+];
+
+const NOOR: &[&str] = &[
+    "    ,,,,,",
+    "   (o  o)",
+    "    \\~~/",
+    "   _/  \\_",
+    "  / |==| \\",
+    "    |  |",
+    "   _|  |_",
+];
+
+const LAMP: &[&str] = &[
+    "     _",
+    "    ( )",
+    "   (^ ^)",
+    "    \\o/",
+    "   /| |\\",
+    "    | |",
+    "   _| |_",
+];
+
+const DOROSHT: &[&str] = &[
+    "   .---.",
+    "  (o   o)",
+    "  | ### |",
+    "   \\###/  /",
+    "  /|   |\\/",
+    "   |   |",
+    "  _|   |_",
+];
+
+const TAJER: &[&str] = &[
+    "    _____",
+    "   /_____\\",
+    "   ( o o )",
+    "    \\ - /",
+    "  $/|   |\\$",
+    "    |___|",
+    "   _|   |_",
+];
+
+const VILLAGER: &[&str] = &[
+    "",
+    "    .-.",
+    "   (o o)",
+    "    |-|",
+    "   /| |\\",
+    "    | |",
+    "   _| |_",
+];
+
+const WEEPING: &[&str] = &[
+    "  \\|/ \\|/",
+    "   .---.",
+    "  ( T T )",
+    "   \\ ~ /",
+    "   /| |\\",
+    "  ; | | ;",
+    "   _| |_",
+];
+
+const CHEST: &[&str] = &[
+    "",
+    "  ._______.",
+    " /_______/|",
+    " |   o   ||",
+    " |_______|/",
+    "",
+    "",
+];
+
+const SIGN: &[&str] = &[
+    "",
+    " ___________",
+    "|  ~~~~~~~  |",
+    "|  ~~~~~    |",
+    "|___________|",
+    "     | |",
+    "    _|_|_",
+];
+
+/// ASCII portrait for whoever is speaking (also used for enemies in battle).
+pub fn portrait(speaker: Speaker) -> &'static [&'static str] {
+    let id = match speaker {
+        Speaker::Chest => return CHEST,
+        Speaker::Sign => return SIGN,
+        Speaker::Npc(id) => id,
+    };
+    match id {
+        NpcId::Shab => SHAB,
+        NpcId::Krad => KRAD,
+        NpcId::Mah => MAH,
+        NpcId::MayorSol => MAYOR_SOL,
+        NpcId::Noor => NOOR,
+        NpcId::Lamp => LAMP,
+        NpcId::Dorosht => DOROSHT,
+        NpcId::Tajer => TAJER,
+        NpcId::Random1 | NpcId::Random2 | NpcId::Random3 => VILLAGER,
+        NpcId::Weeping1 | NpcId::Weeping2 | NpcId::Weeping3 | NpcId::Weeping4 => WEEPING,
+    }
+}
@@ -1,12 +1,18 @@
-use crate::engine::world::{World, GameState, NpcId};
-use crate::engine::entity::{InvTab, InvSelection};
+use crate::engine::crafting;
+use crate::engine::markup::{self, Tint};
+use crate::engine::objects::ObjectKind;
+use crate::engine::world::{World, GameState, BattleSession, HitFx, HitKind, NpcId, Speaker};
+use crate::engine::entity::{Equipment, GearSlot, InvTab, InvSelection, ListView, SlotItem};
+use crate::engine::shop::ShopTab;
 use crate::map::tile::Tile;
+use crate::tui::portrait::{portrait, PORTRAIT_W};
+use crate::tui::theme::Theme;
 
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap, Clear},
+    widgets::{Paragraph, Wrap, Clear, LineGauge},
     Frame,
 };
 
@@ -38,37 +44,71 @@ fn fmt_bonus(v: i32) -> String {
     if v >= 0 { format!("+{}", v) } else { format!("{}", v) }
 }
 
-pub fn render(f: &mut Frame, world: &World) {
+/// A line of map cells, with neighbouring cells of the same style merged into one span: a
+/// full map row is then a handful of spans rather than one per cell.
+struct CellRow {
+    spans: Vec<Span<'static>>,
+    run: String,
+    style: Style,
+}
+
+impl CellRow {
+    fn new() -> Self {
+        Self { spans: Vec::new(), run: String::new(), style: Style::default() }
+    }
+
+    fn push(&mut self, ch: char, style: Style) {
+        if style != self.style { self.flush(style); }
+        self.run.push(ch);
+    }
+
+    fn push_str(&mut self, text: &str, style: Style) {
+        if style != self.style { self.flush(style); }
+        self.run.push_str(text);
+    }
+
+    fn flush(&mut self, next: Style) {
+        if !self.run.is_empty() { self.spans.push(Span::styled(std::mem::take(&mut self.run), self.style)); }
+        self.style = next;
+    }
+
+    fn into_line(mut self) -> Line<'static> {
+        self.flush(Style::default());
+        Line::from(self.spans)
+    }
+}
+
+/// Draw the whole screen for the world's current state into `f`.
+pub fn render(f: &mut Frame, world: &World, theme: &Theme) {
     let size = f.size();
-    f.render_widget(Clear, size);
 
     if size.width < 20 || size.height < 10 {
-        let msg = Paragraph::new("Terminal too small — resize to play.")
-            .block(Block::default().borders(Borders::ALL).title("Sunny Days"))
+        let msg = Paragraph::new("Terminal too small - resize to play.")
+            .block(theme.block().title("Sunny Days"))
             .wrap(Wrap { trim: true });
         f.render_widget(msg, size);
         return;
     }
 
     match world.state {
-        GameState::Title => draw_title(f, size),
-        GameState::Intro => draw_intro_static(f, size, world),
-        GameState::Playing | GameState::Dialogue => draw_playing(f, size, world),
-        GameState::Battle => draw_battle(f, size, world),
-        GameState::Fin => draw_fin(f, size),
+        GameState::Title => draw_title(f, size, theme),
+        GameState::Intro => draw_intro_static(f, size, world, theme),
+        GameState::Playing | GameState::Dialogue | GameState::Shop => draw_playing(f, size, world, theme),
+        GameState::Battle => draw_battle(f, size, world, theme),
+        GameState::Fin => draw_fin(f, size, theme),
     }
 }
 
-fn draw_title(f: &mut Frame, area: Rect) {
+fn draw_title(f: &mut Frame, area: Rect, theme: &Theme) {
     let lines = vec![
         Line::from(Span::styled(
             "Sunny Day",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            theme.title,
         )),
         Line::from(""),
         Line::from(Span::styled(
             "By Kian Kakavandi",
-            Style::default().fg(Color::White),
+            theme.text,
         )),
         Line::from(""),
         Line::from("Click space to continue"),
@@ -76,16 +116,16 @@ fn draw_title(f: &mut Frame, area: Rect) {
 
     let title = Paragraph::new(lines)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        .block(theme.block());
 
     f.render_widget(title, area);
 }
 
-fn draw_intro_static(f: &mut Frame, area: Rect, world: &World) {
+fn draw_intro_static(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
     let mut lines: Vec<Line> = Vec::new();
     lines.push(Line::from(Span::styled(
         "INTRO",
-        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        theme.title,
     )));
     lines.push(Line::from(""));
     for l in world.intro_lines() {
@@ -96,28 +136,28 @@ fn draw_intro_static(f: &mut Frame, area: Rect, world: &World) {
 
     let intro = Paragraph::new(lines)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL))
+        .block(theme.block())
         .wrap(Wrap { trim: true });
 
     f.render_widget(intro, area);
 }
 
-fn draw_fin(f: &mut Frame, area: Rect) {
+fn draw_fin(f: &mut Frame, area: Rect, theme: &Theme) {
     let lines = vec![
         Line::from(""),
         Line::from(Span::styled(
             "FIN",
-            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            theme.danger.add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
         Line::from(Span::styled(
             "SUNNY DAY",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            theme.title,
         )),
         Line::from(""),
         Line::from(Span::styled(
             "BY KIAN KAKAVANDI",
-            Style::default().fg(Color::White).add_modifier(Modifier::ITALIC),
+            theme.text.add_modifier(Modifier::ITALIC),
         )),
         Line::from(""),
         Line::from("Press Ctrl+C to exit"),
@@ -125,27 +165,35 @@ fn draw_fin(f: &mut Frame, area: Rect) {
 
     let fin = Paragraph::new(lines)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        .block(theme.block());
 
     f.render_widget(fin, area);
 }
 
-fn draw_playing(f: &mut Frame, size: Rect, world: &World) {
-    let log_h = (size.height / 4).clamp(5, 10);
+/// The top band (world view and sidebar) and bottom panel of the playing and battle screens.
+fn screen_rows(size: Rect, world: &World) -> (Rect, Rect) {
+    let bottom_h = if world.state == GameState::Battle {
+        (size.height / 2).clamp(10, 16)
+    } else if world.dialogue.is_some() {
+        // Dialogue needs room for the speaker's portrait and the choice list.
+        (size.height / 3).clamp(6, 13)
+    } else {
+        (size.height / 4).clamp(5, 10)
+    };
 
     let vertical = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Min(3),
-            Constraint::Length(log_h),
+            Constraint::Length(bottom_h),
         ])
         .split(size);
+    (vertical[0], vertical[1])
+}
 
-    let top = vertical[0];
-    let bottom = vertical[1];
-
+/// The world view and the sidebar; on narrow terminals the sidebar goes underneath.
+fn split_top(top: Rect) -> (Rect, Rect) {
     let sidebar_w = (top.width / 3).clamp(20, 40);
-
     if top.width < sidebar_w + 25 {
         let stacked = Layout::default()
             .direction(Direction::Vertical)
@@ -154,9 +202,7 @@ fn draw_playing(f: &mut Frame, size: Rect, world: &World) {
                 Constraint::Length(12),
             ])
             .split(top);
-
-        draw_map(f, stacked[0], world);
-        draw_sidebar(f, stacked[1], world);
+        (stacked[0], stacked[1])
     } else {
         let horizontal = Layout::default()
             .direction(Direction::Horizontal)
@@ -165,80 +211,134 @@ fn draw_playing(f: &mut Frame, size: Rect, world: &World) {
                 Constraint::Length(sidebar_w),
             ])
             .split(top);
+        (horizontal[0], horizontal[1])
+    }
+}
+
+/// The map tile drawn at terminal cell `(col, row)` of a `size` screen, if the map shows one there.
+pub fn map_tile_at(size: Rect, world: &World, col: u16, row: u16) -> Option<(i32, i32)> {
+    if size.width < 20 || size.height < 10 { return None; }
+    let on_map = match world.state {
+        GameState::Playing | GameState::Dialogue | GameState::Shop => !world.overview_open,
+        GameState::Battle => true,
+        _ => false,
+    };
+    if !on_map { return None; }
+
+    let (area, _) = split_top(screen_rows(size, world).0);
+    let inner = Rect { x: area.x + 1, y: area.y + 1, width: area.width.saturating_sub(2), height: area.height.saturating_sub(2) };
+    if col < inner.x || row < inner.y || col >= inner.x + inner.width || row >= inner.y + inner.height { return None; }
 
-        draw_map(f, horizontal[0], world);
-        draw_sidebar(f, horizontal[1], world);
+    let view = MapView::new(area, world);
+    let (wx, wy) = (view.x0 + (col - inner.x) as i32, view.y0 + (row - inner.y) as i32);
+    (view.in_zoom(wx, wy, world) && world.current_map().in_bounds(wx, wy)).then_some((wx, wy))
+}
+
+fn draw_playing(f: &mut Frame, size: Rect, world: &World, theme: &Theme) {
+    let (top, bottom) = screen_rows(size, world);
+    let (world_area, sidebar) = split_top(top);
+    draw_world_view(f, world_area, world, theme);
+    draw_sidebar(f, sidebar, world, theme);
+
+    if world.inventory_open && world.inspect_open {
+        draw_inspect(f, world_area, world, theme);
+    }
+    if world.state == GameState::Shop {
+        draw_shop(f, world_area, world, theme);
     }
 
     if world.dialogue.is_some() {
-        draw_dialogue(f, bottom, world);
+        draw_dialogue(f, bottom, world, theme);
     } else if world.stats_open {
-        draw_stats(f, bottom, world);
+        draw_stats(f, bottom, world, theme);
     } else {
-        draw_logs(f, bottom, world);
+        draw_logs(f, bottom, world, theme);
     }
 }
 
-fn draw_battle(f: &mut Frame, size: Rect, world: &World) {
-    let log_h = (size.height / 4).clamp(5, 10);
+fn draw_battle(f: &mut Frame, size: Rect, world: &World, theme: &Theme) {
+    let (top, bottom) = screen_rows(size, world);
+    let (map_area, sidebar) = split_top(top);
+    draw_map(f, map_area, world, theme);
+    draw_sidebar(f, sidebar, world, theme);
 
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Min(3),
-            Constraint::Length(log_h),
-        ])
-        .split(size);
+    if let Some(bs) = &world.battle {
+        let block = theme.block().title("Battle").style(theme.danger);
+        let inner = block.inner(bottom);
+        f.render_widget(block, bottom);
 
-    let top = vertical[0];
-    let bottom = vertical[1];
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(PORTRAIT_W + 2),
+                Constraint::Min(10),
+            ])
+            .split(inner);
 
-    let sidebar_w = (top.width / 3).clamp(20, 40);
+        draw_enemy_portrait(f, columns[0], world, bs, theme);
 
-    if top.width < sidebar_w + 25 {
-        let stacked = Layout::default()
+        let rows = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Min(3),
-                Constraint::Length(12),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Min(1),
             ])
-            .split(top);
+            .split(columns[1]);
 
-        draw_map(f, stacked[0], world);
-        draw_sidebar(f, stacked[1], world);
-    } else {
-        let horizontal = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Min(10),
-                Constraint::Length(sidebar_w),
+        f.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("BATTLE VS {}", bs.enemy_name),
+                theme.danger.add_modifier(Modifier::BOLD),
+            ))),
+            rows[0],
+        );
+
+        let enemy_style = fx_style(bs.enemy_fx, world.ticks, theme.danger, theme);
+        draw_hp_gauge(f, rows[1], &bs.enemy_name, bs.enemy_hp, bs.enemy_max_hp, enemy_style, theme);
+        let player_style = fx_style(bs.player_fx, world.ticks, theme.good, theme);
+        draw_hp_gauge(f, rows[2], "You", world.player.hp, world.player.max_hp, player_style, theme);
+
+        let turn_line = if bs.player_acts_first(world.player.speed()) {
+            Line::from(vec![
+                Span::styled("Turn: ", theme.text),
+                Span::styled("You", theme.good.add_modifier(Modifier::BOLD)),
+                Span::raw(" -> "),
+                Span::styled(bs.enemy_name.clone(), theme.danger),
             ])
-            .split(top);
-
-        draw_map(f, horizontal[0], world);
-        draw_sidebar(f, horizontal[1], world);
-    }
+        } else {
+            let reason = if bs.penalty_mode { " (you hesitated)" }
+                else if bs.terrain == Tile::Water { " (you're wading)" }
+                else { " (faster)" };
+            Line::from(vec![
+                Span::styled("Turn: ", theme.text),
+                Span::styled(bs.enemy_name.clone(), theme.danger.add_modifier(Modifier::BOLD)),
+                Span::raw(" -> "),
+                Span::styled("You", theme.good),
+                Span::styled(reason, theme.muted),
+            ])
+        };
+        f.render_widget(Paragraph::new(turn_line), rows[3]);
+        draw_battle_timer(f, rows[4], world, bs, theme);
 
-    if let Some(bs) = &world.battle {
-        let mut lines = vec![
-            Line::from(Span::styled(
-                format!("BATTLE VS {}", bs.enemy_name),
-                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-            )),
-            Line::from(format!("Enemy HP: {}/{}", bs.enemy_hp, bs.enemy_max_hp)),
-            Line::from(""),
-        ];
-        
+        let mut lines: Vec<Line> = Vec::new();
         if world.inventory_open {
              lines.push(Line::from("SELECT CONSUMABLE (Space) OR I to Cancel"));
-             for (i, c) in world.player.inventory.consumables.iter().enumerate() {
+             let inv = &world.player.inventory;
+             for i in inv.visible_consumables() {
+                 let c = &inv.consumables[i];
                  let marker = if matches!(world.player.inventory.selection(), InvSelection::Consumable(idx) if idx == i) { ">" } else { " " };
-                 lines.push(Line::from(format!("{} {}", marker, c.name)));
+                 lines.push(Line::from(format!("{} {}{}", marker, c.item.name, fmt_qty(c.qty))));
              }
         } else {
-            lines.push(Line::from("1. Fight"));
-            lines.push(Line::from("2. Inventory"));
-            lines.push(Line::from("3. Run"));
+            let pause = if world.can_pause_battle_timer() { "   P. Pause timer" } else { "" };
+            lines.push(Line::from(format!("1. Fight   2. Inventory   3. Run{}", pause)));
+            if let Some(note) = bs.terrain_note() {
+                lines.push(Line::from(Span::styled(note, theme.muted)));
+            }
         }
         
         lines.push(Line::from("--- Log ---"));
@@ -246,116 +346,671 @@ fn draw_battle(f: &mut Frame, size: Rect, world: &World) {
             lines.push(Line::from(l.clone()));
         }
 
-        let block = Block::default().borders(Borders::ALL).title("Battle").style(Style::default().fg(Color::Red));
-        f.render_widget(Paragraph::new(lines).block(block).wrap(Wrap { trim: true }), bottom);
+        f.render_widget(
+            Paragraph::new(lines).style(theme.text).wrap(Wrap { trim: true }),
+            rows[5],
+        );
+    }
+}
+
+fn draw_battle_timer(f: &mut Frame, area: Rect, world: &World, bs: &BattleSession, theme: &Theme) {
+    let (Some(limit), Some(left)) = (world.battle_timer_ticks(), world.battle_time_left()) else {
+        f.render_widget(
+            Paragraph::new(Span::styled("Timer: off", theme.muted)),
+            area,
+        );
+        return;
+    };
+
+    let secs_left = (left * World::TICK_MS).div_ceil(1000);
+    let (label, style) = if bs.penalty_mode {
+        (format!("{} has the initiative", bs.enemy_name), theme.danger)
+    } else if bs.timer_paused {
+        (format!("Timer {:>2}s (paused)", secs_left), theme.muted)
+    } else if secs_left <= 3 {
+        (format!("Timer {:>2}s", secs_left), theme.danger)
+    } else {
+        (format!("Timer {:>2}s", secs_left), theme.warning)
+    };
+
+    let ratio = if limit > 0 { (left as f64 / limit as f64).clamp(0.0, 1.0) } else { 0.0 };
+    let gauge = LineGauge::default()
+        .ratio(ratio)
+        .label(Span::styled(format!("{:<20}", label), theme.text))
+        .line_set(theme.glyphs.line)
+        .gauge_style(style.bg(theme.gauge_bg));
+    f.render_widget(gauge, area);
+}
+
+/// Pulse for a combatant: hits flash between danger and reversed text each tick, deflections glow.
+fn fx_style(fx: Option<HitFx>, ticks: u64, base: Style, theme: &Theme) -> Style {
+    match fx {
+        Some(HitFx { kind: HitKind::Hit, .. }) => {
+            if ticks.is_multiple_of(2) { theme.text.add_modifier(Modifier::REVERSED) } else { theme.danger }
+        }
+        Some(HitFx { kind: HitKind::Deflect, .. }) => theme.deflect,
+        None => base,
+    }
+}
+
+fn draw_hp_gauge(f: &mut Frame, area: Rect, who: &str, hp: i32, max_hp: i32, style: Style, theme: &Theme) {
+    let ratio = if max_hp > 0 { (hp.max(0) as f64 / max_hp as f64).clamp(0.0, 1.0) } else { 0.0 };
+    let gauge = LineGauge::default()
+        .ratio(ratio)
+        .label(Span::styled(
+            format!("{:<10} {:>3}/{:<3}", who, hp.max(0), max_hp),
+            theme.text,
+        ))
+        .line_set(theme.glyphs.line)
+        .gauge_style(style.bg(theme.gauge_bg));
+    f.render_widget(gauge, area);
+}
+
+fn draw_enemy_portrait(f: &mut Frame, area: Rect, world: &World, bs: &BattleSession, theme: &Theme) {
+    // Shake sideways by one column on alternate ticks while a hit lands.
+    let shake = matches!(bs.enemy_fx, Some(HitFx { kind: HitKind::Hit, .. })) && world.ticks.is_multiple_of(2);
+    let pad = if shake { " " } else { "" };
+    let style = fx_style(bs.enemy_fx, world.ticks, theme.enemy, theme);
+
+    let lines: Vec<Line> = portrait(Speaker::Npc(bs.enemy_id))
+        .iter()
+        .map(|row| Line::from(Span::styled(format!("{}{}", pad, row), style)))
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+fn draw_world_view(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
+    if world.overview_open {
+        draw_overview(f, area, world, theme);
+    } else {
+        draw_map(f, area, world, theme);
     }
 }
 
-fn draw_map(f: &mut Frame, area: Rect, world: &World) {
+/// What one overview "pixel" (a block of map tiles) shows, in increasing priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum OverviewCell {
+    Unknown,
+    Wall,
+    Floor,
+    Water,
+    Lava,
+    OpenedChest,
+    Object,
+    Npc(usize),
+    Chest,
+    Door,
+    Player,
+}
+
+fn overview_cell(world: &World, x0: i32, y0: i32, scale: i32) -> OverviewCell {
+    let level = &world.levels[world.current];
+    let map = &level.map;
+    let mut best = OverviewCell::Unknown;
+    // With several people in the block, whoever came first in `npcs` shows.
+    let mut npc: Option<usize> = None;
+
+    for y in y0..y0 + scale {
+        for x in x0..x0 + scale {
+            if !map.in_bounds(x, y) { continue; }
+            if x == world.player.x && y == world.player.y { return OverviewCell::Player; }
+            if !level.is_explored(x, y) { continue; }
+
+            let cell = match map.get(x as usize, y as usize) {
+                Tile::Wall => OverviewCell::Wall,
+                Tile::Floor => OverviewCell::Floor,
+                Tile::Door | Tile::LockedDoor | Tile::Gate => OverviewCell::Door,
+                Tile::Chest => OverviewCell::Chest,
+                Tile::CrackedWall => OverviewCell::Wall,
+                Tile::Lever | Tile::Fountain | Tile::Sign => OverviewCell::Object,
+                Tile::Trap if matches!(level.object_at(x, y).map(|o| &o.kind), Some(ObjectKind::Trap { sprung: true, .. })) => OverviewCell::Object,
+                Tile::Trap => OverviewCell::Floor,
+                Tile::Water => OverviewCell::Water,
+                Tile::Lava => OverviewCell::Lava,
+                Tile::Grass | Tile::Ice | Tile::Dark => OverviewCell::Floor,
+            };
+            best = best.max(cell);
+            // Opened chests turn back into floor tiles, and NPCs aren't tiles at all.
+            if level.chest_at(x, y).is_some_and(|c| c.opened) { best = best.max(OverviewCell::OpenedChest); }
+            if let Some(i) = world.npc_index_at(world.current, x, y) { npc = Some(npc.map_or(i, |j| j.min(i))); }
+        }
+    }
+
+    match npc {
+        Some(i) if best < OverviewCell::Npc(0) => OverviewCell::Npc(i),
+        _ => best,
+    }
+}
+
+fn overview_marker(cell: OverviewCell, world: &World, theme: &Theme) -> Option<Span<'static>> {
+    let (ch, style) = match cell {
+        OverviewCell::Player => (theme.glyphs.player, theme.player),
+        OverviewCell::Door => (theme.glyphs.door, theme.door),
+        OverviewCell::Chest => (theme.glyphs.chest, theme.chest),
+        OverviewCell::OpenedChest => (theme.glyphs.chest.to_ascii_lowercase(), theme.muted),
+        OverviewCell::Object => ('.', theme.object),
+        OverviewCell::Npc(i) => {
+            let npc = &world.npcs()[i];
+            (theme.npc_glyph(npc.id, npc.symbol), theme.npc(npc.id))
+        }
+        _ => return None,
+    };
+    Some(Span::styled(ch.to_string(), style))
+}
+
+/// Scales the whole level into the panel. With colour, each character cell stacks two map rows
+/// using a half-block; otherwise it falls back to plain characters.
+fn draw_overview(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
+    let map = world.current_map();
+    let map_w = map.width as i32;
+    let map_h = map.height as i32;
+
+    let inner_w = (area.width as i32 - 2).max(1);
+    let inner_h = (area.height as i32 - 2).max(1);
+
+    let half_blocks = theme.has_colour() && !theme.glyphs.ascii;
+    let rows_per_cell = if half_blocks { 2 } else { 1 };
+
+    // Uniform scale keeps rooms square-ish: one overview pixel covers scale x scale tiles.
+    let scale = ((map_w + inner_w - 1) / inner_w)
+        .max((map_h + inner_h * rows_per_cell - 1) / (inner_h * rows_per_cell))
+        .max(1);
+    let out_w = (map_w + scale - 1) / scale;
+    let out_h = (map_h + scale * rows_per_cell - 1) / (scale * rows_per_cell);
+
+    let terrain = |cell: OverviewCell| match cell {
+        OverviewCell::Wall => theme.wall.fg.unwrap_or_default(),
+        OverviewCell::Water => theme.water.fg.unwrap_or_default(),
+        OverviewCell::Lava => theme.lava.fg.unwrap_or_default(),
+        _ => theme.floor.fg.unwrap_or_default(),
+    };
+
+    let mut lines: Vec<Line> = Vec::with_capacity(out_h as usize);
+    for cy in 0..out_h {
+        let mut row = CellRow::new();
+        for cx in 0..out_w {
+            let x0 = cx * scale;
+            let top_y = cy * scale * rows_per_cell;
+            let top = overview_cell(world, x0, top_y, scale);
+            let bottom = if half_blocks { overview_cell(world, x0, top_y + scale, scale) } else { top };
+
+            if let Some(marker) = overview_marker(top.max(bottom), world, theme) {
+                row.push_str(&marker.content, marker.style);
+            } else if half_blocks {
+                // Unexplored halves stay transparent rather than taking a colour.
+                let (ch, style) = match (top, bottom) {
+                    (OverviewCell::Unknown, OverviewCell::Unknown) => (' ', Style::default()),
+                    (OverviewCell::Unknown, b) => ('\u{2584}', Style::default().fg(terrain(b))),
+                    (t, OverviewCell::Unknown) => ('\u{2580}', Style::default().fg(terrain(t))),
+                    (t, b) => ('\u{2580}', Style::default().fg(terrain(t)).bg(terrain(b))),
+                };
+                row.push(ch, style);
+            } else {
+                let (ch, style) = match top {
+                    OverviewCell::Wall => (theme.glyphs.wall, theme.wall),
+                    OverviewCell::Water => (theme.glyphs.water, theme.water),
+                    OverviewCell::Lava => (theme.glyphs.lava, theme.lava),
+                    OverviewCell::Unknown => (' ', Style::default()),
+                    _ => ('.', theme.floor),
+                };
+                row.push(ch, style);
+            }
+        }
+        lines.push(row.into_line());
+    }
+
+    let overview = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .block(theme.block().title(format!("Overview 1:{} (M to close)", scale)));
+
+    f.render_widget(overview, area);
+}
+
+/// Which part of the map a map panel of a given size shows.
+struct MapView {
+    x0: i32,
+    y0: i32,
+    view_w: i32,
+    view_h: i32,
+    half_zoom_w: i32,
+    half_zoom_h: i32,
+}
+
+impl MapView {
+    fn new(area: Rect, world: &World) -> Self {
+        let map = world.current_map();
+        let view_w = (area.width as i32).saturating_sub(2).max(1);
+        let view_h = (area.height as i32).saturating_sub(2).max(1);
+        let (x0, y0) = compute_viewport_origin(world.player.x, world.player.y, map.width as i32, map.height as i32, view_w, view_h);
+        Self { x0, y0, view_w, view_h, half_zoom_w: ZOOM_W.min(view_w) / 2, half_zoom_h: ZOOM_H.min(view_h) / 2 }
+    }
+
+    /// Only the window around the player is drawn; the rest of the panel stays blank.
+    fn in_zoom(&self, wx: i32, wy: i32, world: &World) -> bool {
+        (wx - world.player.x).abs() <= self.half_zoom_w && (wy - world.player.y).abs() <= self.half_zoom_h
+    }
+}
+
+fn draw_map(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
     f.render_widget(Clear, area);
 
     let map = world.current_map();
+    let level = world.current_level();
+    let fov = world.field_of_view();
     let px = world.player.x;
     let py = world.player.y;
 
     let map_w = map.width as i32;
     let map_h = map.height as i32;
 
-    let inner_w = (area.width as i32).saturating_sub(2);
-    let inner_h = (area.height as i32).saturating_sub(2);
+    let view = MapView::new(area, world);
+    let (x0, y0) = (view.x0, view.y0);
 
-    let view_w = inner_w.max(1);
-    let view_h = inner_h.max(1);
+    let mut lines: Vec<Line> = Vec::with_capacity(view.view_h as usize);
 
-    let (x0, y0) = compute_viewport_origin(px, py, map_w, map_h, view_w, view_h);
-
-    let zoom_w = ZOOM_W.min(view_w);
-    let zoom_h = ZOOM_H.min(view_h);
-    let half_zoom_w = zoom_w / 2;
-    let half_zoom_h = zoom_h / 2;
-
-    let mut lines: Vec<Line> = Vec::with_capacity(view_h as usize);
-
-    for vy in 0..view_h {
+    for vy in 0..view.view_h {
         let wy = y0 + vy;
-        let mut spans: Vec<Span> = Vec::with_capacity(view_w as usize);
+        let mut row = CellRow::new();
 
-        for vx in 0..view_w {
+        for vx in 0..view.view_w {
             let wx = x0 + vx;
 
-            if (wx - px).abs() > half_zoom_w || (wy - py).abs() > half_zoom_h {
-                spans.push(Span::raw(" "));
+            if !view.in_zoom(wx, wy, world) {
+                row.push(' ', Style::default());
                 continue;
             }
 
             if wx == px && wy == py {
-                spans.push(Span::styled("@", Style::default().fg(Color::Yellow)));
+                row.push(theme.glyphs.player, theme.player);
                 continue;
             }
 
-            if let Some(npc) = world.npc_at(world.current, wx, wy) {
-                let (style, bold) = match npc.id {
-                    NpcId::MayorSol => (Style::default().fg(Color::Cyan), true),
-                    NpcId::Noor => (Style::default().fg(Color::Magenta), true),
-                    NpcId::Lamp | NpcId::Dorosht => (Style::default().fg(Color::Yellow), true),
-                    NpcId::Random1 | NpcId::Random2 | NpcId::Random3 => {
-                        (Style::default().fg(Color::Yellow), true)
-                    }
-                    NpcId::Weeping1 | NpcId::Weeping2 | NpcId::Weeping3 | NpcId::Weeping4 => {
-                        (Style::default().fg(Color::LightBlue), true)
-                    }
-                    NpcId::Shab | NpcId::Krad | NpcId::Mah => {
-                        (Style::default().fg(Color::Red), true)
-                    }
-                };
-                spans.push(Span::styled(
-                    npc.symbol.to_string(),
-                    if bold { style.add_modifier(Modifier::BOLD) } else { style },
-                ));
+            if wx < 0 || wy < 0 || wx >= map_w || wy >= map_h {
+                row.push(' ', Style::default());
                 continue;
             }
 
-            if wx < 0 || wy < 0 || wx >= map_w || wy >= map_h {
-                spans.push(Span::raw(" "));
+            let visible = fov.is_visible(wx, wy);
+            if !visible && !level.is_explored(wx, wy) {
+                row.push(' ', Style::default());
                 continue;
             }
 
+            if visible {
+                if let Some(npc) = world.npc_at(world.current, wx, wy) {
+                    row.push(theme.npc_glyph(npc.id, npc.symbol), theme.npc(npc.id));
+                    continue;
+                }
+
+                if world.pickup_at(wx, wy).is_some() {
+                    row.push(theme.glyphs.pickup, theme.pickup);
+                    continue;
+                }
+            }
+
             let tile = map.get(wx as usize, wy as usize);
-            let (ch, style) = match tile {
-                Tile::Wall => ("#", Style::default().fg(Color::DarkGray)),
-                Tile::Floor => (" ", Style::default()),
-                Tile::Door => ("+", Style::default().fg(Color::White)),
-                Tile::Chest => ("C", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            };
+            let (ch, style) = tile_glyph(tile, level.object_at(wx, wy).map(|o| &o.kind), theme);
+            // Out of sight you only see what you remember, greyed out.
+            let style = if visible { style } else { theme.muted };
 
-            spans.push(Span::styled(ch, style));
+            row.push(ch, style);
         }
 
-        lines.push(Line::from(spans));
+        lines.push(row.into_line());
     }
 
-    let map_widget = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Map"))
-        .wrap(Wrap { trim: false });
+    // Rows are exactly as wide as the panel, so there's nothing to wrap.
+    let map_widget = Paragraph::new(lines).block(theme.block().title("Map"));
 
     f.render_widget(map_widget, area);
 }
 
-fn tab_label(tab: InvTab, active: InvTab, title: &str) -> Span<'static> {
+fn tile_glyph(tile: Tile, object: Option<&ObjectKind>, theme: &Theme) -> (char, Style) {
+    match tile {
+        Tile::Wall => (theme.glyphs.wall, theme.wall),
+        Tile::Floor => (' ', Style::default()),
+        Tile::Door => (theme.glyphs.door, theme.door),
+        Tile::Chest => (theme.glyphs.chest, theme.chest),
+        Tile::LockedDoor => (theme.glyphs.locked_door, theme.locked),
+        Tile::Gate => (theme.glyphs.gate, theme.locked),
+        Tile::Lever if matches!(object, Some(ObjectKind::Lever { pulled: true, .. })) => (theme.glyphs.lever_pulled, theme.object),
+        Tile::Lever => (theme.glyphs.lever, theme.object),
+        Tile::CrackedWall => (theme.glyphs.cracked_wall, theme.wall),
+        Tile::Fountain if matches!(object, Some(ObjectKind::Fountain { charges: 0 })) => (theme.glyphs.fountain, theme.muted),
+        Tile::Fountain => (theme.glyphs.fountain, theme.object),
+        Tile::Trap if matches!(object, Some(ObjectKind::Trap { sprung: true, .. })) => (theme.glyphs.trap, theme.trap),
+        Tile::Trap => (' ', Style::default()), // hidden until it goes off
+        Tile::Sign => (theme.glyphs.sign, theme.object),
+        Tile::Water => (theme.glyphs.water, theme.water),
+        Tile::Grass => (theme.glyphs.grass, theme.grass),
+        Tile::Lava => (theme.glyphs.lava, theme.lava),
+        Tile::Ice => (theme.glyphs.ice, theme.ice),
+        Tile::Dark => (theme.glyphs.dark, theme.dark),
+    }
+}
+
+fn tab_label(tab: InvTab, active: InvTab, title: &str, theme: &Theme) -> Span<'static> {
     if tab == active {
         Span::styled(
             format!("[{}]", title),
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            theme.title,
         )
     } else {
         Span::styled(
             format!(" {} ", title),
-            Style::default().fg(Color::DarkGray),
+            theme.muted,
         )
     }
 }
 
-fn draw_sidebar(f: &mut Frame, area: Rect, world: &World) {
+/// " by name" plus the active filter, shown after a list heading.
+fn view_label(view: ListView, theme: &Theme) -> Span<'static> {
+    let filter = view.filter.map(|k| format!(", {} only", k.name())).unwrap_or_default();
+    Span::styled(format!(" by {}{}", view.sort.name(), filter), theme.muted)
+}
+
+/// Full stats and description of the selected inventory item, drawn over the map.
+fn draw_inspect(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
+    let inv = &world.player.inventory;
+    let w = area.width.saturating_sub(4).min(46);
+    let h = area.height.saturating_sub(2).min(13);
+    let popup = Rect {
+        x: area.x + (area.width - w) / 2,
+        y: area.y + (area.height - h) / 2,
+        width: w,
+        height: h,
+    };
+
+    let stat = |label: &str, v: i32| Span::styled(
+        format!("{} {}  ", label, fmt_bonus(v)),
+        if v > 0 { theme.good } else if v < 0 { theme.danger } else { theme.muted },
+    );
+
+    let mut lines: Vec<Line> = Vec::new();
+    let (equipped, gear) = match inv.selection() {
+        InvSelection::Gear(slot) => (true, inv.equipped(slot)),
+        InvSelection::BackpackItem(i) => (false, inv.backpack.get(i)),
+        _ => (false, None),
+    };
+
+    if let Some(eq) = gear {
+        lines.push(Line::from(Span::styled(eq.name.clone(), theme.rarity(eq.rarity).add_modifier(Modifier::BOLD))));
+        let state = if equipped { "  (equipped)" } else { "" };
+        lines.push(Line::from(Span::styled(
+            format!("{} {}{}", eq.rarity.name(), eq.kind().name(), state),
+            theme.muted,
+        )));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            stat("ATK", eq.atk_bonus),
+            stat("DEF", eq.def_bonus),
+            stat("SPD", eq.speed_bonus),
+            stat("HP", eq.hp_bonus),
+        ]));
+        lines.push(Line::from(format!("Weight: {}", eq.weight)));
+        let condition = if eq.is_broken() {
+            "  broken"
+        } else if eq.is_damaged() {
+            "  worn: bonuses reduced"
+        } else {
+            ""
+        };
+        lines.push(Line::from(vec![
+            Span::raw("Durability:"),
+            durability_span(eq, theme),
+            Span::styled(condition, theme.muted),
+        ]));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(eq.description(), theme.text.add_modifier(Modifier::ITALIC))));
+    } else if let InvSelection::Consumable(i) = inv.selection() {
+        let stack = &inv.consumables[i];
+        let c = &stack.item;
+        lines.push(Line::from(Span::styled(format!("{}{}", c.name, fmt_qty(stack.qty)), theme.title)));
+        lines.push(Line::from(Span::styled(c.kind().name(), theme.muted)));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![stat("HP", c.heal)]));
+        if c.atk_bonus != 0 || c.def_bonus != 0 {
+            lines.push(Line::from(vec![
+                stat("ATK", c.atk_bonus),
+                stat("DEF", c.def_bonus),
+                Span::styled("for 30 sec", theme.muted),
+            ]));
+        }
+        lines.push(Line::from(format!("Weight: {} each", c.weight)));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(c.description(), theme.text.add_modifier(Modifier::ITALIC))));
+    } else {
+        lines.push(Line::from(Span::styled("Nothing selected.", theme.muted)));
+    }
+
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(theme.block().title("Inspect (E to close)").border_style(theme.accent))
+            .wrap(Wrap { trim: true }),
+        popup,
+    );
+}
+
+/// "ATK: 10 -> 13 (+3)", green when it goes up and red when it goes down.
+fn delta_line(label: &str, before: i32, after: i32, theme: &Theme) -> Line<'static> {
+    let arrow = if theme.glyphs.ascii { "->" } else { "→" };
+    let diff = after - before;
+    let style = if diff > 0 { theme.good } else if diff < 0 { theme.danger } else { theme.muted };
+    let change = if diff == 0 { String::new() } else { format!(" ({})", fmt_bonus(diff)) };
+    Line::from(vec![
+        Span::raw(format!("  {:<4}{} {} ", label, before, arrow)),
+        Span::styled(format!("{}{}", after, change), style),
+    ])
+}
+
+/// What using or equipping the hovered item would do to your stats.
+fn compare_lines(world: &World, theme: &Theme) -> Vec<Line<'static>> {
+    let p = &world.player;
+    let inv = &p.inventory;
+    let mut lines = Vec::new();
+
+    match inv.selection() {
+        InvSelection::BackpackItem(i) if inv.tab == InvTab::Backpack => {
+            let Some(eq) = inv.backpack.get(i) else { return lines };
+            lines.push(Line::from(Span::styled("Compare", theme.accent)));
+            lines.push(Line::from(vec![
+                Span::styled(eq.name.clone(), theme.rarity(eq.rarity)),
+                Span::raw(format!(
+                    " ({} ATK, {} DEF, {} SPD, {} HP)",
+                    fmt_bonus(eq.effective(eq.atk_bonus)),
+                    fmt_bonus(eq.effective(eq.def_bonus)),
+                    fmt_bonus(eq.effective(eq.speed_bonus)),
+                    fmt_bonus(eq.hp_bonus),
+                )),
+            ]));
+            if eq.is_broken() {
+                lines.push(Line::from(Span::styled("Broken: repair it before equipping.", theme.danger)));
+                return lines;
+            }
+            let replaces = inv.slot_for(eq).and_then(|s| inv.equipped(s));
+            lines.push(Line::from(match replaces {
+                Some(old) => vec![Span::raw("Replaces "), Span::styled(old.name.clone(), theme.rarity(old.rarity))],
+                None => vec![Span::styled("Fills an empty slot", theme.muted)],
+            }));
+
+            // Stats add up slot by slot, so the swap changes each one by the difference.
+            let swap = |bonus: fn(&Equipment) -> i32| bonus(eq) - replaces.map_or(0, bonus);
+            lines.push(delta_line("ATK", p.attack(), p.attack() + swap(|e| e.effective(e.atk_bonus)), theme));
+            lines.push(delta_line("DEF", p.defense(), p.defense() + swap(|e| e.effective(e.def_bonus)), theme));
+            lines.push(delta_line("SPD", p.speed(), p.speed() + swap(|e| e.effective(e.speed_bonus)), theme));
+            lines.push(delta_line("HP", p.max_hp, p.max_hp + swap(|e| e.hp_bonus), theme));
+        }
+        InvSelection::Consumable(i) if inv.tab == InvTab::Consumables => {
+            let Some(stack) = inv.consumables.get(i) else { return lines };
+            let c = &stack.item;
+            lines.push(Line::from(Span::styled("If used", theme.accent)));
+            lines.push(delta_line("HP", p.hp, (p.hp + c.heal).min(p.max_hp), theme));
+            if c.atk_bonus != 0 {
+                lines.push(delta_line("ATK", p.attack(), p.attack() + c.atk_bonus, theme));
+            }
+            if c.def_bonus != 0 {
+                lines.push(delta_line("DEF", p.defense(), p.defense() + c.def_bonus, theme));
+            }
+            if c.atk_bonus != 0 || c.def_bonus != 0 {
+                lines.push(Line::from(Span::styled("  for 30 sec", theme.muted)));
+            }
+            if c.repair > 0 {
+                let target = world.most_worn().and_then(|sel| world.gear_at(&sel));
+                lines.push(Line::from(match target {
+                    Some(eq) => vec![
+                        Span::raw(format!("  Mends {}:", eq.unbroken_name())),
+                        durability_span(eq, theme),
+                        Span::styled(
+                            format!(" -> {}", (eq.durability + c.repair).min(eq.max_durability)),
+                            theme.good,
+                        ),
+                    ],
+                    None => vec![Span::styled("  Nothing needs mending.", theme.muted)],
+                }));
+            }
+        }
+        _ => {}
+    }
+    lines
+}
+
+/// " 12/20", coloured by how close the item is to breaking.
+fn durability_span(eq: &Equipment, theme: &Theme) -> Span<'static> {
+    let style = if eq.is_broken() {
+        theme.danger
+    } else if eq.is_damaged() {
+        theme.warning
+    } else {
+        theme.muted
+    };
+    Span::styled(format!(" {}/{}", eq.durability, eq.max_durability), style)
+}
+
+fn shop_tab_label(tab: ShopTab, active: ShopTab, theme: &Theme) -> Span<'static> {
+    if tab == active {
+        Span::styled(format!("[{}]", tab.name()), theme.title)
+    } else {
+        Span::styled(format!(" {} ", tab.name()), theme.muted)
+    }
+}
+
+/// Tajer's counter: buy, sell and buyback lists with prices, drawn over the map.
+fn draw_shop(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
+    let shop = &world.shop;
+    let speaker = Speaker::Npc(NpcId::Tajer);
+    let accent = speaker_style(speaker, theme);
+
+    let w = area.width.saturating_sub(2).min(64);
+    let h = area.height.saturating_sub(2).min(16);
+    let popup = Rect {
+        x: area.x + (area.width - w) / 2,
+        y: area.y + (area.height - h) / 2,
+        width: w,
+        height: h,
+    };
+    f.render_widget(Clear, popup);
+
+    let block = theme
+        .block()
+        .title(Span::styled(" Tajer's Wares ", accent.add_modifier(Modifier::BOLD)))
+        .border_style(accent);
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(PORTRAIT_W + 1),
+            Constraint::Min(10),
+        ])
+        .split(inner);
+
+    let face: Vec<Line> = portrait(speaker)
+        .iter()
+        .map(|row| Line::from(Span::styled(row.to_string(), accent)))
+        .collect();
+    f.render_widget(Paragraph::new(face), columns[0]);
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(vec![
+            shop_tab_label(ShopTab::Buy, shop.tab, theme),
+            Span::raw(" "),
+            shop_tab_label(ShopTab::Sell, shop.tab, theme),
+            Span::raw(" "),
+            shop_tab_label(ShopTab::Buyback, shop.tab, theme),
+            Span::raw(" "),
+            shop_tab_label(ShopTab::Repair, shop.tab, theme),
+        ]),
+        Line::from(vec![
+            Span::styled("Gold: ", theme.text),
+            Span::styled(world.player.gold.to_string(), theme.warning),
+        ]),
+        Line::from(""),
+    ];
+
+    let entries = world.shop_entries();
+    // Header, footer and blank lines take 6 rows; scroll the rest to keep the cursor visible.
+    let rows = (columns[1].height as usize).saturating_sub(6).max(1);
+    let first = shop.cursor.saturating_sub(rows - 1);
+    let list_w = columns[1].width.saturating_sub(1) as usize;
+
+    if entries.is_empty() {
+        let empty = match shop.tab {
+            ShopTab::Buy => "Sold out.",
+            ShopTab::Sell => "You have nothing to sell.",
+            ShopTab::Buyback => "Nothing sold recently.",
+            ShopTab::Repair => "Nothing needs mending.",
+        };
+        lines.push(Line::from(Span::styled(empty, theme.muted)));
+    }
+    for (i, (item, price)) in entries.iter().enumerate().skip(first).take(rows) {
+        let selected = i == shop.cursor;
+        let marker = if selected { "> " } else { "  " };
+        let style = match item {
+            SlotItem::Gear(eq) => theme.rarity(eq.rarity),
+            SlotItem::Stack(_) => theme.text,
+        };
+        let style = if selected { style.add_modifier(Modifier::BOLD) } else { style };
+        // Red when you can't afford it; selling always pays.
+        let price_style = if shop.tab != ShopTab::Sell && world.player.gold < *price { theme.danger } else { theme.warning };
+        let price = format!("{}g", price);
+        let label = item.label();
+        let wear = match item {
+            SlotItem::Gear(eq) if eq.durability < eq.max_durability => durability_span(eq, theme),
+            _ => Span::raw(""),
+        };
+        let pad = list_w.saturating_sub(marker.len() + label.chars().count() + wear.width() + price.len());
+        lines.push(Line::from(vec![
+            Span::styled(marker, theme.accent),
+            Span::styled(label, style),
+            wear,
+            Span::raw(" ".repeat(pad)),
+            Span::styled(price, price_style),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    let action = match shop.tab {
+        ShopTab::Buy | ShopTab::Buyback => "SPACE: buy",
+        ShopTab::Sell => "SPACE: sell one",
+        ShopTab::Repair => "SPACE: repair",
+    };
+    lines.push(Line::from(Span::styled(
+        format!("{}   T: tab   Esc: leave", action),
+        theme.muted.add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines), columns[1]);
+}
+
+/// " x3" for stacks, nothing for single items.
+fn fmt_qty(qty: u32) -> String {
+    if qty > 1 { format!(" x{}", qty) } else { String::new() }
+}
+
+fn draw_sidebar(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
     f.render_widget(Clear, area);
 
     let p = &world.player;
@@ -364,186 +1019,249 @@ fn draw_sidebar(f: &mut Frame, area: Rect, world: &World) {
 
     let mut text: Vec<Line> = vec![
         Line::from(vec![
-            Span::styled("HP: ", Style::default().fg(Color::White)),
+            Span::styled("HP: ", theme.text),
             Span::styled(
                 format!("{}/{}", p.hp, p.max_hp),
-                Style::default().fg(Color::Green),
+                theme.good,
             ),
         ]),
         Line::from(format!("ATK: {}", p.attack())),
         Line::from(format!("DEF: {}", p.defense())),
         Line::from(format!("SPD: {}", p.speed())),
+        Line::from(vec![
+            Span::styled("Gold: ", theme.text),
+            Span::styled(p.gold.to_string(), theme.warning),
+        ]),
+    ];
+    if !inv.keys.is_empty() {
+        text.push(Line::from(vec![
+            Span::styled("Keys: ", theme.text),
+            Span::styled(inv.keys.join(", "), theme.locked),
+        ]));
+    }
+    text.extend([
         Line::from(format!("Pos: ({}, {})", p.x, p.y)),
         Line::from(format!("Room: {}", room_label)),
-        Line::from(""),
-    ];
+    ]);
+    if let Some(ground) = world.current_map().get(p.x as usize, p.y as usize).terrain_name() {
+        text.push(Line::from(format!("Ground: {}", ground)));
+    }
+    text.push(Line::from(""));
 
     if world.inventory_open {
         text.push(Line::from(Span::styled(
             "Inventory",
-            Style::default().fg(Color::Cyan),
+            theme.accent,
         )));
 
         text.push(Line::from(vec![
-            tab_label(InvTab::Weapons, inv.tab, "Weapons"),
+            tab_label(InvTab::Gear, inv.tab, "Gear", theme),
             Span::raw(" "),
-            tab_label(InvTab::Consumables, inv.tab, "Consumables"),
+            tab_label(InvTab::Consumables, inv.tab, "Consumables", theme),
             Span::raw(" "),
-            tab_label(InvTab::Backpack, inv.tab, "Backpack"),
+            tab_label(InvTab::Backpack, inv.tab, "Backpack", theme),
+            Span::raw(" "),
+            tab_label(InvTab::Crafting, inv.tab, "Craft", theme),
         ]));
-        text.push(Line::from(""));
 
-        text.push(Line::from(Span::styled(
-            "Weapons",
-            Style::default().fg(Color::White),
-        )));
+        let full = inv.free_slots() == 0;
+        let mut usage = vec![Span::styled(
+            format!("Slots: {}/{}", inv.slots_used(), inv.capacity),
+            if full { theme.warning } else { theme.muted },
+        )];
+        if let Some(max) = inv.max_weight {
+            let heavy = inv.weight() >= max;
+            usage.push(Span::styled(
+                format!("   Weight: {}/{}", inv.weight(), max),
+                if heavy { theme.warning } else { theme.muted },
+            ));
+        }
+        text.push(Line::from(usage));
+        text.push(Line::from(""));
 
-        let sword_marker = if inv.tab == InvTab::Weapons
-            && matches!(inv.selection(), InvSelection::SwordSlot)
-        {
-            ">"
+        if inv.tab == InvTab::Crafting {
+            text.extend(crafting_lines(world, theme));
         } else {
-            " "
-        };
-
-        let sword_line = match &inv.sword {
-            Some(sw) => {
-                if inv.tab == InvTab::Weapons
-                    && matches!(inv.selection(), InvSelection::SwordSlot)
-                {
-                    format!(
-                        "{} Sword : {} ({} ATK, {} DEF, {} SPD, {} HP) [Space to unequip]",
-                        sword_marker,
-                        sw.name,
-                        fmt_bonus(sw.atk_bonus),
-                        fmt_bonus(sw.def_bonus),
-                        fmt_bonus(sw.speed_bonus),
-                        fmt_bonus(sw.hp_bonus),
-                    )
-                } else {
-                    format!("{} Sword : {}", sword_marker, sw.name)
-                }
+            text.push(Line::from(Span::styled(
+                "Gear (Space to unequip)",
+                theme.text,
+            )));
+
+            for slot in GearSlot::ALL {
+                let selected = inv.tab == InvTab::Gear
+                    && matches!(inv.selection(), InvSelection::Gear(s) if s == slot);
+                let marker = if selected { ">" } else { " " };
+                let label = format!("{} {:<7}: ", marker, slot.name());
+
+                let line = match inv.equipped(slot) {
+                    Some(eq) if selected => Line::from(vec![
+                        Span::raw(label),
+                        Span::styled(eq.name.clone(), theme.rarity(eq.rarity)),
+                        durability_span(eq, theme),
+                        Span::raw(format!(
+                            " ({} ATK, {} DEF, {} SPD, {} HP)",
+                            fmt_bonus(eq.effective(eq.atk_bonus)),
+                            fmt_bonus(eq.effective(eq.def_bonus)),
+                            fmt_bonus(eq.effective(eq.speed_bonus)),
+                            fmt_bonus(eq.hp_bonus),
+                        )),
+                    ]),
+                    Some(eq) => Line::from(vec![
+                        Span::raw(label),
+                        Span::styled(eq.name.clone(), theme.rarity(eq.rarity)),
+                        durability_span(eq, theme),
+                    ]),
+                    None => Line::from(vec![Span::raw(label), Span::styled("<empty>", theme.muted)]),
+                };
+                text.push(line);
             }
-            None => format!("{} Sword : <empty>", sword_marker),
-        };
-        text.push(Line::from(sword_line));
-
-        let shield_marker = if inv.tab == InvTab::Weapons
-            && matches!(inv.selection(), InvSelection::ShieldSlot)
-        {
-            ">"
-        } else {
-            " "
-        };
 
-        let shield_line = match &inv.shield {
-            Some(sh) => {
-                if inv.tab == InvTab::Weapons
-                    && matches!(inv.selection(), InvSelection::ShieldSlot)
-                {
-                    format!(
-                        "{} Shield: {} ({} ATK, {} DEF, {} SPD, {} HP) [Space to unequip]",
-                        shield_marker,
-                        sh.name,
-                        fmt_bonus(sh.atk_bonus),
-                        fmt_bonus(sh.def_bonus),
-                        fmt_bonus(sh.speed_bonus),
-                        fmt_bonus(sh.hp_bonus),
-                    )
-                } else {
-                    format!("{} Shield: {}", shield_marker, sh.name)
+            text.push(Line::from(""));
+
+            text.push(Line::from(vec![
+                Span::styled("Consumables (Space to use)", theme.text),
+                view_label(inv.consumable_view, theme),
+            ]));
+
+            let shown = inv.visible_consumables();
+            if shown.is_empty() {
+                let marker = if inv.tab == InvTab::Consumables { ">" } else { " " };
+                text.push(Line::from(format!("{} <none>", marker)));
+            } else {
+                for &i in &shown {
+                    let stack = &inv.consumables[i];
+                    let c = &stack.item;
+                    let qty = fmt_qty(stack.qty);
+                    let selected = inv.tab == InvTab::Consumables
+                        && matches!(inv.selection(), InvSelection::Consumable(idx) if idx == i);
+
+                    let marker = if selected { ">" } else { " " };
+
+                    if selected {
+                        text.push(Line::from(format!(
+                            "{} {}{} ({} HP, {} ATK, {} DEF) [Space to use]",
+                            marker,
+                            c.name,
+                            qty,
+                            fmt_bonus(c.heal),
+                            fmt_bonus(c.atk_bonus),
+                            fmt_bonus(c.def_bonus),
+                        )));
+                    } else {
+                        text.push(Line::from(format!("{} {}{}", marker, c.name, qty)));
+                    }
                 }
             }
-            None => format!("{} Shield: <empty>", shield_marker),
-        };
-        text.push(Line::from(shield_line));
-
-        text.push(Line::from(""));
 
-        text.push(Line::from(Span::styled(
-            "Consumables (Space to use)",
-            Style::default().fg(Color::White),
-        )));
-
-        if inv.consumables.is_empty() {
-            let marker = if inv.tab == InvTab::Consumables { ">" } else { " " };
-            text.push(Line::from(format!("{} <none>", marker)));
-        } else {
-            for (i, c) in inv.consumables.iter().enumerate() {
-                let selected = inv.tab == InvTab::Consumables
-                    && matches!(inv.selection(), InvSelection::Consumable(idx) if idx == i);
-
-                let marker = if selected { ">" } else { " " };
-
-                if selected {
-                    text.push(Line::from(format!(
-                        "{} {} ({} HP, {} ATK, {} DEF) [Space to use]",
-                        marker,
-                        c.name,
-                        fmt_bonus(c.heal),
-                        fmt_bonus(c.atk_bonus),
-                        fmt_bonus(c.def_bonus),
-                    )));
-                } else {
-                    text.push(Line::from(format!("{} {}", marker, c.name)));
+            text.push(Line::from(""));
+
+            text.push(Line::from(vec![
+                Span::styled("Backpack (Space to equip)", theme.text),
+                view_label(inv.backpack_view, theme),
+            ]));
+
+            let shown = inv.visible_backpack();
+            if shown.is_empty() {
+                let marker = if inv.tab == InvTab::Backpack { ">" } else { " " };
+                text.push(Line::from(format!("{} <empty>", marker)));
+            } else {
+                for &i in &shown {
+                    let b = &inv.backpack[i];
+                    let marker = if inv.tab == InvTab::Backpack
+                        && matches!(inv.selection(), InvSelection::BackpackItem(idx) if idx == i)
+                    {
+                        ">"
+                    } else {
+                        " "
+                    };
+                    text.push(Line::from(vec![
+                        Span::raw(format!("{} ", marker)),
+                        Span::styled(b.name.clone(), theme.rarity(b.rarity)),
+                        durability_span(b, theme),
+                    ]));
                 }
             }
         }
 
-        let empty_slots = 10usize.saturating_sub(inv.consumables.len());
-        text.push(Line::from(format!("Empty slots: {}", empty_slots)));
-
-        text.push(Line::from(""));
-
-        text.push(Line::from(Span::styled(
-            "Backpack (Space to equip)",
-            Style::default().fg(Color::White),
-        )));
-
-        if inv.backpack.is_empty() {
-            let marker = if inv.tab == InvTab::Backpack { ">" } else { " " };
-            text.push(Line::from(format!("{} <empty>", marker)));
-        } else {
-            for (i, b) in inv.backpack.iter().enumerate() {
-                let marker = if inv.tab == InvTab::Backpack
-                    && matches!(inv.selection(), InvSelection::BackpackItem(idx) if idx == i)
-                {
-                    ">"
-                } else {
-                    " "
-                };
-                text.push(Line::from(format!("{} {}", marker, b.name)));
-            }
+        let preview = compare_lines(world, theme);
+        if !preview.is_empty() {
+            text.push(Line::from(""));
+            text.extend(preview);
         }
 
         text.push(Line::from(""));
         text.push(Line::from("Up/Down: select"));
         text.push(Line::from("T: change tab"));
         text.push(Line::from("Space: use/unequip/equip"));
+        text.push(Line::from("E: inspect   D: drop"));
+        text.push(Line::from("O: sort   F: filter"));
+        text.push(Line::from("C: combine (Craft tab)"));
         text.push(Line::from("I or Esc: close"));
         text.push(Line::from("Q: stats"));
     } else {
         text.push(Line::from(Span::styled(
             "Controls",
-            Style::default().fg(Color::Cyan),
+            theme.accent,
         )));
         text.push(Line::from("WASD / Arrows: Move"));
         text.push(Line::from("E: Talk / Open chest"));
         text.push(Line::from("I: Inventory"));
         text.push(Line::from("T: Inventory Tab"));
         text.push(Line::from("Q: Stats"));
+        text.push(Line::from("M: Overview map"));
         text.push(Line::from("Ctrl+C: Quit"));
         text.push(Line::from("E on +: Switch rooms"));
     }
 
     let sidebar = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("Player"))
+        .block(theme.block().title("Player"))
         .wrap(Wrap { trim: true });
 
     f.render_widget(sidebar, area);
 }
 
-fn draw_logs(f: &mut Frame, area: Rect, world: &World) {
+/// The Crafting tab: every pack slot with how much of it is marked, then the known recipes.
+fn crafting_lines(world: &World, theme: &Theme) -> Vec<Line<'static>> {
+    let inv = &world.player.inventory;
+    let mut lines = vec![Line::from(Span::styled("Pack (Space to mark, C to combine)", theme.text))];
+
+    let labels = inv.slot_labels();
+    if labels.is_empty() {
+        lines.push(Line::from("> <empty>"));
+    }
+    for (i, label) in labels.into_iter().enumerate() {
+        let marker = if i == inv.crafting_cursor { ">" } else { " " };
+        let marked = inv.craft_marks.get(i).copied().unwrap_or(0);
+        let (check, style) = match marked {
+            0 => ("[ ]".to_string(), theme.text),
+            1 if i >= inv.consumables.len() => ("[x]".to_string(), theme.good),
+            n => (format!("[{}]", n), theme.good),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{} ", marker)),
+            Span::styled(check, style),
+            Span::raw(" "),
+            Span::styled(label, style),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("Known recipes", theme.text)));
+    if world.known_recipes.is_empty() {
+        lines.push(Line::from(Span::styled("  None yet. Try combining things.", theme.muted)));
+    }
+    for &idx in &world.known_recipes {
+        let recipe = &crafting::recipes()[idx];
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {}", recipe.inputs_label()), theme.muted),
+            Span::raw(" => "),
+            Span::styled(recipe.output_name().to_string(), theme.accent),
+        ]));
+    }
+    lines
+}
+
+fn draw_logs(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
     f.render_widget(Clear, area);
 
     let mut lines: Vec<Line> = Vec::new();
@@ -552,60 +1270,151 @@ fn draw_logs(f: &mut Frame, area: Rect, world: &World) {
     }
 
     let logs = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("Log"))
+        .block(theme.block().title("Log"))
         .wrap(Wrap { trim: true });
 
     f.render_widget(logs, area);
 }
 
-fn draw_stats(f: &mut Frame, area: Rect, world: &World) {
+fn draw_stats(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
     let p = &world.player;
     let inv = &p.inventory;
 
-    let sword = inv.sword.as_ref().map(|s| s.name.as_str()).unwrap_or("<empty>");
-    let shield = inv.shield.as_ref().map(|s| s.name.as_str()).unwrap_or("<empty>");
-
-    let lines = vec![
+    let mut lines = vec![
         Line::from(Span::styled(
             "Current Stats",
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            theme.title,
         )),
         Line::from(""),
         Line::from(format!("HP  : {}/{}", p.hp, p.max_hp)),
         Line::from(format!("ATK : {}", p.attack())),
         Line::from(format!("DEF : {}", p.defense())),
         Line::from(format!("SPD : {}", p.speed())),
+        Line::from(format!("Gold: {}", p.gold)),
         Line::from(""),
-        Line::from(format!("Sword : {}", sword)),
-        Line::from(format!("Shield: {}", shield)),
+    ];
+    for slot in GearSlot::ALL {
+        let name = inv.equipped(slot).map(|e| e.name.as_str()).unwrap_or("<empty>");
+        lines.push(Line::from(format!("{:<7}: {}", slot.name(), name)));
+    }
+    lines.extend([
+        Line::from(format!("Difficulty: {}", world.settings.difficulty.name())),
+        Line::from(format!("Theme: {}", theme.name.name())),
         Line::from(""),
         Line::from(Span::styled(
             "Press Q or Esc to close.",
-            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            theme.muted.add_modifier(Modifier::ITALIC),
         )),
-    ];
+    ]);
 
     let stats = Paragraph::new(lines)
         .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL).title("Stats"))
+        .block(theme.block().title("Stats"))
         .wrap(Wrap { trim: true });
 
     f.render_widget(stats, area);
 }
 
-fn draw_dialogue(f: &mut Frame, area: Rect, world: &World) {
-    let d = world.dialogue.as_ref().unwrap();
-    let page_text = &d.pages[d.page_index];
+fn tint_style(tint: Tint, theme: &Theme) -> Style {
+    match tint {
+        Tint::Red => theme.danger,
+        Tint::Gold => theme.title,
+        Tint::Blue => theme.accent,
+        Tint::Green => theme.good,
+        Tint::Grey => theme.muted,
+    }
+}
 
+/// Styled lines for a marked-up page, cut off after `budget` visible characters.
+fn markup_lines(src: &str, budget: usize, base: Style, theme: &Theme) -> Vec<Line<'static>> {
     let mut lines: Vec<Line> = Vec::new();
-    for raw_line in page_text.lines() {
-        lines.push(Line::from(raw_line.to_string()));
+    let mut current: Vec<Span> = Vec::new();
+    let mut left = budget;
+
+    for seg in markup::parse(src) {
+        let mut style = seg.tint.map(|t| tint_style(t, theme)).unwrap_or(base);
+        if seg.bold { style = style.add_modifier(Modifier::BOLD); }
+        if seg.italic { style = style.add_modifier(Modifier::ITALIC); }
+
+        let shown: String = seg.text.chars().take(left).collect();
+        left -= shown.chars().count();
+
+        let mut parts = shown.split('\n').peekable();
+        while let Some(part) = parts.next() {
+            if !part.is_empty() { current.push(Span::styled(part.to_string(), style)); }
+            if parts.peek().is_some() { lines.push(Line::from(std::mem::take(&mut current))); }
+        }
+        if left == 0 { break; }
+    }
+    lines.push(Line::from(current));
+    lines
+}
+
+fn speaker_style(speaker: Speaker, theme: &Theme) -> Style {
+    match speaker {
+        Speaker::Npc(id) => theme.npc(id),
+        Speaker::Chest => theme.chest,
+        Speaker::Sign => theme.object,
+    }
+}
+
+fn draw_dialogue(f: &mut Frame, area: Rect, world: &World, theme: &Theme) {
+    let Some(d) = world.dialogue.as_ref() else { return };
+    let accent = speaker_style(d.speaker, theme);
+
+    let block = theme
+        .block()
+        .title(Span::styled(format!(" {} ", d.title), accent.add_modifier(Modifier::BOLD)))
+        .border_style(accent);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Length(PORTRAIT_W + 2),
+            Constraint::Min(10),
+        ])
+        .split(inner);
+
+    let face: Vec<Line> = portrait(d.speaker)
+        .iter()
+        .map(|row| Line::from(Span::styled(row.to_string(), accent)))
+        .collect();
+    f.render_widget(
+        Paragraph::new(face).block(theme.block().border_style(accent)),
+        columns[0],
+    );
+
+    let mut lines = markup_lines(d.page(), d.revealed, theme.text, theme);
+
+    let choices = match &d.awaiting {
+        Some(choice) if d.on_last_page() && d.fully_revealed() => choice.options(),
+        _ => Vec::new(),
+    };
+    if !choices.is_empty() {
+        lines.push(Line::from(""));
+        // Long lists (e.g. picking what to drop from a full pack) go in two columns.
+        let columns_n = if choices.len() > 4 { 2 } else { 1 };
+        let col_w = (columns[1].width.saturating_sub(2) / columns_n as u16) as usize;
+        for row in choices.chunks(columns_n) {
+            let mut spans = Vec::new();
+            for (key, label) in row {
+                let key = format!(" [{}] ", key);
+                let pad = col_w.saturating_sub(key.len() + label.chars().count());
+                spans.push(Span::styled(key, accent.add_modifier(Modifier::BOLD)));
+                spans.push(Span::styled(format!("{}{}", label, " ".repeat(pad)), theme.text));
+            }
+            lines.push(Line::from(spans));
+        }
     }
 
-    let footer = if d.page_index + 1 < d.pages.len() {
+    let footer = if !d.fully_revealed() {
+        "Press SPACE to skip..."
+    } else if !d.on_last_page() {
         "Press SPACE to continue..."
     } else if d.awaiting.is_some() {
-        "Enter your choice (letter)..."
+        "Press a letter to choose."
     } else {
         "Press SPACE to close."
     };
@@ -613,12 +1422,9 @@ fn draw_dialogue(f: &mut Frame, area: Rect, world: &World) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         footer,
-        Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        theme.muted.add_modifier(Modifier::ITALIC),
     )));
 
-    let dialog = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title(d.title.clone()))
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(dialog, area);
-}
\ No newline at end of file
+    let text = Paragraph::new(lines).wrap(Wrap { trim: true });
+    f.render_widget(text, columns[1].inner(&ratatui::layout::Margin { horizontal: 1, vertical: 0 }));
+}
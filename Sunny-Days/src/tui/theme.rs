@@ -0,0 +1,421 @@
+use crate::engine::entity::Rarity;
+use crate::engine::world::NpcId;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    symbols::{border, line},
+    widgets::{Block, Borders},
+};
+
+/// The palettes to pick from with `--theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeName {
+    /// The full-colour palette.
+    Default,
+    /// Bright, bold colours.
+    HighContrast,
+    /// Blue/orange instead of red/green.
+    Deuteranopia,
+    /// No colour at all, only bold/dim/reverse.
+    Monochrome,
+}
+
+impl ThemeName {
+    /// Every theme, in the order an unknown `--theme` error lists them.
+    pub const ALL: [ThemeName; 4] = [
+        ThemeName::Default,
+        ThemeName::HighContrast,
+        ThemeName::Deuteranopia,
+        ThemeName::Monochrome,
+    ];
+
+    /// Parses a `--theme` value; a few aliases are accepted. Case doesn't matter.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "default" => Some(ThemeName::Default),
+            "high-contrast" | "highcontrast" => Some(ThemeName::HighContrast),
+            "deuteranopia" | "colourblind" | "colorblind" => Some(ThemeName::Deuteranopia),
+            "monochrome" | "mono" => Some(ThemeName::Monochrome),
+            _ => None,
+        }
+    }
+
+    /// The canonical `--theme` value.
+    pub fn name(self) -> &'static str {
+        match self {
+            ThemeName::Default => "default",
+            ThemeName::HighContrast => "high-contrast",
+            ThemeName::Deuteranopia => "deuteranopia",
+            ThemeName::Monochrome => "monochrome",
+        }
+    }
+}
+
+/// Characters used to draw the world and the UI chrome.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyphs {
+    pub ascii: bool,
+    pub player: char,
+    pub villager: char,
+    pub wall: char,
+    pub door: char,
+    pub chest: char,
+    pub pickup: char,
+    pub locked_door: char,
+    pub gate: char,
+    pub lever: char,
+    pub lever_pulled: char,
+    pub cracked_wall: char,
+    pub fountain: char,
+    pub trap: char,
+    pub sign: char,
+    pub water: char,
+    pub grass: char,
+    pub lava: char,
+    pub ice: char,
+    pub dark: char,
+    pub border: border::Set,
+    pub line: line::Set,
+}
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+const ASCII_LINE: line::Set = line::Set {
+    vertical: "|",
+    horizontal: "=",
+    top_right: "+",
+    top_left: "+",
+    bottom_right: "+",
+    bottom_left: "+",
+    vertical_left: "+",
+    vertical_right: "+",
+    horizontal_down: "+",
+    horizontal_up: "+",
+    cross: "+",
+};
+
+impl Glyphs {
+    pub const UNICODE: Glyphs = Glyphs {
+        ascii: false,
+        player: '@',
+        villager: '●',
+        wall: '#',
+        door: '+',
+        chest: 'C',
+        pickup: '*',
+        locked_door: '▣',
+        gate: '╫',
+        lever: '⌐',
+        lever_pulled: '¬',
+        cracked_wall: '▒',
+        fountain: 'Ω',
+        trap: '^',
+        sign: '¶',
+        water: '≈',
+        grass: '"',
+        lava: '≋',
+        ice: '·',
+        dark: '░',
+        border: border::PLAIN,
+        line: line::NORMAL,
+    };
+
+    pub const ASCII: Glyphs = Glyphs {
+        ascii: true,
+        player: '@',
+        villager: 'o',
+        wall: '#',
+        door: '+',
+        chest: 'C',
+        pickup: '*',
+        locked_door: '&',
+        gate: '=',
+        lever: '/',
+        lever_pulled: '\\',
+        cracked_wall: '%',
+        fountain: 'Y',
+        trap: '^',
+        sign: '?',
+        water: '~',
+        grass: '"',
+        lava: '!',
+        ice: '.',
+        dark: ':',
+        border: ASCII_BORDER,
+        line: ASCII_LINE,
+    };
+}
+
+/// A named palette plus glyph set; every draw function styles through this.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub(crate) name: ThemeName,
+    pub(crate) glyphs: Glyphs,
+
+    // UI chrome
+    pub(crate) title: Style,
+    pub(crate) text: Style,
+    pub(crate) muted: Style,
+    pub(crate) accent: Style,
+    pub(crate) good: Style,
+    pub(crate) warning: Style,
+    pub(crate) danger: Style,
+    pub(crate) deflect: Style,
+    pub(crate) gauge_bg: Color,
+
+    // Map
+    pub(crate) player: Style,
+    pub(crate) villager: Style,
+    pub(crate) weeping: Style,
+    pub(crate) mayor: Style,
+    pub(crate) noor: Style,
+    pub(crate) enemy: Style,
+    pub(crate) wall: Style,
+    pub(crate) floor: Style, // explored floor on the overview map
+    pub(crate) door: Style,
+    pub(crate) chest: Style,
+    pub(crate) pickup: Style, // items dropped on the floor
+    pub(crate) locked: Style, // locked doors and closed gates
+    pub(crate) object: Style, // levers, fountains, signs
+    pub(crate) trap: Style,
+    pub(crate) water: Style,
+    pub(crate) grass: Style,
+    pub(crate) lava: Style,
+    pub(crate) ice: Style,
+    pub(crate) dark: Style,
+
+    /// Item names by rarity, Common through Legendary.
+    pub(crate) rarities: [Style; 5],
+}
+
+impl Theme {
+    /// The palette for `name`, drawn with ASCII glyphs if `ascii`.
+    pub fn new(name: ThemeName, ascii: bool) -> Self {
+        let glyphs = if ascii { Glyphs::ASCII } else { Glyphs::UNICODE };
+        let fg = |c: Color| Style::default().fg(c);
+        let bold = Modifier::BOLD;
+
+        match name {
+            ThemeName::Default => Self {
+                name,
+                glyphs,
+                title: fg(Color::Yellow).add_modifier(bold),
+                text: fg(Color::White),
+                muted: fg(Color::DarkGray),
+                accent: fg(Color::Cyan),
+                good: fg(Color::Green),
+                warning: fg(Color::Yellow),
+                danger: fg(Color::Red),
+                deflect: fg(Color::Cyan),
+                gauge_bg: Color::Black,
+                player: fg(Color::Yellow),
+                villager: fg(Color::Yellow).add_modifier(bold),
+                weeping: fg(Color::LightBlue).add_modifier(bold),
+                mayor: fg(Color::Cyan).add_modifier(bold),
+                noor: fg(Color::Magenta).add_modifier(bold),
+                enemy: fg(Color::Red).add_modifier(bold),
+                wall: fg(Color::DarkGray),
+                floor: fg(Color::Gray),
+                door: fg(Color::White),
+                chest: fg(Color::Green).add_modifier(bold),
+                pickup: fg(Color::LightYellow),
+                locked: fg(Color::LightRed).add_modifier(bold),
+                object: fg(Color::LightCyan),
+                trap: fg(Color::Red),
+                water: fg(Color::Blue),
+                grass: fg(Color::Green),
+                lava: fg(Color::LightRed).add_modifier(bold),
+                ice: fg(Color::LightCyan),
+                dark: fg(Color::DarkGray),
+                rarities: [
+                    fg(Color::White),
+                    fg(Color::Green),
+                    fg(Color::LightBlue),
+                    fg(Color::Magenta),
+                    fg(Color::Rgb(255, 140, 0)).add_modifier(bold),
+                ],
+            },
+            ThemeName::HighContrast => Self {
+                name,
+                glyphs,
+                title: fg(Color::LightYellow).add_modifier(bold),
+                text: fg(Color::White).add_modifier(bold),
+                muted: fg(Color::Gray),
+                accent: fg(Color::LightCyan).add_modifier(bold),
+                good: fg(Color::LightGreen).add_modifier(bold),
+                warning: fg(Color::LightYellow).add_modifier(bold),
+                danger: fg(Color::LightRed).add_modifier(bold),
+                deflect: fg(Color::LightCyan).add_modifier(bold),
+                gauge_bg: Color::Black,
+                player: fg(Color::Black).bg(Color::LightYellow).add_modifier(bold),
+                villager: fg(Color::LightYellow).add_modifier(bold),
+                weeping: fg(Color::LightBlue).add_modifier(bold),
+                mayor: fg(Color::LightCyan).add_modifier(bold),
+                noor: fg(Color::LightMagenta).add_modifier(bold),
+                enemy: fg(Color::White).bg(Color::Red).add_modifier(bold),
+                wall: fg(Color::White),
+                floor: fg(Color::DarkGray),
+                door: fg(Color::Black).bg(Color::White).add_modifier(bold),
+                chest: fg(Color::Black).bg(Color::LightGreen).add_modifier(bold),
+                pickup: fg(Color::LightYellow).add_modifier(bold),
+                locked: fg(Color::Black).bg(Color::LightRed).add_modifier(bold),
+                object: fg(Color::LightCyan).add_modifier(bold),
+                trap: fg(Color::LightRed).add_modifier(bold),
+                water: fg(Color::LightBlue).add_modifier(bold),
+                grass: fg(Color::LightGreen),
+                lava: fg(Color::Black).bg(Color::LightRed).add_modifier(bold),
+                ice: fg(Color::White).add_modifier(bold),
+                dark: fg(Color::Gray),
+                rarities: [
+                    fg(Color::White),
+                    fg(Color::LightGreen).add_modifier(bold),
+                    fg(Color::LightCyan).add_modifier(bold),
+                    fg(Color::LightMagenta).add_modifier(bold),
+                    fg(Color::Black).bg(Color::LightYellow).add_modifier(bold),
+                ],
+            },
+            ThemeName::Deuteranopia => {
+                // Okabe-Ito palette: distinguishable without relying on red vs green.
+                let blue = Color::Rgb(0, 114, 178);
+                let sky = Color::Rgb(86, 180, 233);
+                let orange = Color::Rgb(230, 159, 0);
+                let vermillion = Color::Rgb(213, 94, 0);
+                let yellow = Color::Rgb(240, 228, 66);
+                let purple = Color::Rgb(204, 121, 167);
+                Self {
+                    name,
+                    glyphs,
+                    title: fg(yellow).add_modifier(bold),
+                    text: fg(Color::White),
+                    muted: fg(Color::DarkGray),
+                    accent: fg(sky),
+                    good: fg(blue),
+                    warning: fg(orange),
+                    danger: fg(vermillion),
+                    deflect: fg(sky),
+                    gauge_bg: Color::Black,
+                    player: fg(yellow),
+                    villager: fg(yellow).add_modifier(bold),
+                    weeping: fg(sky).add_modifier(bold),
+                    mayor: fg(blue).add_modifier(bold),
+                    noor: fg(purple).add_modifier(bold),
+                    enemy: fg(vermillion).add_modifier(bold),
+                    wall: fg(Color::DarkGray),
+                    floor: fg(Color::Gray),
+                    door: fg(Color::White),
+                    chest: fg(orange).add_modifier(bold),
+                    pickup: fg(yellow),
+                    locked: fg(vermillion).add_modifier(bold),
+                    object: fg(sky),
+                    trap: fg(vermillion),
+                    water: fg(blue),
+                    grass: fg(yellow),
+                    lava: fg(vermillion).add_modifier(bold),
+                    ice: fg(sky),
+                    dark: fg(Color::DarkGray),
+                    rarities: [
+                        fg(Color::White),
+                        fg(sky),
+                        fg(blue).add_modifier(bold),
+                        fg(purple).add_modifier(bold),
+                        fg(orange).add_modifier(bold),
+                    ],
+                }
+            }
+            ThemeName::Monochrome => {
+                let plain = Style::default();
+                Self {
+                    name,
+                    glyphs,
+                    title: plain.add_modifier(bold),
+                    text: plain,
+                    muted: plain.add_modifier(Modifier::DIM),
+                    accent: plain.add_modifier(Modifier::UNDERLINED),
+                    good: plain,
+                    warning: plain.add_modifier(bold),
+                    danger: plain.add_modifier(bold),
+                    deflect: plain.add_modifier(Modifier::REVERSED),
+                    gauge_bg: Color::Reset,
+                    player: plain.add_modifier(Modifier::REVERSED | bold),
+                    villager: plain,
+                    weeping: plain,
+                    mayor: plain.add_modifier(bold),
+                    noor: plain.add_modifier(bold),
+                    enemy: plain.add_modifier(Modifier::UNDERLINED | bold),
+                    wall: plain.add_modifier(Modifier::DIM),
+                    floor: plain,
+                    door: plain.add_modifier(bold),
+                    chest: plain.add_modifier(bold),
+                    pickup: plain,
+                    locked: plain.add_modifier(bold | Modifier::UNDERLINED),
+                    object: plain.add_modifier(bold),
+                    trap: plain.add_modifier(Modifier::REVERSED),
+                    water: plain,
+                    grass: plain,
+                    lava: plain.add_modifier(bold),
+                    ice: plain,
+                    dark: plain.add_modifier(Modifier::DIM),
+                    rarities: [
+                        plain,
+                        plain.add_modifier(Modifier::ITALIC),
+                        plain.add_modifier(bold),
+                        plain.add_modifier(bold | Modifier::UNDERLINED),
+                        plain.add_modifier(bold | Modifier::REVERSED),
+                    ],
+                }
+            }
+        }
+    }
+
+    /// Whether the palette uses colour at all (monochrome relies on modifiers).
+    pub(crate) fn has_colour(&self) -> bool {
+        self.name != ThemeName::Monochrome
+    }
+
+    /// Bordered block drawn with the theme's glyph set.
+    pub(crate) fn block<'a>(&self) -> Block<'a> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(self.glyphs.border)
+    }
+
+    pub(crate) fn rarity(&self, rarity: Rarity) -> Style {
+        self.rarities[rarity as usize]
+    }
+
+    pub(crate) fn npc(&self, id: NpcId) -> Style {
+        match id {
+            NpcId::MayorSol => self.mayor,
+            NpcId::Noor => self.noor,
+            NpcId::Lamp | NpcId::Dorosht | NpcId::Tajer => self.villager,
+            NpcId::Random1 | NpcId::Random2 | NpcId::Random3 => self.villager,
+            NpcId::Weeping1 | NpcId::Weeping2 | NpcId::Weeping3 | NpcId::Weeping4 => self.weeping,
+            NpcId::Shab | NpcId::Krad | NpcId::Mah => self.enemy,
+        }
+    }
+
+    /// Map glyph for an NPC; villagers use the theme's villager glyph so ASCII mode stays ASCII.
+    pub(crate) fn npc_glyph(&self, id: NpcId, symbol: char) -> char {
+        match id {
+            NpcId::Random1 | NpcId::Random2 | NpcId::Random3
+            | NpcId::Weeping1 | NpcId::Weeping2 | NpcId::Weeping3 | NpcId::Weeping4 => self.glyphs.villager,
+            _ if self.glyphs.ascii && !symbol.is_ascii() => '?',
+            _ => symbol,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeName::Default, false)
+    }
+}